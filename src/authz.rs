@@ -0,0 +1,109 @@
+//! Authorization for server functions that take a trip, detail or conversation ID.
+//!
+//! Every such function turns its token into a [`Caller`] and loads the referenced resource
+//! through it. The policy functions decide who may do what; a resource the caller may not
+//! access is reported as not found so IDs of other users' data are not confirmed.
+
+use crate::db::get_client;
+use crate::server::auth::controller::auth;
+use crate::server::auth::model::User;
+use crate::server::conversation::model::Conversation;
+use crate::server::trip::model::{Detail, Trip};
+use bson::doc;
+use bson::oid::ObjectId;
+use dioxus::prelude::ServerFnError;
+use mongodb::Database;
+
+/// What a caller intends to do with a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Modify,
+}
+
+/// Owners may read and modify their trips; admins may read any trip.
+pub fn can_access_trip(user: &User, trip: &Trip, access: Access) -> bool {
+    trip.user == user.id || (access == Access::Read && user.is_admin())
+}
+
+/// A detail is governed by the trip it belongs to.
+pub fn can_access_detail(user: &User, trip: &Trip, detail: &Detail, access: Access) -> bool {
+    detail.trip_id == trip.id && can_access_trip(user, trip, access)
+}
+
+/// Owners may read and write their conversations, and the messages in them; admins may read.
+pub fn can_access_conversation(user: &User, conversation: &Conversation, access: Access) -> bool {
+    conversation.user == user.id || (access == Access::Read && user.is_admin())
+}
+
+/// Parses a client-supplied ID, naming the resource kind in the error.
+pub fn parse_id(id: &str, kind: &str) -> Result<ObjectId, ServerFnError> {
+    ObjectId::parse_str(id).map_err(|_| ServerFnError::new(format!("Invalid {} ID", kind)))
+}
+
+/// An authenticated user, through which server functions load the resources they act on.
+pub struct Caller {
+    pub user: User,
+    db: Database,
+}
+
+impl Caller {
+    pub async fn from_token(token: String) -> Result<Self, ServerFnError> {
+        let user = auth(token)
+            .await
+            .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+        let client = get_client().await;
+        let db = client
+            .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+        Ok(Self { user, db })
+    }
+
+    pub async fn trip(&self, trip_id: ObjectId, access: Access) -> Result<Trip, ServerFnError> {
+        self.db
+            .collection::<Trip>("trips")
+            .find_one(doc! { "_id": trip_id })
+            .await?
+            .filter(|trip| can_access_trip(&self.user, trip, access))
+            .ok_or(ServerFnError::new("Trip not found"))
+    }
+
+    /// Loads a detail together with the trip it belongs to.
+    pub async fn detail(
+        &self,
+        detail_id: ObjectId,
+        access: Access,
+    ) -> Result<(Trip, Detail), ServerFnError> {
+        let not_found = || ServerFnError::new("Detail not found");
+        let detail = self
+            .db
+            .collection::<Detail>("details")
+            .find_one(doc! { "_id": detail_id })
+            .await?
+            .ok_or_else(not_found)?;
+        let trip = self
+            .db
+            .collection::<Trip>("trips")
+            .find_one(doc! { "_id": detail.trip_id })
+            .await?
+            .ok_or_else(not_found)?;
+
+        if !can_access_detail(&self.user, &trip, &detail, access) {
+            return Err(not_found());
+        }
+        Ok((trip, detail))
+    }
+
+    pub async fn conversation(
+        &self,
+        conversation_id: ObjectId,
+        access: Access,
+    ) -> Result<Conversation, ServerFnError> {
+        self.db
+            .collection::<Conversation>("conversations")
+            .find_one(doc! { "_id": conversation_id })
+            .await?
+            .filter(|conversation| can_access_conversation(&self.user, conversation, access))
+            .ok_or(ServerFnError::new("Conversation not found"))
+    }
+}
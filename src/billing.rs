@@ -0,0 +1,578 @@
+use crate::db::get_client;
+use crate::server::auth::model::User;
+use crate::server::billing::model::{CheckoutSession, Subscription, SubscriptionStatus};
+use crate::server::plan::model::Plan;
+use anyhow::{anyhow, bail};
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use bson::{doc, oid::ObjectId, Document};
+use chrono::prelude::*;
+use dioxus_logger::tracing;
+use futures_util::TryStreamExt;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::env;
+use tokio::sync::OnceCell;
+
+const DEFAULT_STRIPE_API_BASE: &str = "https://api.stripe.com";
+/// Oldest webhook signature accepted, matching Stripe's own libraries.
+const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+pub struct CheckoutRequest<'a> {
+    pub user: &'a User,
+    pub plan: Plan,
+    pub success_url: String,
+    pub cancel_url: String,
+}
+
+/// A verified provider event, reduced to what billing acts on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookEvent {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    pub kind: WebhookEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookEventKind {
+    /// A checkout finished; links the provider's customer and subscription to the user.
+    CheckoutCompleted {
+        user: ObjectId,
+        customer: String,
+        subscription: String,
+    },
+    /// A subscription was created, renewed, changed or canceled.
+    SubscriptionUpdated {
+        subscription: String,
+        customer: String,
+        status: SubscriptionStatus,
+        plan: Option<Plan>,
+        user: Option<ObjectId>,
+        current_period_end: Option<DateTime<Utc>>,
+    },
+    /// An event type billing does not act on.
+    Other(String),
+}
+
+pub(crate) trait PaymentProvider {
+    fn name(&self) -> &'static str;
+
+    fn create_checkout_session(
+        &self,
+        checkout: CheckoutRequest<'_>,
+    ) -> impl std::future::Future<Output = anyhow::Result<CheckoutSession>> + Send;
+
+    /// Cancels a subscription right away, without proration or a final invoice.
+    fn cancel_subscription(
+        &self,
+        subscription: &str,
+    ) -> impl std::future::Future<Output = anyhow::Result<()>> + Send;
+
+    /// Checks `signature` against the raw request body and parses the event it carries.
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> anyhow::Result<WebhookEvent>;
+}
+
+/// Talks to the Stripe API, or to any server speaking it such as `stripe-mock`.
+pub struct StripeProvider {
+    api_base: String,
+    secret_key: String,
+    webhook_secret: String,
+    prices: Vec<(Plan, String)>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct StripeCheckoutSession {
+    id: String,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StripeEvent {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    created: i64,
+    data: StripeEventData,
+}
+
+#[derive(Deserialize)]
+struct StripeEventData {
+    object: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct StripeCheckoutObject {
+    mode: Option<String>,
+    client_reference_id: Option<String>,
+    customer: Option<String>,
+    subscription: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StripeSubscriptionObject {
+    id: String,
+    customer: String,
+    status: String,
+    #[serde(default)]
+    metadata: HashMap<String, String>,
+    current_period_end: Option<i64>,
+    items: Option<StripeList<StripeSubscriptionItem>>,
+}
+
+#[derive(Deserialize)]
+struct StripeList<T> {
+    data: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct StripeSubscriptionItem {
+    price: StripePrice,
+    current_period_end: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct StripePrice {
+    id: String,
+}
+
+impl StripeProvider {
+    pub fn from_env() -> Self {
+        let prices = [
+            (Plan::Pro, "STRIPE_PRICE_PRO"),
+            (Plan::Team, "STRIPE_PRICE_TEAM"),
+        ]
+        .into_iter()
+        .filter_map(|(plan, var)| env::var(var).ok().map(|price| (plan, price)))
+        .collect();
+
+        StripeProvider {
+            api_base: env::var("STRIPE_API_BASE")
+                .unwrap_or_else(|_| DEFAULT_STRIPE_API_BASE.to_string()),
+            secret_key: env::var("STRIPE_SECRET_KEY").unwrap_or_default(),
+            webhook_secret: env::var("STRIPE_WEBHOOK_SECRET").unwrap_or_default(),
+            prices,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn price_for(&self, plan: Plan) -> Option<&str> {
+        self.prices
+            .iter()
+            .find(|(candidate, _)| *candidate == plan)
+            .map(|(_, price)| price.as_str())
+    }
+
+    fn plan_for_price(&self, price: &str) -> Option<Plan> {
+        self.prices
+            .iter()
+            .find(|(_, candidate)| candidate == price)
+            .map(|(plan, _)| *plan)
+    }
+
+    fn parse_event(&self, payload: &[u8]) -> anyhow::Result<WebhookEvent> {
+        let event: StripeEvent = serde_json::from_slice(payload)?;
+        let created = DateTime::from_timestamp(event.created, 0)
+            .ok_or_else(|| anyhow!("invalid event timestamp"))?;
+
+        let kind = match event.kind.as_str() {
+            "checkout.session.completed" => {
+                let session: StripeCheckoutObject = serde_json::from_value(event.data.object)?;
+                match (
+                    session.mode.as_deref(),
+                    session.client_reference_id,
+                    session.customer,
+                    session.subscription,
+                ) {
+                    (Some("subscription"), Some(user), Some(customer), Some(subscription)) => {
+                        WebhookEventKind::CheckoutCompleted {
+                            user: ObjectId::parse_str(&user)?,
+                            customer,
+                            subscription,
+                        }
+                    }
+                    _ => WebhookEventKind::Other(event.kind),
+                }
+            }
+            "customer.subscription.created"
+            | "customer.subscription.updated"
+            | "customer.subscription.deleted" => {
+                let subscription: StripeSubscriptionObject =
+                    serde_json::from_value(event.data.object)?;
+                let item = subscription
+                    .items
+                    .and_then(|items| items.data.into_iter().next());
+                let Some(status) = SubscriptionStatus::from_provider(&subscription.status) else {
+                    return Ok(WebhookEvent {
+                        id: event.id,
+                        created,
+                        kind: WebhookEventKind::Other(event.kind),
+                    });
+                };
+
+                WebhookEventKind::SubscriptionUpdated {
+                    status,
+                    // The price is what the customer pays for; metadata written at checkout
+                    // only names the plan when the price is not one we configured.
+                    plan: item
+                        .as_ref()
+                        .and_then(|item| self.plan_for_price(&item.price.id))
+                        .or_else(|| {
+                            subscription
+                                .metadata
+                                .get("plan")
+                                .and_then(|plan| plan.parse().ok())
+                        }),
+                    user: subscription
+                        .metadata
+                        .get("user")
+                        .and_then(|user| ObjectId::parse_str(user).ok()),
+                    current_period_end: subscription
+                        .current_period_end
+                        .or_else(|| item.and_then(|item| item.current_period_end))
+                        .and_then(|end| DateTime::from_timestamp(end, 0)),
+                    subscription: subscription.id,
+                    customer: subscription.customer,
+                }
+            }
+            _ => WebhookEventKind::Other(event.kind),
+        };
+
+        Ok(WebhookEvent {
+            id: event.id,
+            created,
+            kind,
+        })
+    }
+}
+
+impl PaymentProvider for StripeProvider {
+    fn name(&self) -> &'static str {
+        "stripe"
+    }
+
+    async fn create_checkout_session(
+        &self,
+        checkout: CheckoutRequest<'_>,
+    ) -> anyhow::Result<CheckoutSession> {
+        if self.secret_key.is_empty() {
+            bail!("STRIPE_SECRET_KEY is not set");
+        }
+        let price = self
+            .price_for(checkout.plan)
+            .ok_or_else(|| anyhow!("no price configured for the {} plan", checkout.plan))?;
+        let user = checkout.user.id.to_hex();
+
+        let session = self
+            .client
+            .post(format!("{}/v1/checkout/sessions", self.api_base))
+            .bearer_auth(&self.secret_key)
+            .form(&[
+                ("mode", "subscription"),
+                ("line_items[0][price]", price),
+                ("line_items[0][quantity]", "1"),
+                ("success_url", &checkout.success_url),
+                ("cancel_url", &checkout.cancel_url),
+                ("client_reference_id", &user),
+                ("customer_email", &checkout.user.email),
+                ("subscription_data[metadata][user]", &user),
+                ("subscription_data[metadata][plan]", checkout.plan.name()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<StripeCheckoutSession>()
+            .await?;
+
+        Ok(CheckoutSession {
+            url: session
+                .url
+                .ok_or_else(|| anyhow!("checkout session {} has no URL", session.id))?,
+            id: session.id,
+        })
+    }
+
+    async fn cancel_subscription(&self, subscription: &str) -> anyhow::Result<()> {
+        if self.secret_key.is_empty() {
+            bail!("STRIPE_SECRET_KEY is not set");
+        }
+        self.client
+            .delete(format!(
+                "{}/v1/subscriptions/{}",
+                self.api_base, subscription
+            ))
+            .bearer_auth(&self.secret_key)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn verify_webhook(&self, payload: &[u8], signature: &str) -> anyhow::Result<WebhookEvent> {
+        if self.webhook_secret.is_empty() {
+            bail!("STRIPE_WEBHOOK_SECRET is not set");
+        }
+        verify_signature(&self.webhook_secret, payload, signature, Utc::now())?;
+        self.parse_event(payload)
+    }
+}
+
+/// Verifies a `Stripe-Signature` header (`t=<unix time>,v1=<hex hmac>,...`): one of the `v1`
+/// values must be the HMAC-SHA256 of `<t>.<payload>`, and `t` must be recent.
+fn verify_signature(
+    secret: &str,
+    payload: &[u8],
+    header: &str,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or_else(|| anyhow!("signature has no timestamp"))?;
+    if (now.timestamp() - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+        bail!("signature timestamp is outside the tolerance");
+    }
+
+    let verified = signatures.iter().any(|signature| {
+        let Ok(expected) = hex::decode(signature) else {
+            return false;
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(payload);
+        mac.verify_slice(&expected).is_ok()
+    });
+    if !verified {
+        bail!("no signature matches the payload");
+    }
+    Ok(())
+}
+
+static PROVIDER: OnceCell<StripeProvider> = OnceCell::const_new();
+
+pub(crate) async fn payment_provider() -> &'static (impl PaymentProvider + Sync) {
+    PROVIDER
+        .get_or_init(|| async { StripeProvider::from_env() })
+        .await
+}
+
+/// Sets the user's plan to the best plan among their subscriptions that grant one, or to
+/// the free plan if none do. Paid features read `plan`, never `role`, which only separates
+/// admins from everyone else.
+async fn sync_user_plan(user: ObjectId) -> anyhow::Result<Plan> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let plan = db
+        .collection::<Subscription>("subscriptions")
+        .find(doc! { "user": user })
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .filter(|subscription| subscription.status.grants_plan())
+        .map(|subscription| subscription.plan)
+        .max()
+        .unwrap_or_default();
+
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user },
+            doc! { "$set": { "plan": plan.name(), "updatedAt": Utc::now() } },
+        )
+        .await?;
+    Ok(plan)
+}
+
+/// Applies one event to the `subscriptions` collection and the affected user's plan.
+async fn apply_event(provider: &str, event: &WebhookEvent) -> anyhow::Result<()> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let subscriptions = db.collection::<Subscription>("subscriptions");
+
+    let user = match &event.kind {
+        WebhookEventKind::CheckoutCompleted {
+            user,
+            customer,
+            subscription,
+        } => {
+            // Subscription events carry the user in their metadata and create the record;
+            // the checkout only confirms the link.
+            subscriptions
+                .update_one(
+                    doc! { "provider": provider, "provider_subscription_id": subscription },
+                    doc! { "$set": {
+                        "user": user,
+                        "provider_customer_id": customer,
+                        "updatedAt": Utc::now(),
+                    } },
+                )
+                .await?;
+            *user
+        }
+        WebhookEventKind::SubscriptionUpdated {
+            subscription,
+            customer,
+            status,
+            plan,
+            user,
+            current_period_end,
+        } => {
+            let existing = subscriptions
+                .find_one(doc! { "provider": provider, "provider_subscription_id": subscription })
+                .await?;
+
+            match existing {
+                Some(existing) => {
+                    if event.created < existing.last_event_at {
+                        tracing::info!("Skipping stale billing event {}", event.id);
+                        return Ok(());
+                    }
+                    if !existing.status.can_transition_to(*status) {
+                        tracing::warn!(
+                            "Ignoring subscription {} transition from {:?} to {:?}",
+                            subscription,
+                            existing.status,
+                            status
+                        );
+                        return Ok(());
+                    }
+
+                    subscriptions
+                        .update_one(
+                            doc! { "_id": existing.id },
+                            doc! { "$set": {
+                                "status": bson::to_bson(status)?,
+                                "plan": plan.unwrap_or(existing.plan).name(),
+                                "current_period_end": bson::to_bson(current_period_end)?,
+                                "last_event_at": event.created,
+                                "updatedAt": Utc::now(),
+                            } },
+                        )
+                        .await?;
+                    existing.user
+                }
+                None => {
+                    let user = match user {
+                        Some(user) => *user,
+                        None => subscriptions
+                            .find_one(
+                                doc! { "provider": provider, "provider_customer_id": customer },
+                            )
+                            .await?
+                            .map(|other| other.user)
+                            .ok_or_else(|| {
+                                anyhow!("subscription {} has no known user", subscription)
+                            })?,
+                    };
+                    let plan = plan.ok_or_else(|| {
+                        anyhow!("subscription {} has no known plan", subscription)
+                    })?;
+
+                    subscriptions
+                        .insert_one(Subscription {
+                            id: ObjectId::new(),
+                            user,
+                            plan,
+                            status: *status,
+                            provider: provider.to_string(),
+                            provider_customer_id: customer.clone(),
+                            provider_subscription_id: subscription.clone(),
+                            current_period_end: *current_period_end,
+                            last_event_at: event.created,
+                            created_at: Utc::now(),
+                            updated_at: Utc::now(),
+                        })
+                        .await?;
+                    user
+                }
+            }
+        }
+        WebhookEventKind::Other(kind) => {
+            tracing::debug!("Ignoring billing event {} of type {}", event.id, kind);
+            return Ok(());
+        }
+    };
+
+    let plan = sync_user_plan(user).await?;
+    tracing::info!("User {} is now on the {} plan", user, plan);
+    Ok(())
+}
+
+/// Applies an event once. The event is recorded before it is applied, so a redelivery
+/// racing the first delivery hits the unique `_id` and is acknowledged without effect.
+async fn process_event(provider: &str, event: WebhookEvent) -> anyhow::Result<()> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let events = db.collection::<Document>("billing_events");
+
+    let recorded = events
+        .insert_one(doc! {
+            "_id": &event.id,
+            "provider": provider,
+            "createdAt": event.created,
+            "processedAt": Utc::now(),
+        })
+        .await;
+    match recorded {
+        Ok(_) => {}
+        Err(e) if is_duplicate_key(&e) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
+
+    if let Err(e) = apply_event(provider, &event).await {
+        // Forget the event so the provider's retry applies it.
+        events.delete_one(doc! { "_id": &event.id }).await?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+fn is_duplicate_key(error: &mongodb::error::Error) -> bool {
+    matches!(
+        error.kind.as_ref(),
+        mongodb::error::ErrorKind::Write(mongodb::error::WriteFailure::WriteError(failure))
+            if failure.code == 11000
+    )
+}
+
+/// `POST /api/billing/webhook`: receives subscription events from the payment provider.
+pub async fn billing_webhook(headers: HeaderMap, body: Bytes) -> StatusCode {
+    let provider = payment_provider().await;
+    let Some(signature) = headers
+        .get("Stripe-Signature")
+        .and_then(|value| value.to_str().ok())
+    else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let event = match provider.verify_webhook(&body, signature) {
+        Ok(event) => event,
+        Err(e) => {
+            tracing::warn!("Rejected billing webhook: {}", e);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    match process_event(provider.name(), event).await {
+        Ok(()) => StatusCode::OK,
+        Err(e) => {
+            tracing::error!("Failed to process billing webhook: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
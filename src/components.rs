@@ -1,9 +1,12 @@
+pub(crate) mod admin;
 pub(crate) mod common;
 pub(crate) mod dashboard;
 pub(crate) mod features;
 pub(crate) mod footer;
 pub(crate) mod hero;
 pub(crate) mod navbar;
+pub(crate) mod oidc;
 pub(crate) mod spinner;
 pub(crate) mod testimonial;
 pub mod toast;
+pub(crate) mod two_factor;
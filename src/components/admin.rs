@@ -0,0 +1,14 @@
+pub(crate) mod audit;
+pub(crate) mod overview;
+pub(crate) mod users;
+
+use dioxus::prelude::ServerFnError;
+
+/// The message of a server function error without the `error running server function:` prefix.
+pub(crate) fn error_message(error: &ServerFnError) -> String {
+    let message = error.to_string();
+    message
+        .split_once("error running server function:")
+        .map(|(_, rest)| rest.trim().to_string())
+        .unwrap_or(message)
+}
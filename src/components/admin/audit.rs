@@ -0,0 +1,46 @@
+use crate::components::admin::error_message;
+use crate::server::admin::controller::admin_audit_log;
+use crate::server::admin::model::AuditEntry;
+use dioxus::prelude::*;
+
+#[component]
+pub fn AuditLogPanel(user_token: Signal<String>) -> Element {
+    let mut entries = use_signal(Vec::<AuditEntry>::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        match admin_audit_log(user_token()).await {
+            Ok(response) => entries.set(response.data),
+            Err(e) => error.set(Some(error_message(&e))),
+        }
+    });
+
+    rsx! {
+        div {
+            h2 { class: "text-xl font-semibold mb-4", "Audit Log" }
+            if let Some(error) = error() {
+                p { class: "text-red-500", "{error}" }
+            }
+            table { class: "w-full text-sm text-left",
+                thead {
+                    tr {
+                        th { class: "py-2", "When" }
+                        th { "Admin" }
+                        th { "Action" }
+                        th { "Details" }
+                    }
+                }
+                tbody {
+                    for entry in entries() {
+                        tr { class: "border-t border-gray-200",
+                            td { class: "py-2", {entry.created_at.format("%Y-%m-%d %H:%M").to_string()} }
+                            td { "{entry.admin_email}" }
+                            td { "{entry.action.label()}" }
+                            td { "{entry.details}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::components::admin::error_message;
+use crate::components::dashboard::charts::compact;
+use crate::server::admin::controller::{admin_generation_stats, dashboard_overview};
+use crate::server::admin::response::{DashboardResponse, GenerationStats};
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+
+#[component]
+pub fn OverviewPanel(user_token: Signal<String>) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut overview = use_signal(DashboardResponse::default);
+    let mut stats = use_signal(GenerationStats::default);
+    let mut error = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        match dashboard_overview(user_token()).await {
+            Ok(response) => overview.set(response.data),
+            Err(e) => error.set(Some(error_message(&e))),
+        }
+        match admin_generation_stats(user_token()).await {
+            Ok(response) => stats.set(response.data),
+            Err(e) => error.set(Some(error_message(&e))),
+        }
+    });
+
+    let card = if dark_mode {
+        "bg-gray-900"
+    } else {
+        "bg-gray-100"
+    };
+
+    rsx! {
+        div { class: "space-y-6",
+            if let Some(error) = error() {
+                p { class: "text-red-500", "{error}" }
+            }
+
+            div { class: "grid grid-cols-2 lg:grid-cols-5 gap-4",
+                for (title, value) in [
+                    ("Users", overview().users),
+                    ("Paid Users", overview().paid_users),
+                    ("Suspended", overview().suspended_users),
+                    ("Admins", overview().admins),
+                    ("Trips", overview().trips),
+                ] {
+                    div { class: format!("p-4 rounded-lg shadow {}", card),
+                        h3 { class: "text-sm text-gray-500", "{title}" }
+                        p { class: "text-2xl font-bold", "{value}" }
+                    }
+                }
+            }
+
+            div {
+                h2 { class: "text-xl font-semibold mb-2",
+                    "Generation since {stats().since.format(\"%b %-d, %Y\")}"
+                }
+                p { class: "text-sm text-gray-500 mb-4",
+                    "{stats().total_calls} calls, {stats().failed_calls} failed, {compact(stats().total_tokens)} tokens, ${stats().total_cost:.2}"
+                }
+                table { class: "w-full text-sm text-left",
+                    thead {
+                        tr {
+                            th { class: "py-2", "Purpose" }
+                            th { "Calls" }
+                            th { "Failures" }
+                            th { "Avg Latency" }
+                            th { "Tokens" }
+                            th { "Cost" }
+                        }
+                    }
+                    tbody {
+                        for purpose in stats().purposes {
+                            tr { class: "border-t border-gray-200",
+                                td { class: "py-2", "{purpose.purpose}" }
+                                td { "{purpose.calls}" }
+                                td { class: if purpose.failures > 0 { "text-red-500" } else { "" }, "{purpose.failures}" }
+                                td { "{purpose.avg_latency_ms / 1000.0:.2}s" }
+                                td { "{compact(purpose.tokens)}" }
+                                td { "${purpose.cost:.4}" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                h2 { class: "text-xl font-semibold mb-2", "Recent Failures" }
+                if stats().recent_failures.is_empty() {
+                    p { class: "text-sm text-gray-500", "No failed model calls." }
+                }
+                ul { class: "space-y-2",
+                    for failure in stats().recent_failures {
+                        li { class: format!("p-3 rounded text-sm {}", card),
+                            div { class: "flex justify-between text-gray-500",
+                                span { "{failure.purpose} · {failure.model}" }
+                                span { {failure.created_at.format("%Y-%m-%d %H:%M").to_string()} }
+                            }
+                            p { class: "text-red-500", "{failure.error}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::components::admin::error_message;
+use crate::components::dashboard::charts::compact;
+use crate::server::admin::controller::{
+    admin_delete_user, admin_get_user, admin_list_users, admin_set_role, admin_set_suspended,
+};
+use crate::server::admin::request::{
+    AdminListUsersRequest, AdminSetRoleRequest, AdminSetSuspendedRequest, AdminUserRequest,
+};
+use crate::server::admin::response::{AdminUserDetail, UserListResponse};
+use crate::server::auth::model::ROLES;
+use crate::server::plan::model::QuotaMetric;
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+
+#[component]
+pub fn UsersPanel(user_token: Signal<String>) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut query = use_signal(String::new);
+    let mut page = use_signal(|| 0u64);
+    let mut users = use_signal(UserListResponse::default);
+    let mut selected = use_signal(|| None::<String>);
+    let mut refresh = use_signal(|| 0u32);
+    let mut error = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        refresh();
+        match admin_list_users(AdminListUsersRequest {
+            token: user_token(),
+            query: query(),
+            page: page(),
+        })
+        .await
+        {
+            Ok(response) => users.set(response.data),
+            Err(e) => error.set(Some(error_message(&e))),
+        }
+    });
+
+    let pages = users().total.div_ceil(users().page_size.max(1)).max(1);
+
+    rsx! {
+        div { class: "flex flex-col lg:flex-row gap-6",
+            div { class: "flex-1",
+                input {
+                    class: format!("w-full p-2 mb-4 border rounded {}", if dark_mode { "bg-gray-900 border-gray-700" } else { "border-gray-300" }),
+                    placeholder: "Search by name or email",
+                    value: "{query}",
+                    oninput: move |e| {
+                        query.set(e.value());
+                        page.set(0);
+                    },
+                }
+                if let Some(error) = error() {
+                    p { class: "text-red-500 mb-2", "{error}" }
+                }
+                table { class: "w-full text-sm text-left",
+                    thead {
+                        tr {
+                            th { class: "py-2", "Name" }
+                            th { "Email" }
+                            th { "Role" }
+                            th { "Plan" }
+                            th { "Status" }
+                        }
+                    }
+                    tbody {
+                        for user in users().users {
+                            tr {
+                                class: format!("border-t border-gray-200 cursor-pointer {}", if selected() == Some(user.id.to_hex()) { "bg-blue-100 text-gray-900" } else { "" }),
+                                onclick: move |_| selected.set(Some(user.id.to_hex())),
+                                td { class: "py-2", "{user.name}" }
+                                td { "{user.email}" }
+                                td { "{user.role}" }
+                                td { class: "capitalize", "{user.plan}" }
+                                td { class: if user.suspended { "text-red-500" } else { "text-green-500" },
+                                    if user.suspended { "Suspended" } else { "Active" }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "flex justify-between items-center mt-4 text-sm",
+                    span { "{users().total} users" }
+                    div { class: "space-x-2",
+                        button {
+                            class: "px-3 py-1 rounded bg-gray-200 text-gray-800 disabled:opacity-50",
+                            disabled: page() == 0,
+                            onclick: move |_| page.set(page().saturating_sub(1)),
+                            "Previous"
+                        }
+                        span { "Page {page() + 1} of {pages}" }
+                        button {
+                            class: "px-3 py-1 rounded bg-gray-200 text-gray-800 disabled:opacity-50",
+                            disabled: page() + 1 >= pages,
+                            onclick: move |_| page.set(page() + 1),
+                            "Next"
+                        }
+                    }
+                }
+            }
+
+            if let Some(user_id) = selected() {
+                UserDetailPanel {
+                    key: "{user_id}",
+                    user_token,
+                    user_id,
+                    on_change: move |deleted: bool| {
+                        if deleted {
+                            selected.set(None);
+                        }
+                        refresh.set(refresh() + 1);
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// A user's account, usage and trips, with the actions an admin can take on them.
+#[component]
+fn UserDetailPanel(
+    user_token: Signal<String>,
+    user_id: String,
+    on_change: EventHandler<bool>,
+) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut detail = use_signal(|| None::<AdminUserDetail>);
+    let mut error = use_signal(|| None::<String>);
+    let mut confirm_delete = use_signal(|| false);
+    let user_id = use_signal(|| user_id);
+
+    let _ = use_resource(move || async move {
+        match admin_get_user(AdminUserRequest {
+            token: user_token(),
+            user_id: user_id(),
+        })
+        .await
+        {
+            Ok(response) => detail.set(Some(response.data)),
+            Err(e) => error.set(Some(error_message(&e))),
+        }
+    });
+
+    let set_suspended = move |suspended: bool| {
+        spawn(async move {
+            match admin_set_suspended(AdminSetSuspendedRequest {
+                token: user_token(),
+                user_id: user_id(),
+                suspended,
+            })
+            .await
+            {
+                Ok(response) => {
+                    if let Some(detail) = detail.write().as_mut() {
+                        detail.user = response.data;
+                    }
+                    on_change.call(false);
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let set_role = move |role: String| {
+        spawn(async move {
+            match admin_set_role(AdminSetRoleRequest {
+                token: user_token(),
+                user_id: user_id(),
+                role,
+            })
+            .await
+            {
+                Ok(response) => {
+                    if let Some(detail) = detail.write().as_mut() {
+                        detail.user = response.data;
+                    }
+                    on_change.call(false);
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let delete_user = move |_| {
+        if !confirm_delete() {
+            confirm_delete.set(true);
+            return;
+        }
+        spawn(async move {
+            match admin_delete_user(AdminUserRequest {
+                token: user_token(),
+                user_id: user_id(),
+            })
+            .await
+            {
+                Ok(_) => on_change.call(true),
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: format!("w-full lg:w-96 p-4 rounded-lg shadow {}", if dark_mode { "bg-gray-900" } else { "bg-gray-100" }),
+            if let Some(error) = error() {
+                p { class: "text-red-500 mb-2", "{error}" }
+            }
+            if let Some(detail) = detail() {
+                h2 { class: "text-xl font-semibold", "{detail.user.name}" }
+                p { class: "text-sm text-gray-500 mb-4",
+                    "{detail.user.email} · joined {detail.user.created_at.format(\"%b %-d, %Y\")}"
+                }
+
+                div { class: "flex items-center space-x-2 mb-4",
+                    select {
+                        class: "p-1 border rounded text-gray-900",
+                        value: "{detail.user.role}",
+                        onchange: move |e| set_role(e.value()),
+                        for role in ROLES {
+                            option { value: role, selected: detail.user.role == role, "{role}" }
+                        }
+                    }
+                    button {
+                        class: "px-3 py-1 rounded bg-yellow-500 text-white",
+                        onclick: move |_| set_suspended(!detail.user.suspended),
+                        if detail.user.suspended { "Unsuspend" } else { "Suspend" }
+                    }
+                    button {
+                        class: "px-3 py-1 rounded bg-red-600 text-white",
+                        onclick: delete_user,
+                        if confirm_delete() { "Confirm delete" } else { "Delete" }
+                    }
+                }
+
+                h3 { class: "font-semibold mb-2 capitalize", "{detail.usage.plan} plan usage this month" }
+                ul { class: "text-sm mb-4",
+                    for metric in QuotaMetric::ALL {
+                        li { class: "flex justify-between",
+                            span { class: "capitalize", "{metric.label()}" }
+                            span { "{compact(detail.usage.used.get(metric))} / {compact(detail.usage.limits.get(metric))}" }
+                        }
+                    }
+                }
+
+                h3 { class: "font-semibold mb-2", "Trips ({detail.trips.len()})" }
+                ul { class: "text-sm space-y-1 max-h-64 overflow-y-auto",
+                    for trip in detail.trips {
+                        li { class: "flex justify-between",
+                            span { "{trip.title}" }
+                            span { class: "text-gray-500", "{trip.created_at.format(\"%Y-%m-%d\")}" }
+                        }
+                    }
+                }
+            } else {
+                p { class: "text-sm text-gray-500", "Loading user..." }
+            }
+        }
+    }
+}
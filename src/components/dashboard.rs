@@ -1,7 +1,11 @@
 pub(crate) mod analytics;
+pub(crate) mod charts;
 pub(crate) mod chat;
 pub(crate) mod fields;
 pub(crate) mod navbar;
+pub(crate) mod preferences;
 pub(crate) mod profile;
+pub(crate) mod sessions;
 pub(crate) mod sidebar;
 pub(crate) mod trips;
+pub(crate) mod two_factor;
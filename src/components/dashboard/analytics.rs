@@ -1,36 +1,24 @@
-use crate::server::auth::controller::about_me;
-use crate::server::trip::controller::fetch_analytics_data;
-use crate::server::trip::response::AnalyticsData;
+use crate::components::dashboard::charts::{ChartKind, SeriesChart};
+use crate::server::analytics::controller::fetch_analytics_data;
+use crate::server::analytics::model::AnalyticsRange;
+use crate::server::analytics::request::AnalyticsRequest;
+use crate::server::analytics::response::{AnalyticsData, SeriesPoint};
 use dioxus::prelude::*;
-use gloo_storage::SessionStorage;
-use gloo_storage::Storage;
 
 #[component]
-pub fn AnalyticsPage() -> Element {
+pub fn AnalyticsPage(user_token: Signal<String>) -> Element {
     let mut analytics = use_signal(|| AnalyticsData::default());
-    let mut user_token = use_signal(|| "".to_string());
-    let navigator = use_navigator();
-
-    use_effect(move || {
-        spawn(async move {
-            let token: String = SessionStorage::get("jwt").unwrap_or_default();
-            if token.is_empty() {
-                navigator.push("/login");
-            } else {
-                match about_me(token.clone()).await {
-                    Ok(data) => {
-                        let _user = data.data.user;
-                        user_token.set(token.clone());
-                    }
-                    Err(_) => {
-                        navigator.push("/login");
-                    }
-                }
-            }
-        });
-    });
+    let mut range = use_signal(AnalyticsRange::default);
     let _ = use_resource(move || async move {
-        match fetch_analytics_data(user_token()).await {
+        if user_token().is_empty() {
+            return;
+        }
+        match fetch_analytics_data(AnalyticsRequest {
+            token: user_token(),
+            range: range(),
+        })
+        .await
+        {
             Ok(response) => {
                 analytics.set(response.data);
             }
@@ -43,7 +31,18 @@ pub fn AnalyticsPage() -> Element {
     rsx! {
         div {
             class: "pb-6",
-            h1 { class: "text-3xl font-bold mb-6 text-gray-800 dark:text-gray-100", "Analytics" }
+            div { class: "flex flex-wrap justify-between items-center mb-6 gap-2",
+                h1 { class: "text-3xl font-bold text-gray-800 dark:text-gray-100", "Analytics" }
+                div { class: "flex space-x-2",
+                    for option in AnalyticsRange::ALL {
+                        button {
+                            class: format!("px-3 py-1 rounded text-sm {}", if range() == option { "bg-blue-600 text-white" } else { "bg-gray-200 text-gray-800 dark:bg-gray-700 dark:text-gray-100" }),
+                            onclick: move |_| range.set(option),
+                            "{option.label()}"
+                        }
+                    }
+                }
+            }
             div {
                 class: "grid grid-cols-1 sm:grid-cols-2 lg:grid-cols-4 gap-6",
                 MetricCard { title: "Total Trips", value: analytics().engagement.total_trips.to_string(), index: 0 }
@@ -51,8 +50,70 @@ pub fn AnalyticsPage() -> Element {
                 MetricCard { title: "Avg Places per Trip", value: format!("{:.2}", analytics().engagement.avg_details_per_trip), index: 2 }
                 MetricCard { title: "Trending Trip", value: analytics().predictions.trending_genre.clone(), index: 3 }
                 // MetricCard { title: "Projected Growth", value: format!("{:.2}%", analytics().predictions.projected_growth), index: 4 }
-                // MetricCard { title: "Avg Gen Time", value: format!("{:.2}s", analytics().ai_usage.avg_gen_time), index: 5 }
-                // MetricCard { title: "Success Rate", value: format!("{:.2}%", analytics().ai_usage.success_rate), index: 6 }
+                MetricCard { title: "AI Generated Places", value: analytics().ai_usage.total_ai_details.to_string(), index: 4 }
+                MetricCard { title: "Avg Gen Time", value: format!("{:.2}s", analytics().ai_usage.avg_gen_time), index: 5 }
+                MetricCard { title: "Success Rate", value: format!("{:.2}%", analytics().ai_usage.success_rate), index: 6 }
+                MetricCard { title: "Tokens Used", value: format!("{} in / {} out", analytics().ai_usage.input_tokens, analytics().ai_usage.output_tokens), index: 7 }
+                MetricCard { title: "Estimated AI Cost", value: format!("${:.4}", analytics().ai_usage.total_cost), index: 8 }
+                MetricCard { title: "Chat Messages", value: analytics().ai_usage.chat_messages.to_string(), index: 9 }
+                MetricCard { title: "Completion Rate", value: format!("{:.2}%", analytics().engagement.completion_rate), index: 10 }
+            }
+            AnalyticsCharts { analytics: analytics() }
+        }
+    }
+}
+
+/// Per-day or per-month charts of the series returned for the selected range.
+#[component]
+fn AnalyticsCharts(analytics: AnalyticsData) -> Element {
+    let granularity = analytics.granularity();
+    let labels: Vec<String> = analytics
+        .series
+        .iter()
+        .map(|point| granularity.label(point.period))
+        .collect();
+    let values = |metric: fn(&SeriesPoint) -> f64| -> Vec<f64> {
+        analytics.series.iter().map(metric).collect()
+    };
+
+    rsx! {
+        div { class: "grid grid-cols-1 lg:grid-cols-2 gap-6 mt-6",
+            SeriesChart {
+                title: "Trips Created",
+                labels: labels.clone(),
+                values: values(|point| point.trips_created as f64),
+                kind: ChartKind::Bar,
+                color: "text-blue-500",
+            }
+            SeriesChart {
+                title: "Places Generated",
+                labels: labels.clone(),
+                values: values(|point| point.details_generated as f64),
+                kind: ChartKind::Bar,
+                color: "text-green-500",
+            }
+            SeriesChart {
+                title: "Chat Messages",
+                labels: labels.clone(),
+                values: values(|point| point.chat_messages as f64),
+                kind: ChartKind::Bar,
+                color: "text-purple-500",
+            }
+            SeriesChart {
+                title: "Tokens Used",
+                labels: labels.clone(),
+                values: values(|point| point.tokens as f64),
+                kind: ChartKind::Line,
+                color: "text-yellow-500",
+            }
+            SeriesChart {
+                title: "Completion Rate",
+                labels,
+                values: values(|point| point.completion_rate),
+                kind: ChartKind::Line,
+                color: "text-red-500",
+                suffix: "%",
+                max: 100.0,
             }
         }
     }
@@ -0,0 +1,149 @@
+use dioxus::prelude::*;
+
+const WIDTH: f64 = 480.0;
+const HEIGHT: f64 = 200.0;
+const LEFT: f64 = 44.0;
+const RIGHT: f64 = 8.0;
+const TOP: f64 = 8.0;
+const BOTTOM: f64 = 24.0;
+const GRID_LINES: usize = 4;
+const MAX_X_LABELS: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChartKind {
+    Line,
+    Bar,
+}
+
+/// Formats large counts as `12.5k` or `3M`.
+pub(crate) fn compact(value: u64) -> String {
+    match value {
+        0..=999 => value.to_string(),
+        1_000..=999_999 => format!("{:.1}k", value as f64 / 1_000.0).replace(".0k", "k"),
+        _ => format!("{:.1}M", value as f64 / 1_000_000.0).replace(".0M", "M"),
+    }
+}
+
+/// Smallest "round" number (1, 2 or 5 times a power of ten) at or above `value`.
+fn nice_ceiling(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|candidate| *candidate >= value)
+        .unwrap_or(value)
+}
+
+/// One series drawn as an SVG line or bar chart. `labels` and `values` are parallel;
+/// `max` fixes the top of the scale, e.g. 100 for percentages.
+#[component]
+pub fn SeriesChart(
+    title: String,
+    labels: Vec<String>,
+    values: Vec<f64>,
+    kind: ChartKind,
+    color: String,
+    #[props(default)] suffix: String,
+    #[props(default)] max: Option<f64>,
+) -> Element {
+    let plot_width = WIDTH - LEFT - RIGHT;
+    let plot_height = HEIGHT - TOP - BOTTOM;
+    let count = values.len().max(1);
+    let top = max.unwrap_or_else(|| nice_ceiling(values.iter().cloned().fold(0.0, f64::max)));
+    let slot = plot_width / count as f64;
+    let x = move |index: usize| LEFT + slot * (index as f64 + 0.5);
+    let y = move |value: f64| TOP + plot_height * (1.0 - (value / top).clamp(0.0, 1.0));
+
+    let grid: Vec<(f64, String)> = (0..=GRID_LINES)
+        .map(|step| {
+            let value = top * step as f64 / GRID_LINES as f64;
+            (
+                y(value),
+                format!("{}{}", compact(value.round() as u64), suffix),
+            )
+        })
+        .collect();
+    let label_every = count.div_ceil(MAX_X_LABELS);
+    let points: Vec<(f64, f64, String)> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let label = labels.get(index).cloned().unwrap_or_default();
+            (
+                x(index),
+                y(*value),
+                format!("{}: {}{}", label, compact(value.round() as u64), suffix),
+            )
+        })
+        .collect();
+    let polyline = points
+        .iter()
+        .map(|(x, y, _)| format!("{:.1},{:.1}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let baseline = TOP + plot_height;
+
+    rsx! {
+        div { class: "p-4 rounded-lg shadow-lg bg-gray-100 dark:bg-gray-900",
+            h3 { class: "text-sm font-medium mb-2 text-gray-800 dark:text-gray-100", "{title}" }
+            svg {
+                view_box: "0 0 {WIDTH} {HEIGHT}",
+                class: "w-full h-auto",
+                role: "img",
+                "aria-label": "{title}",
+
+                g { class: "text-gray-400",
+                    for (line_y, label) in grid {
+                        line {
+                            x1: "{LEFT}", x2: "{WIDTH - RIGHT}", y1: "{line_y}", y2: "{line_y}",
+                            stroke: "currentColor", stroke_opacity: "0.3", stroke_width: "1",
+                        }
+                        text {
+                            x: "{LEFT - 6.0}", y: "{line_y + 3.0}",
+                            text_anchor: "end", font_size: "10", fill: "currentColor",
+                            "{label}"
+                        }
+                    }
+                    for (index, label) in labels.iter().enumerate().filter(|(index, _)| index % label_every == 0) {
+                        text {
+                            x: "{x(index)}", y: "{HEIGHT - 6.0}",
+                            text_anchor: "middle", font_size: "10", fill: "currentColor",
+                            "{label}"
+                        }
+                    }
+                }
+
+                g { class: "{color}",
+                    match kind {
+                        ChartKind::Bar => rsx! {
+                            for (bar_x, bar_y, tooltip) in points {
+                                rect {
+                                    x: "{bar_x - slot * 0.35}", y: "{bar_y}",
+                                    width: "{slot * 0.7}", height: "{baseline - bar_y}",
+                                    rx: "2", fill: "currentColor",
+                                    title { "{tooltip}" }
+                                }
+                            }
+                        },
+                        ChartKind::Line => rsx! {
+                            polyline {
+                                points: "{polyline}",
+                                fill: "none", stroke: "currentColor", stroke_width: "2",
+                                stroke_linejoin: "round",
+                            }
+                            for (point_x, point_y, tooltip) in points {
+                                circle {
+                                    cx: "{point_x}", cy: "{point_y}", r: "3", fill: "currentColor",
+                                    title { "{tooltip}" }
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -10,6 +10,8 @@ use crate::server::conversation::controller::send_query_to_bedrock;
 use crate::server::conversation::model::Message;
 use crate::server::conversation::request::GetMessagesRequest;
 use crate::server::conversation::request::SendQueryRequest;
+use crate::server::llm::controller::get_model_registry;
+use crate::server::llm::model::ModelInfo;
 use crate::server::trip::controller::get_details_for_trip;
 use crate::server::trip::controller::get_trips_for_user;
 use crate::server::trip::model::Detail;
@@ -54,6 +56,17 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
     let mut trips = use_signal(Vec::<Trip>::new);
     let mut thinking = use_signal(|| false);
     let mut loading = use_signal(|| false);
+    let mut selected_model = use_signal(String::new);
+    let mut models = use_signal(Vec::<ModelInfo>::new);
+
+    let _ = use_resource(move || async move {
+        if let Ok(response) = get_model_registry().await {
+            if let Some(default_model) = response.data.first() {
+                selected_model.set(default_model.name.clone());
+            }
+            models.set(response.data);
+        }
+    });
 
     let _ = use_resource(move || async move {
         let now = Utc::now().timestamp();
@@ -112,6 +125,7 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
             }
 
             if let Ok(response) = get_details_for_trip(GetDetailContentRequest {
+                token: user_token(),
                 trip_id: trip_id.clone(),
             })
             .await
@@ -185,6 +199,7 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
                     conversation: conversation_id(),
                     sender: "user".to_string(),
                     content: query_text.clone(),
+                    prompt: None,
                     timestamp: Utc::now(),
                 };
 
@@ -198,7 +213,7 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
                 //         trip: trip.id.to_string(),
                 //         detail: detail.id.to_string(),
                 //         conversation_id: conversation_id(),
-                //         model: "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+                //         model: selected_model(),
                 //         token: user_token(),
                 //     })
                 //     .await;
@@ -215,7 +230,13 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
                 //             thinking.set(false);
                 //         }
                 //     }
-                //     save_message_to_db(user_message).await.unwrap();
+                //     save_message_to_db(SaveMessageRequest {
+                //         token: user_token(),
+                //         conversation_id: conversation_id(),
+                //         content: query_text,
+                //     })
+                //     .await
+                //     .unwrap();
                 // });
 
                 input_query.set("".to_string());
@@ -230,6 +251,7 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
                 spawn({
                     async move {
                         if let Ok(response) = get_details_for_trip(GetDetailContentRequest {
+                            token: user_token(),
                             trip_id: trip.id.to_string(),
                         })
                         .await
@@ -283,6 +305,18 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
                         option { value: "{detail.id}", "{truncate(detail.title.clone(), 20)}" }
                     }
                 }
+
+                select {
+                    class: format!(
+                        "p-2 rounded-lg flex-grow w-full md:w-auto truncate {}",
+                        if *THEME.read() == Theme::Dark { "bg-gray-700 text-white" } else { "bg-gray-100 text-black" }
+                    ),
+                    value: "{selected_model}",
+                    onchange: move |evt| selected_model.set(evt.value()),
+                    for model in models().iter() {
+                        option { value: "{model.name}", "{model.name}" }
+                    }
+                }
             }
 
             div {
@@ -3,11 +3,7 @@ use crate::theme::THEME;
 use dioxus::prelude::*;
 
 #[component]
-pub fn SelectField(
-    label: &'static str,
-    options: Vec<&'static str>,
-    selected: Signal<String>,
-) -> Element {
+pub fn SelectField(label: &'static str, options: Vec<String>, selected: Signal<String>) -> Element {
     let dark_mode = *THEME.read() == Theme::Dark;
     rsx! {
         div {
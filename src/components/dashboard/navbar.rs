@@ -1,23 +1,91 @@
+use crate::components::admin::error_message;
+use crate::components::dashboard::charts::compact;
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
 use crate::pages::dashboard::toggle_theme;
+use crate::server::auth::controller::{about_me, logout, resend_verification_email};
+use crate::server::billing::controller::{create_checkout_session, get_subscription};
+use crate::server::billing::model::Subscription;
+use crate::server::billing::request::CreateCheckoutRequest;
+use crate::server::plan::controller::get_usage;
+use crate::server::plan::model::{Plan, QuotaMetric, Usage};
 use dioxus::prelude::*;
 use gloo_storage::Storage;
 use gloo_storage::{LocalStorage, SessionStorage};
 
+/// The signed-in user's avatar URL, updated by the profile panel when it changes.
+pub static PROFILE_PHOTO: GlobalSignal<String> = GlobalSignal::new(String::new);
+
+pub const DEFAULT_PHOTO: &str = "https://rustacean.net/assets/rustacean-flat-happy.svg";
+
 #[component]
 pub fn Navbar(dark_mode: bool) -> Element {
     let mut show_dropdown = use_signal(|| false);
     let mut loading = use_signal(|| false);
     let navigator = use_navigator();
+    let mut usage = use_signal(|| None::<Usage>);
+    let mut subscription = use_signal(|| None::<Subscription>);
+    let mut is_admin = use_signal(|| false);
+    let mut verified = use_signal(|| true);
+    let mut verification_notice = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if token.is_empty() {
+            return;
+        }
+        match get_usage(token.clone()).await {
+            Ok(response) => usage.set(Some(response.data)),
+            Err(e) => dioxus_logger::tracing::error!("{}", e),
+        }
+        if let Ok(response) = about_me(token.clone()).await {
+            is_admin.set(response.data.user.is_admin());
+            verified.set(response.data.user.verified);
+            *PROFILE_PHOTO.write() = response.data.user.photo;
+        }
+        match get_subscription(token).await {
+            Ok(response) => subscription.set(response.data),
+            Err(e) => dioxus_logger::tracing::error!("{}", e),
+        }
+    });
+
+    let handle_upgrade = move |plan: Plan| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match create_checkout_session(CreateCheckoutRequest { token, plan }).await {
+                Ok(response) => {
+                    let url = serde_json::to_string(&response.data.url).unwrap_or_default();
+                    let _ = eval(&format!("window.location.href = {};", url));
+                }
+                Err(e) => dioxus_logger::tracing::error!("{}", e),
+            }
+        });
+    };
+
+    let handle_resend = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match resend_verification_email(token).await {
+                Ok(response) => verification_notice
+                    .set(Some(format!("We sent a new link to {}.", response.data))),
+                Err(e) => verification_notice.set(Some(error_message(&e))),
+            }
+        });
+    };
 
     let handle_logout = move |e: Event<MouseData>| {
         e.stop_propagation();
-        loading.set(false);
+        loading.set(true);
 
-        SessionStorage::clear();
-        LocalStorage::clear();
-        navigator.push("/login");
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            if let Err(e) = logout(token).await {
+                dioxus_logger::tracing::error!("{}", e);
+            }
+            SessionStorage::clear();
+            LocalStorage::clear();
+            navigator.push("/login");
+        });
     };
 
     rsx! {
@@ -25,6 +93,10 @@ pub fn Navbar(dark_mode: bool) -> Element {
             h1 { class: "text-2xl font-semibold", "Dashboard" }
 
             div { class: "flex items-center space-x-4",
+                if let Some(usage) = usage() {
+                    UsageMeter { usage, dark_mode }
+                }
+
                 button {
                     onclick: |_| toggle_theme(),
                     class: "p-2 rounded-full text-lg",
@@ -36,14 +108,41 @@ pub fn Navbar(dark_mode: bool) -> Element {
                         class: format!("p-2 rounded-full flex items-center justify-center {}", if dark_mode { "bg-gray-700" } else { "bg-gray-200" }),
                         onclick: move |_| show_dropdown.set(!show_dropdown()),
                         img {
-                            src: "https://rustacean.net/assets/rustacean-flat-happy.svg",
+                            src: if PROFILE_PHOTO.read().is_empty() { DEFAULT_PHOTO.to_string() } else { PROFILE_PHOTO() },
                             alt: "User profile image",
-                            class: "w-8 h-8 rounded-full"
+                            class: "w-8 h-8 rounded-full object-cover"
                         }
                     }
                     if show_dropdown() {
                         div { class: format!("absolute right-0 mt-2 w-48 shadow-lg rounded-lg {}", if dark_mode { "bg-gray-800" } else { "bg-white" }),
+                            if let Some(usage) = usage() {
+                                div { class: format!("px-4 py-2 text-xs border-b {}", if dark_mode { "border-gray-700 text-gray-400" } else { "text-gray-500" }),
+                                    span { class: "capitalize", "{usage.plan} plan" }
+                                    if let Some(subscription) = subscription() {
+                                        span { " · {subscription.status.label()}" }
+                                        if let Some(end) = subscription.current_period_end {
+                                            span { " until {end.format(\"%b %e, %Y\")}" }
+                                        }
+                                    }
+                                }
+                                for plan in Plan::ALL.into_iter().filter(|plan| *plan > usage.plan) {
+                                    button {
+                                        class: format!("w-full text-left px-4 py-2 text-blue-600 hover:bg-gray-100 {}", if dark_mode { "hover:bg-gray-700" } else { "" }),
+                                        onclick: move |_| handle_upgrade(plan),
+                                        span { class: "capitalize", "Upgrade to {plan}" }
+                                    }
+                                }
+                            }
                             button { class: format!("w-full text-left px-4 py-2 hover:bg-gray-100 {}", if dark_mode { "hover:bg-gray-700" } else { "" }), "Profile" }
+                            if is_admin() {
+                                button {
+                                    class: format!("w-full text-left px-4 py-2 hover:bg-gray-100 {}", if dark_mode { "hover:bg-gray-700" } else { "" }),
+                                    onclick: move |_| {
+                                        navigator.push("/admin");
+                                    },
+                                    "Admin Console"
+                                }
+                            }
                             button {
                                 class: "w-full text-left px-4 py-2 hover:bg-gray-100",
                                 onclick: handle_logout,
@@ -63,5 +162,51 @@ pub fn Navbar(dark_mode: bool) -> Element {
                 }
             }
         }
+        if !verified() {
+            div { class: format!("flex justify-between items-center mb-4 p-3 rounded text-sm {}", if dark_mode { "bg-yellow-900 text-yellow-100" } else { "bg-yellow-100 text-yellow-800" }),
+                span {
+                    if let Some(notice) = verification_notice() {
+                        "{notice}"
+                    } else {
+                        "Please verify your email address. Check your inbox for the link we sent."
+                    }
+                }
+                button {
+                    class: "px-3 py-1 rounded bg-yellow-500 text-white",
+                    onclick: handle_resend,
+                    "Resend link"
+                }
+            }
+        }
+    }
+}
+
+/// Compact per-plan usage bars for the current month.
+#[component]
+fn UsageMeter(usage: Usage, dark_mode: bool) -> Element {
+    rsx! {
+        div { class: "hidden md:flex items-center space-x-4 text-xs",
+            span { class: format!("px-2 py-1 rounded-full uppercase font-semibold {}", if dark_mode { "bg-blue-900 text-blue-200" } else { "bg-blue-100 text-blue-700" }),
+                "{usage.plan}"
+            }
+            for metric in QuotaMetric::ALL {
+                div { class: "w-28",
+                    div { class: "flex justify-between mb-1",
+                        span { class: "capitalize", "{metric.label()}" }
+                        span { "{compact(usage.used.get(metric))}/{compact(usage.limits.get(metric))}" }
+                    }
+                    div { class: format!("h-1.5 rounded-full {}", if dark_mode { "bg-gray-700" } else { "bg-gray-200" }),
+                        div {
+                            class: format!("h-1.5 rounded-full {}", match usage.percent(metric) {
+                                0..=74 => "bg-green-500",
+                                75..=99 => "bg-yellow-500",
+                                _ => "bg-red-500",
+                            }),
+                            style: "width: {usage.percent(metric)}%"
+                        }
+                    }
+                }
+            }
+        }
     }
 }
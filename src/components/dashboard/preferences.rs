@@ -0,0 +1,154 @@
+use crate::components::admin::error_message;
+use crate::components::dashboard::fields::select::SelectField;
+use crate::currency::currency_codes;
+use crate::server::auth::controller::{about_me, update_preferences};
+use crate::server::auth::model::{BudgetStyle, TravelPace, UserPreferences};
+use crate::server::auth::response::UpdatePreferencesSchema;
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// Travel preferences that prefill new trips and are passed to every generated plan and answer.
+#[component]
+pub fn TravelPreferencesPanel() -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut home_city = use_signal(String::new);
+    let mut home_currency = use_signal(|| "USD".to_string());
+    let mut language = use_signal(|| "English".to_string());
+    let mut pace = use_signal(|| TravelPace::default().label().to_string());
+    let mut budget_style = use_signal(|| BudgetStyle::default().label().to_string());
+    let mut interests = use_signal(String::new);
+    let mut dietary_needs = use_signal(String::new);
+    let mut mobility = use_signal(String::new);
+    let mut timezone = use_signal(|| "UTC".to_string());
+    let mut message = use_signal(|| None::<Result<String, String>>);
+
+    let mut fill = move |preferences: UserPreferences| {
+        home_city.set(preferences.home_city);
+        home_currency.set(preferences.home_currency);
+        language.set(preferences.language);
+        pace.set(preferences.pace.label().to_string());
+        budget_style.set(preferences.budget_style.label().to_string());
+        interests.set(preferences.interests.join(", "));
+        dietary_needs.set(preferences.dietary_needs);
+        mobility.set(preferences.mobility);
+        timezone.set(preferences.timezone);
+    };
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = about_me(token).await {
+            fill(response.data.user.preferences);
+        }
+    });
+
+    let handle_submit = move |_| {
+        let preferences = UserPreferences {
+            home_currency: home_currency(),
+            home_city: home_city(),
+            language: language(),
+            pace: TravelPace::ALL
+                .into_iter()
+                .find(|option| option.label() == pace())
+                .unwrap_or_default(),
+            interests: interests().split(',').map(String::from).collect(),
+            dietary_needs: dietary_needs(),
+            mobility: mobility(),
+            budget_style: BudgetStyle::ALL
+                .into_iter()
+                .find(|option| option.label() == budget_style())
+                .unwrap_or_default(),
+            timezone: timezone(),
+        };
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match update_preferences(UpdatePreferencesSchema { token, preferences }).await {
+                Ok(response) => {
+                    fill(response.data);
+                    message.set(Some(Ok("Preferences saved".into())));
+                }
+                Err(e) => message.set(Some(Err(error_message(&e)))),
+            }
+        });
+    };
+
+    let input_class = format!(
+        "w-full p-2 mb-3 border border-gray-300 rounded-md {}",
+        if dark_mode {
+            "bg-gray-700 text-white"
+        } else {
+            "bg-white text-gray-900"
+        }
+    );
+
+    rsx! {
+        form { class: "max-w-md mt-6", onsubmit: handle_submit,
+            h3 { class: "text-lg font-semibold mb-2", "Travel Preferences" }
+            p { class: "text-xs text-gray-500 mb-4",
+                "Used as defaults for new trips and taken into account in every plan, detail and chat answer."
+            }
+            label { class: "block text-sm mb-1", "Home city" }
+            input {
+                class: "{input_class}",
+                r#type: "text",
+                placeholder: "e.g. Lisbon, Portugal",
+                value: "{home_city}",
+                oninput: move |e| home_city.set(e.value()),
+            }
+            label { class: "block text-sm mb-1", "Default language" }
+            input {
+                class: "{input_class}",
+                r#type: "text",
+                value: "{language}",
+                oninput: move |e| language.set(e.value()),
+            }
+            div { class: "grid grid-cols-3 gap-2 mb-3",
+                SelectField { label: "Home currency", options: currency_codes().into_iter().map(String::from).collect(), selected: home_currency }
+                SelectField { label: "Pace", options: TravelPace::ALL.iter().map(|option| option.label().to_string()).collect(), selected: pace }
+                SelectField { label: "Budget style", options: BudgetStyle::ALL.iter().map(|option| option.label().to_string()).collect(), selected: budget_style }
+            }
+            label { class: "block text-sm mb-1", "Interests" }
+            input {
+                class: "{input_class}",
+                r#type: "text",
+                placeholder: "museums, hiking, street food",
+                value: "{interests}",
+                oninput: move |e| interests.set(e.value()),
+            }
+            label { class: "block text-sm mb-1", "Dietary needs" }
+            input {
+                class: "{input_class}",
+                r#type: "text",
+                placeholder: "e.g. vegetarian, no nuts",
+                value: "{dietary_needs}",
+                oninput: move |e| dietary_needs.set(e.value()),
+            }
+            label { class: "block text-sm mb-1", "Mobility constraints" }
+            input {
+                class: "{input_class}",
+                r#type: "text",
+                placeholder: "e.g. wheelchair user, avoid long walks",
+                value: "{mobility}",
+                oninput: move |e| mobility.set(e.value()),
+            }
+            label { class: "block text-sm mb-1", "Timezone" }
+            input {
+                class: "{input_class}",
+                r#type: "text",
+                placeholder: "e.g. Europe/Lisbon",
+                value: "{timezone}",
+                oninput: move |e| timezone.set(e.value()),
+            }
+            match message() {
+                Some(Ok(message)) => rsx! { p { class: "text-green-500 text-sm mb-3", "{message}" } },
+                Some(Err(message)) => rsx! { p { class: "text-red-500 text-sm mb-3", "{message}" } },
+                None => rsx! {},
+            }
+            button {
+                class: "px-3 py-1 rounded bg-blue-600 text-white text-sm",
+                r#type: "submit",
+                "Save preferences"
+            }
+        }
+    }
+}
@@ -1,14 +1,316 @@
+use crate::components::admin::error_message;
+use crate::components::dashboard::navbar::{DEFAULT_PHOTO, PROFILE_PHOTO};
+use crate::components::dashboard::preferences::TravelPreferencesPanel;
+use crate::components::dashboard::sessions::SessionsPanel;
+use crate::components::dashboard::two_factor::TwoFactorPanel;
+use crate::server::auth::controller::{
+    about_me, change_password, remove_avatar, update_profile, upload_avatar,
+};
+use crate::server::auth::response::{
+    ChangePasswordSchema, UpdateProfileSchema, UploadAvatarSchema,
+};
 use crate::theme::Theme;
 use crate::theme::THEME;
 use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// Avatars are center-cropped and scaled to this many pixels square before upload.
+const AVATAR_SIZE: u32 = 256;
+
+/// Reads the picked file, crops it square on a canvas and sends back base64 JPEG data, or
+/// `null` if the browser can't decode it.
+fn resize_avatar_script() -> String {
+    format!(
+        r#"
+        const input = document.getElementById("avatar-input");
+        const file = input.files[0];
+        input.value = "";
+        if (!file) {{
+            dioxus.send(null);
+        }} else {{
+            createImageBitmap(file).then((bitmap) => {{
+                const side = Math.min(bitmap.width, bitmap.height);
+                const canvas = document.createElement("canvas");
+                canvas.width = {size};
+                canvas.height = {size};
+                canvas.getContext("2d").drawImage(
+                    bitmap,
+                    (bitmap.width - side) / 2,
+                    (bitmap.height - side) / 2,
+                    side,
+                    side,
+                    0,
+                    0,
+                    {size},
+                    {size}
+                );
+                dioxus.send(canvas.toDataURL("image/jpeg", 0.9).split(",")[1]);
+            }}).catch(() => dioxus.send(null));
+        }}
+        "#,
+        size = AVATAR_SIZE
+    )
+}
 
 #[component]
 pub fn EditProfilePanel() -> Element {
     let dark_mode = *THEME.read() == Theme::Dark;
+    let mut name = use_signal(String::new);
+    let mut email = use_signal(String::new);
+    let mut saved_email = use_signal(String::new);
+    let mut pending_email = use_signal(|| None::<String>);
+    let mut email_password = use_signal(String::new);
+    let mut verified = use_signal(|| true);
+    let mut profile_message = use_signal(|| None::<Result<String, String>>);
+    let mut avatar_message = use_signal(|| None::<String>);
+    let mut uploading = use_signal(|| false);
+    let mut current_password = use_signal(String::new);
+    let mut new_password = use_signal(String::new);
+    let mut confirm_password = use_signal(String::new);
+    let mut password_message = use_signal(|| None::<Result<String, String>>);
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = about_me(token).await {
+            let user = response.data.user;
+            name.set(user.name);
+            email.set(user.email.clone());
+            saved_email.set(user.email);
+            pending_email.set(user.pending_email);
+            verified.set(user.verified);
+            *PROFILE_PHOTO.write() = user.photo;
+        }
+    });
+
+    let handle_profile = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match update_profile(UpdateProfileSchema {
+                token,
+                name: name(),
+                email: email(),
+                current_password: email_password(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    let user = response.data.user;
+                    profile_message.set(Some(Ok(match &user.pending_email {
+                        Some(pending) => format!(
+                            "Profile saved. Open the link sent to {} to start using it.",
+                            pending
+                        ),
+                        None => "Profile saved".into(),
+                    })));
+                    name.set(user.name);
+                    email.set(user.email.clone());
+                    saved_email.set(user.email);
+                    pending_email.set(user.pending_email);
+                    email_password.set(String::new());
+                    verified.set(user.verified);
+                }
+                Err(e) => profile_message.set(Some(Err(error_message(&e)))),
+            }
+        });
+    };
+
+    let handle_avatar = move |_| {
+        uploading.set(true);
+        spawn(async move {
+            let mut resize = eval(&resize_avatar_script());
+            match resize.recv().await {
+                Ok(serde_json::Value::String(image)) => {
+                    let token: String = SessionStorage::get("jwt").unwrap_or_default();
+                    match upload_avatar(UploadAvatarSchema { token, image }).await {
+                        Ok(response) => {
+                            avatar_message.set(None);
+                            *PROFILE_PHOTO.write() = response.data;
+                        }
+                        Err(e) => avatar_message.set(Some(error_message(&e))),
+                    }
+                }
+                _ => avatar_message.set(Some("That file could not be read as an image".into())),
+            }
+            uploading.set(false);
+        });
+    };
+
+    let handle_remove_avatar = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match remove_avatar(token).await {
+                Ok(_) => {
+                    avatar_message.set(None);
+                    *PROFILE_PHOTO.write() = String::new();
+                }
+                Err(e) => avatar_message.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let handle_password = move |_| {
+        if new_password() != confirm_password() {
+            password_message.set(Some(Err("New passwords don't match".into())));
+            return;
+        }
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match change_password(ChangePasswordSchema {
+                token,
+                current_password: current_password(),
+                new_password: new_password(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    password_message.set(Some(Ok(format!(
+                        "{}. Other devices have been signed out.",
+                        response.data
+                    ))));
+                    current_password.set(String::new());
+                    new_password.set(String::new());
+                    confirm_password.set(String::new());
+                }
+                Err(e) => password_message.set(Some(Err(error_message(&e)))),
+            }
+        });
+    };
+
+    let input_class = format!(
+        "w-full p-2 mb-3 border border-gray-300 rounded-md {}",
+        if dark_mode {
+            "bg-gray-700 text-white"
+        } else {
+            "bg-white text-gray-900"
+        }
+    );
+
     rsx! {
         div { class: format!("p-4 {}", if dark_mode { "bg-gray-800 text-white" } else { "bg-white text-gray-900" }),
             h2 { class: "text-xl font-semibold mb-4", "Edit Profile" }
-            p { "TODO" }
+            div { class: "flex items-center space-x-4 mb-6",
+                img {
+                    src: if PROFILE_PHOTO.read().is_empty() { DEFAULT_PHOTO.to_string() } else { PROFILE_PHOTO() },
+                    alt: "Profile photo",
+                    class: "w-20 h-20 rounded-full object-cover"
+                }
+                div {
+                    label { class: "px-3 py-1 rounded bg-blue-600 text-white text-sm cursor-pointer",
+                        if uploading() { "Uploading..." } else { "Upload photo" }
+                        input {
+                            id: "avatar-input",
+                            class: "hidden",
+                            r#type: "file",
+                            accept: "image/png, image/jpeg",
+                            disabled: uploading(),
+                            onchange: handle_avatar,
+                        }
+                    }
+                    if !PROFILE_PHOTO.read().is_empty() {
+                        button {
+                            class: "ml-2 px-3 py-1 rounded bg-gray-200 text-gray-800 text-sm",
+                            onclick: handle_remove_avatar,
+                            "Remove"
+                        }
+                    }
+                    if let Some(message) = avatar_message() {
+                        p { class: "text-red-500 text-sm mt-2", "{message}" }
+                    }
+                }
+            }
+            form { class: "max-w-md", onsubmit: handle_profile,
+                label { class: "block text-sm mb-1", "Name" }
+                input {
+                    class: "{input_class}",
+                    r#type: "text",
+                    value: "{name}",
+                    required: true,
+                    oninput: move |e| name.set(e.value()),
+                }
+                label { class: "block text-sm mb-1",
+                    "Email"
+                    if !verified() {
+                        span { class: "ml-2 text-xs text-yellow-500", "not verified" }
+                    }
+                }
+                input {
+                    class: "{input_class}",
+                    r#type: "email",
+                    value: "{email}",
+                    required: true,
+                    oninput: move |e| email.set(e.value()),
+                }
+                if email() != saved_email() {
+                    input {
+                        class: "{input_class}",
+                        r#type: "password",
+                        autocomplete: "current-password",
+                        placeholder: "Current password",
+                        value: "{email_password}",
+                        required: true,
+                        oninput: move |e| email_password.set(e.value()),
+                    }
+                }
+                p { class: "text-xs text-gray-500 mb-3",
+                    match pending_email() {
+                        Some(pending) => format!("Waiting for {} to be confirmed from the link we sent there.", pending),
+                        None => "A new email address is used once you open the link we send to it.".to_string(),
+                    }
+                }
+                match profile_message() {
+                    Some(Ok(message)) => rsx! { p { class: "text-green-500 text-sm mb-3", "{message}" } },
+                    Some(Err(message)) => rsx! { p { class: "text-red-500 text-sm mb-3", "{message}" } },
+                    None => rsx! {},
+                }
+                button {
+                    class: "px-3 py-1 rounded bg-blue-600 text-white text-sm",
+                    r#type: "submit",
+                    "Save profile"
+                }
+            }
+            form { class: "max-w-md mt-6", onsubmit: handle_password,
+                h3 { class: "text-lg font-semibold mb-4", "Change Password" }
+                input {
+                    class: "{input_class}",
+                    r#type: "password",
+                    autocomplete: "current-password",
+                    placeholder: "Current password",
+                    value: "{current_password}",
+                    required: true,
+                    oninput: move |e| current_password.set(e.value()),
+                }
+                input {
+                    class: "{input_class}",
+                    r#type: "password",
+                    autocomplete: "new-password",
+                    placeholder: "New password",
+                    value: "{new_password}",
+                    required: true,
+                    oninput: move |e| new_password.set(e.value()),
+                }
+                input {
+                    class: "{input_class}",
+                    r#type: "password",
+                    autocomplete: "new-password",
+                    placeholder: "Confirm new password",
+                    value: "{confirm_password}",
+                    required: true,
+                    oninput: move |e| confirm_password.set(e.value()),
+                }
+                match password_message() {
+                    Some(Ok(message)) => rsx! { p { class: "text-green-500 text-sm mb-3", "{message}" } },
+                    Some(Err(message)) => rsx! { p { class: "text-red-500 text-sm mb-3", "{message}" } },
+                    None => rsx! {},
+                }
+                button {
+                    class: "px-3 py-1 rounded bg-blue-600 text-white text-sm",
+                    r#type: "submit",
+                    "Change password"
+                }
+            }
+            TravelPreferencesPanel {}
+            TwoFactorPanel {}
+            SessionsPanel {}
         }
     }
 }
@@ -0,0 +1,98 @@
+use crate::components::admin::error_message;
+use crate::server::auth::controller::{list_sessions, revoke_all_sessions, revoke_session};
+use crate::server::auth::response::{RevokeSessionSchema, SessionInfo};
+use crate::session::clear_tokens;
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// The devices signed in to the current account, each of which can be signed out.
+#[component]
+pub fn SessionsPanel() -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let navigator = use_navigator();
+    let mut sessions = use_signal(Vec::<SessionInfo>::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut refresh = use_signal(|| 0u32);
+
+    let _ = use_resource(move || async move {
+        refresh();
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        match list_sessions(token).await {
+            Ok(response) => sessions.set(response.data),
+            Err(e) => error.set(Some(error_message(&e))),
+        }
+    });
+
+    let handle_revoke = move |session: SessionInfo| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match revoke_session(RevokeSessionSchema {
+                token,
+                session_id: session.id,
+            })
+            .await
+            {
+                Ok(_) if session.current => {
+                    clear_tokens();
+                    navigator.push("/login");
+                }
+                Ok(_) => refresh.set(refresh() + 1),
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let handle_revoke_all = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match revoke_all_sessions(token).await {
+                Ok(_) => {
+                    clear_tokens();
+                    navigator.push("/login");
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "mt-6",
+            div { class: "flex justify-between items-center mb-4",
+                h3 { class: "text-lg font-semibold", "Active Sessions" }
+                button {
+                    class: "px-3 py-1 rounded bg-red-600 text-white text-sm",
+                    onclick: handle_revoke_all,
+                    "Sign out everywhere"
+                }
+            }
+            if let Some(error) = error() {
+                p { class: "text-red-500 mb-2", "{error}" }
+            }
+            ul { class: "space-y-2",
+                for session in sessions() {
+                    li {
+                        key: "{session.id}",
+                        class: format!("flex justify-between items-center p-3 rounded {}", if dark_mode { "bg-gray-900" } else { "bg-gray-100" }),
+                        div {
+                            p { class: "font-medium",
+                                "{session.device}"
+                                if session.current {
+                                    span { class: "ml-2 text-xs text-green-500", "This device" }
+                                }
+                            }
+                            p { class: "text-xs text-gray-500", title: "{session.user_agent}",
+                                "Signed in {session.created_at.format(\"%b %-d, %Y\")} · last active {session.last_used_at.format(\"%b %-d, %Y\")}"
+                            }
+                        }
+                        button {
+                            class: "px-3 py-1 rounded bg-gray-200 text-gray-800 text-sm",
+                            onclick: move |_| handle_revoke(session.clone()),
+                            if session.current { "Sign out" } else { "Revoke" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,7 @@
+pub(crate) mod budget;
 pub(crate) mod create;
+pub(crate) mod expenses;
 pub(crate) mod list;
+pub(crate) mod packing;
 pub(crate) mod read;
+pub(crate) mod weather;
@@ -0,0 +1,77 @@
+use crate::currency::format_amount;
+use crate::currency::RateTable;
+use crate::server::trip::model::Detail;
+use crate::server::trip::model::Trip;
+use crate::theme::Theme;
+use crate::theme::THEME;
+use dioxus::prelude::*;
+use std::collections::BTreeMap;
+
+pub fn daily_costs(details: &[Detail]) -> BTreeMap<u64, f64> {
+    let mut days = BTreeMap::new();
+    for detail in details {
+        *days.entry(detail.day).or_insert(0.0) += detail.estimated_cost;
+    }
+    days
+}
+
+#[component]
+pub fn BudgetSummary(
+    trip: Trip,
+    details: Vec<Detail>,
+    rates: RateTable,
+    home_currency: String,
+) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let days = daily_costs(&details);
+    let total: f64 = days.values().sum();
+    let over_budget = trip.budget > 0.0 && total > trip.budget;
+    let in_home = |amount: f64| {
+        rates
+            .convert(amount, &trip.currency, &home_currency)
+            .map(|converted| format_amount(converted, &home_currency))
+            .unwrap_or_else(|| format_amount(amount, &trip.currency))
+    };
+
+    rsx! {
+        div {
+            class: format!("p-4 mb-4 rounded-lg shadow {}", if dark_mode { "bg-gray-800" } else { "bg-gray-100" }),
+            h3 { class: "text-lg font-semibold mb-2", "Budget" }
+            if over_budget {
+                p {
+                    class: "mb-2 p-2 rounded bg-red-100 text-red-700",
+                    "This plan exceeds your budget by {in_home(total - trip.budget)}."
+                }
+            }
+            ul {
+                class: "space-y-1 text-sm",
+                for (day, cost) in days {
+                    li {
+                        class: "flex justify-between",
+                        span {
+                            "Day {day}"
+                            if let Some(date) = trip.date_for_day(day) {
+                                " · {date.format(\"%a, %b %-d\")}"
+                            }
+                        }
+                        span { "{in_home(cost)}" }
+                    }
+                }
+            }
+            div {
+                class: "flex justify-between mt-2 pt-2 border-t font-semibold",
+                span { "Total" }
+                span {
+                    class: if over_budget { "text-red-500" } else { "text-green-500" },
+                    "{in_home(total)} / {in_home(trip.budget)}"
+                }
+            }
+            if trip.currency != home_currency {
+                p {
+                    class: "mt-1 text-xs text-gray-400 text-right",
+                    "Planned in {format_amount(total, &trip.currency)} / {format_amount(trip.budget, &trip.currency)}"
+                }
+            }
+        }
+    }
+}
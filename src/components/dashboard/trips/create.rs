@@ -7,6 +7,11 @@ use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
 use crate::components::toast::manager::ToastManager;
 use crate::components::toast::manager::ToastType;
+use crate::currency::currency_codes;
+use crate::server::auth::controller::about_me;
+use crate::server::llm::controller::get_model_registry;
+use crate::server::llm::model::ModelInfo;
+use crate::server::plan::model::QuotaError;
 use crate::server::trip::controller::fetch_google_places_autocomplete;
 use crate::server::trip::controller::generate_detail_content;
 use crate::server::trip::controller::generate_trip_outline;
@@ -15,6 +20,7 @@ use crate::server::trip::request::GenerateTripRequest;
 use crate::theme::Theme;
 use crate::theme::THEME;
 use chrono::Duration;
+use chrono::NaiveDate;
 use chrono::Utc;
 use dioxus::prelude::*;
 use gloo_storage::{LocalStorage, Storage};
@@ -35,25 +41,51 @@ struct Prediction {
 pub fn CreateTripPanel(user_token: Signal<String>) -> Element {
     let dark_mode = *THEME.read() == Theme::Dark;
     let title = use_signal(|| "".to_string());
-    let model = use_signal(|| "anthropic.claude-3-haiku-20240307-v1:0".to_string());
-    let subtopics = use_signal(|| 30);
-    let details = use_signal(|| 5);
-    let language = use_signal(|| "English".to_string());
-    let max_length = use_signal(|| 10);
+    let mut model = use_signal(String::new);
+    let mut models = use_signal(Vec::<ModelInfo>::new);
+    let budget = use_signal(|| 1000);
+    let mut currency = use_signal(|| "USD".to_string());
+    let details = use_signal(|| 3);
+    let mut language = use_signal(|| "English".to_string());
+    let max_length = use_signal(|| 3);
+    let mut start_date = use_signal(String::new);
+    let timezone = use_signal(String::new);
     let api_key = use_signal(|| "google_api_key".to_string());
 
     let title_valid = use_signal(|| true);
     let destination_valid = use_signal(|| true);
     let language_valid = use_signal(|| true);
+    let timezone_valid = use_signal(|| true);
     let mut loading = use_signal(|| false);
     let _form_error = use_signal(|| None::<String>);
 
     let validate_title = |title: &str| !title.is_empty();
     let validate_destination = |destination: &str| !destination.is_empty();
     let validate_language = |language: &str| !language.is_empty();
+    let validate_timezone = |timezone: &str| {
+        timezone.is_empty() || timezone == "UTC" || timezone.split('/').count() >= 2
+    };
 
     let mut toasts_manager = use_context::<Signal<ToastManager>>();
 
+    let _ = use_resource(move || async move {
+        if let Ok(response) = get_model_registry().await {
+            if let Some(default_model) = response.data.first() {
+                model.set(default_model.name.clone());
+            }
+            models.set(response.data);
+        }
+    });
+
+    // Start from the user's travel preferences.
+    let _ = use_resource(move || async move {
+        if let Ok(response) = about_me(user_token()).await {
+            let preferences = response.data.user.preferences;
+            currency.set(preferences.home_currency);
+            language.set(preferences.language);
+        }
+    });
+
     let mut recommended_destinations = use_signal(|| vec![]);
     let mut destination = use_signal(|| "".to_string());
     let mut selected_destination = use_signal(|| Some("Beirut, Lebanon".to_string()));
@@ -120,10 +152,13 @@ pub fn CreateTripPanel(user_token: Signal<String>) -> Element {
                         token: user_token(),
                         subtitle: selected_destination().expect("destination"),
                         model: model(),
-                        subtopics: subtopics(),
+                        budget: budget(),
+                        currency: currency(),
                         details: details(),
                         language: language(),
                         max_length: max_length(),
+                        start_date: NaiveDate::parse_from_str(&start_date(), "%Y-%m-%d").ok(),
+                        timezone: Some(timezone()).filter(|tz| !tz.is_empty()),
                     })
                     .await
                     {
@@ -159,6 +194,7 @@ pub fn CreateTripPanel(user_token: Signal<String>) -> Element {
                             );
                             for detail in response.data.details {
                                 match generate_detail_content(GenerateDetailContentRequest {
+                                    token: user_token(),
                                     detail_title: detail.title,
                                     trip_title: title(),
                                     language: language(),
@@ -204,16 +240,25 @@ pub fn CreateTripPanel(user_token: Signal<String>) -> Element {
                         }
                         Err(e) => {
                             let msg = e.to_string();
-                            let error_message = msg
-                                .splitn(2, "error running server function:")
-                                .nth(1)
-                                .unwrap_or("")
-                                .trim();
+                            let error_message = match QuotaError::from_server_error(&e) {
+                                Some(quota) => format!(
+                                    "You have used all {} {} included in the {} plan this month.",
+                                    quota.limit,
+                                    quota.metric.label(),
+                                    quota.plan
+                                ),
+                                None => msg
+                                    .splitn(2, "error running server function:")
+                                    .nth(1)
+                                    .unwrap_or("")
+                                    .trim()
+                                    .to_string(),
+                            };
                             toasts_manager.set(
                                 toasts_manager()
                                     .add_toast(
                                         "Error".into(),
-                                        error_message.into(),
+                                        error_message,
                                         ToastType::Error,
                                         Some(Duration::seconds(5)),
                                     )
@@ -274,10 +319,25 @@ pub fn CreateTripPanel(user_token: Signal<String>) -> Element {
                         }
                     }
 
-                    SelectField { label: "Model", options: vec!["claude-3", "claude-3.5-sonet"], selected: model }
-                    NumberField { label: "Budget ($)", value: subtopics, required: true }
+                    SelectField { label: "Model", options: models().into_iter().map(|model| model.name).collect(), selected: model }
+                    NumberField { label: "Budget", value: budget, required: true }
+                    SelectField { label: "Currency", options: currency_codes().into_iter().map(String::from).collect(), selected: currency }
                     InputField { label: "Language", value: language, is_valid: language_valid, validate: validate_language, required: true }
-                    NumberField { label: "NB Days", value: max_length, required: true }
+                    NumberField { label: "Days", value: max_length, required: true }
+                    NumberField { label: "Places per Day", value: details, required: true }
+                    div {
+                        label {
+                            class: format!("block text-sm font-medium {}", if dark_mode { "text-gray-300" } else { "text-gray-700" }),
+                            "Start Date"
+                        }
+                        input {
+                            r#type: "date",
+                            class: format!("mt-1 block w-full p-2 border rounded-md shadow-sm {}", if dark_mode { "bg-gray-900 border-gray-700" } else { "border-gray-300" }),
+                            value: "{start_date}",
+                            oninput: move |e| start_date.set(e.value()),
+                        }
+                    }
+                    InputField { label: "Destination Timezone (e.g. Asia/Beirut)", value: timezone, is_valid: timezone_valid, validate: validate_timezone, required: false }
 
                     button {
                         class: format!("flex items-center space-x-2 bg-blue-500 text-white px-4 py-2 rounded {}", if dark_mode { "bg-blue-600" } else { "" }),
@@ -0,0 +1,307 @@
+use crate::components::dashboard::trips::budget::daily_costs;
+use crate::components::toast::manager::ToastManager;
+use crate::components::toast::manager::ToastType;
+use crate::currency::currency_codes;
+use crate::currency::format_amount;
+use crate::currency::RateTable;
+use crate::server::expense::controller::add_expense;
+use crate::server::expense::controller::delete_expense;
+use crate::server::expense::controller::get_expenses_for_trip;
+use crate::server::expense::model::Expense;
+use crate::server::expense::model::EXPENSE_CATEGORIES;
+use crate::server::expense::request::AddExpenseRequest;
+use crate::server::expense::request::DeleteExpenseRequest;
+use crate::server::expense::request::GetExpensesRequest;
+use crate::server::trip::model::Detail;
+use crate::server::trip::model::Trip;
+use crate::theme::Theme;
+use crate::theme::THEME;
+use chrono::Duration;
+use dioxus::prelude::*;
+use std::collections::BTreeMap;
+
+pub struct ExpenseTotals {
+    pub total: f64,
+    pub by_category: BTreeMap<String, f64>,
+    pub by_day: BTreeMap<u64, f64>,
+    pub unconverted: usize,
+}
+
+/// Sums expenses in `currency`, converting through `rates` where needed. Expenses whose
+/// currency has no known rate are counted in `unconverted` and left out of the totals.
+pub fn expense_totals(expenses: &[Expense], rates: &RateTable, currency: &str) -> ExpenseTotals {
+    let mut totals = ExpenseTotals {
+        total: 0.0,
+        by_category: BTreeMap::new(),
+        by_day: BTreeMap::new(),
+        unconverted: 0,
+    };
+
+    for expense in expenses {
+        let Some(amount) = rates.convert(expense.amount, &expense.currency, currency) else {
+            totals.unconverted += 1;
+            continue;
+        };
+        totals.total += amount;
+        *totals
+            .by_category
+            .entry(expense.category.clone())
+            .or_insert(0.0) += amount;
+        if let Some(day) = expense.day {
+            *totals.by_day.entry(day).or_insert(0.0) += amount;
+        }
+    }
+
+    totals
+}
+
+fn server_error_message(e: ServerFnError) -> String {
+    let msg = e.to_string();
+    msg.split_once("error running server function:")
+        .map(|(_, message)| message.trim())
+        .unwrap_or("")
+        .to_string()
+}
+
+#[component]
+pub fn ExpensesPanel(
+    user_token: Signal<String>,
+    trip: Trip,
+    details: Vec<Detail>,
+    rates: RateTable,
+    home_currency: String,
+) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut toasts_manager = use_context::<Signal<ToastManager>>();
+    let mut expenses = use_signal(Vec::<Expense>::new);
+
+    let mut amount = use_signal(String::new);
+    let mut currency = use_signal(|| trip.currency.clone());
+    let mut category = use_signal(|| EXPENSE_CATEGORIES[0].to_string());
+    let mut detail_id = use_signal(String::new);
+    let mut payer = use_signal(String::new);
+    let mut note = use_signal(String::new);
+
+    let trip_id = trip.id.to_string();
+    let trip_id_clone = trip_id.clone();
+    let _ = use_resource(move || {
+        let trip_id = trip_id_clone.clone();
+        async move {
+            if let Ok(response) = get_expenses_for_trip(GetExpensesRequest {
+                token: user_token(),
+                trip_id,
+            })
+            .await
+            {
+                expenses.set(response.data);
+            }
+        }
+    });
+
+    let handle_submit = move |e: Event<FormData>| {
+        e.stop_propagation();
+        let trip_id = trip_id.clone();
+
+        let Ok(value) = amount().trim().parse::<f64>() else {
+            toasts_manager.set(
+                toasts_manager()
+                    .add_toast(
+                        "Error".into(),
+                        "Amount must be a number!".into(),
+                        ToastType::Error,
+                        Some(Duration::seconds(5)),
+                    )
+                    .clone(),
+            );
+            return;
+        };
+
+        spawn(async move {
+            match add_expense(AddExpenseRequest {
+                token: user_token(),
+                trip_id,
+                detail_id: Some(detail_id()),
+                day: None,
+                amount: value,
+                currency: currency(),
+                category: category(),
+                payer: payer(),
+                note: note(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    expenses.write().push(response.data);
+                    amount.set(String::new());
+                    note.set(String::new());
+                }
+                Err(e) => {
+                    toasts_manager.set(
+                        toasts_manager()
+                            .add_toast(
+                                "Error".into(),
+                                server_error_message(e),
+                                ToastType::Error,
+                                Some(Duration::seconds(5)),
+                            )
+                            .clone(),
+                    );
+                }
+            }
+        });
+    };
+
+    let handle_delete = move |expense_id: String| {
+        spawn(async move {
+            if delete_expense(DeleteExpenseRequest {
+                token: user_token(),
+                expense_id: expense_id.clone(),
+            })
+            .await
+            .is_ok()
+            {
+                expenses.retain(|expense| expense.id.to_string() != expense_id);
+            }
+        });
+    };
+
+    let totals = expense_totals(&expenses(), &rates, &home_currency);
+    let to_home = |amount: f64| {
+        rates
+            .convert(amount, &trip.currency, &home_currency)
+            .unwrap_or(amount)
+    };
+    let planned: BTreeMap<u64, f64> = daily_costs(&details)
+        .into_iter()
+        .map(|(day, cost)| (day, to_home(cost)))
+        .collect();
+    let planned_total: f64 = planned.values().sum();
+    let budget = to_home(trip.budget);
+    let over_budget = budget > 0.0 && totals.total > budget;
+    let field_class = format!(
+        "mt-1 block w-full p-2 border rounded-md shadow-sm {}",
+        if dark_mode {
+            "bg-gray-900 border-gray-700"
+        } else {
+            "border-gray-300"
+        }
+    );
+
+    rsx! {
+        div {
+            class: format!("p-4 mb-4 rounded-lg shadow {}", if dark_mode { "bg-gray-800" } else { "bg-gray-100" }),
+            h3 { class: "text-lg font-semibold mb-4", "Expenses" }
+
+            form {
+                class: "grid grid-cols-1 md:grid-cols-3 gap-4 mb-6",
+                onsubmit: handle_submit,
+                input {
+                    class: field_class.clone(),
+                    r#type: "number",
+                    step: "0.01",
+                    placeholder: "Amount",
+                    value: "{amount}",
+                    oninput: move |e| amount.set(e.value()),
+                    required: true
+                }
+                select {
+                    class: field_class.clone(),
+                    value: "{currency}",
+                    oninput: move |e| currency.set(e.value()),
+                    for code in currency_codes() {
+                        option { value: "{code}", "{code}" }
+                    }
+                }
+                select {
+                    class: field_class.clone(),
+                    value: "{category}",
+                    oninput: move |e| category.set(e.value()),
+                    for option in EXPENSE_CATEGORIES {
+                        option { value: "{option}", "{option}" }
+                    }
+                }
+                select {
+                    class: field_class.clone(),
+                    value: "{detail_id}",
+                    oninput: move |e| detail_id.set(e.value()),
+                    option { value: "", "Whole trip" }
+                    for detail in details.iter() {
+                        option { value: "{detail.id}", "{detail.title}" }
+                    }
+                }
+                input {
+                    class: field_class.clone(),
+                    placeholder: "Paid by",
+                    value: "{payer}",
+                    oninput: move |e| payer.set(e.value())
+                }
+                input {
+                    class: field_class.clone(),
+                    placeholder: "Note",
+                    value: "{note}",
+                    oninput: move |e| note.set(e.value())
+                }
+                button {
+                    class: "bg-blue-500 text-white px-4 py-2 rounded md:col-span-3",
+                    r#type: "submit",
+                    "Add Expense"
+                }
+            }
+
+            div {
+                class: "grid grid-cols-1 md:grid-cols-3 gap-4 mb-6 text-sm",
+                div {
+                    h4 { class: "font-semibold mb-2", "Spent vs Plan" }
+                    p { "Spent: {format_amount(totals.total, &home_currency)}" }
+                    p { "Planned: {format_amount(planned_total, &home_currency)}" }
+                    p { "Budget: {format_amount(budget, &home_currency)}" }
+                    if over_budget {
+                        p { class: "text-red-500", "Spending is over budget!" }
+                    }
+                    if totals.unconverted > 0 {
+                        p { class: "text-yellow-500", "{totals.unconverted} expense(s) in currencies without a known rate are not included." }
+                    }
+                }
+                div {
+                    h4 { class: "font-semibold mb-2", "By Category" }
+                    for (name, value) in totals.by_category.iter() {
+                        p { class: "flex justify-between", span { "{name}" } span { "{format_amount(*value, &home_currency)}" } }
+                    }
+                }
+                div {
+                    h4 { class: "font-semibold mb-2", "By Day" }
+                    for (day, value) in totals.by_day.iter() {
+                        p {
+                            class: "flex justify-between",
+                            span { "Day {day}" }
+                            span { "{format_amount(*value, &home_currency)} / {format_amount(planned.get(day).copied().unwrap_or(0.0), &home_currency)}" }
+                        }
+                    }
+                }
+            }
+
+            ul {
+                class: "space-y-2",
+                for expense in expenses() {
+                    li {
+                        class: format!("flex justify-between items-center p-2 rounded {}", if dark_mode { "bg-gray-700" } else { "bg-white" }),
+                        div {
+                            p { class: "font-medium", "{format_amount(expense.amount, &expense.currency)} · {expense.category}" }
+                            p {
+                                class: "text-xs text-gray-400",
+                                if let Some(day) = expense.day { "Day {day} · " }
+                                if !expense.payer.is_empty() { "Paid by {expense.payer} · " }
+                                "{expense.note}"
+                            }
+                        }
+                        button {
+                            class: "text-red-500 text-sm",
+                            onclick: move |_| handle_delete(expense.id.to_string()),
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
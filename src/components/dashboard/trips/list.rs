@@ -91,7 +91,7 @@ pub fn TripsPanel(user_token: Signal<String>) -> Element {
 
     rsx! {
         div {
-            AnalyticsPage {}
+            AnalyticsPage { user_token }
             div {
                 div {
                     class: "w-full md:w-1/3 pb-4 mb-4 md:mb-0 flex flex-col gap-8",
@@ -136,6 +136,12 @@ pub fn TripsPanel(user_token: Signal<String>) -> Element {
                                     class: "text-sm text-gray-500 mb-2",
                                     "{trip.created_at.format(\"%B %d, %Y\")} · {trip.title.len() / 7000} min read"
                                 }
+                                if let (Some(start), Some(end)) = (trip.start_date, trip.end_date) {
+                                    p {
+                                        class: "text-sm text-blue-500 mb-2",
+                                        "{start.format(\"%b %-d\")} – {end.format(\"%b %-d, %Y\")}"
+                                    }
+                                }
                                 p {
                                     class: format!(
                                         "text-sm {}",
@@ -0,0 +1,342 @@
+use crate::components::toast::manager::ToastManager;
+use crate::components::toast::manager::ToastType;
+use crate::server::packing::controller::apply_packing_template;
+use crate::server::packing::controller::delete_packing_template;
+use crate::server::packing::controller::generate_packing_list;
+use crate::server::packing::controller::get_packing_list;
+use crate::server::packing::controller::get_packing_templates;
+use crate::server::packing::controller::save_packing_template;
+use crate::server::packing::controller::update_packing_list;
+use crate::server::packing::model::PackingItem;
+use crate::server::packing::model::PackingTemplate;
+use crate::server::packing::model::PACKING_CATEGORIES;
+use crate::server::packing::request::ApplyPackingTemplateRequest;
+use crate::server::packing::request::DeletePackingTemplateRequest;
+use crate::server::packing::request::GeneratePackingListRequest;
+use crate::server::packing::request::GetPackingListRequest;
+use crate::server::packing::request::GetPackingTemplatesRequest;
+use crate::server::packing::request::SavePackingTemplateRequest;
+use crate::server::packing::request::UpdatePackingListRequest;
+use crate::theme::Theme;
+use crate::theme::THEME;
+use chrono::Duration;
+use dioxus::prelude::*;
+
+fn server_error_message(e: ServerFnError) -> String {
+    let msg = e.to_string();
+    msg.split_once("error running server function:")
+        .map(|(_, message)| message.trim())
+        .unwrap_or("")
+        .to_string()
+}
+
+#[component]
+pub fn PackingPanel(user_token: Signal<String>, trip_id: String) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut toasts_manager = use_context::<Signal<ToastManager>>();
+    let mut items = use_signal(Vec::<PackingItem>::new);
+    let mut templates = use_signal(Vec::<PackingTemplate>::new);
+    let mut generating = use_signal(|| false);
+
+    let mut name = use_signal(String::new);
+    let mut category = use_signal(|| PACKING_CATEGORIES[0].to_string());
+    let mut quantity = use_signal(|| "1".to_string());
+    let mut template_name = use_signal(String::new);
+
+    let trip_id_clone = trip_id.clone();
+    let _ = use_resource(move || {
+        let trip_id = trip_id_clone.clone();
+        async move {
+            if let Ok(response) = get_packing_list(GetPackingListRequest {
+                token: user_token(),
+                trip_id,
+            })
+            .await
+            {
+                items.set(response.data.map(|list| list.items).unwrap_or_default());
+            }
+            if let Ok(response) = get_packing_templates(GetPackingTemplatesRequest {
+                token: user_token(),
+            })
+            .await
+            {
+                templates.set(response.data);
+            }
+        }
+    });
+
+    let mut show_error = move |message: String| {
+        toasts_manager.set(
+            toasts_manager()
+                .add_toast(
+                    "Error".into(),
+                    message,
+                    ToastType::Error,
+                    Some(Duration::seconds(5)),
+                )
+                .clone(),
+        );
+    };
+
+    let trip_id_save = trip_id.clone();
+    let save = move || {
+        let trip_id = trip_id_save.clone();
+        spawn(async move {
+            if let Err(e) = update_packing_list(UpdatePackingListRequest {
+                token: user_token(),
+                trip_id,
+                items: items(),
+            })
+            .await
+            {
+                show_error(server_error_message(e));
+            }
+        });
+    };
+
+    let trip_id_generate = trip_id.clone();
+    let handle_generate = move |_| {
+        let trip_id = trip_id_generate.clone();
+        generating.set(true);
+        spawn(async move {
+            match generate_packing_list(GeneratePackingListRequest {
+                token: user_token(),
+                trip_id,
+                language: "English".to_string(),
+            })
+            .await
+            {
+                Ok(response) => items.set(response.data.items),
+                Err(e) => show_error(server_error_message(e)),
+            }
+            generating.set(false);
+        });
+    };
+
+    let save_add = save.clone();
+    let handle_add = move |e: Event<FormData>| {
+        e.stop_propagation();
+        if name().trim().is_empty() {
+            return;
+        }
+        items.write().push(PackingItem {
+            name: name().trim().to_string(),
+            category: category(),
+            quantity: quantity().parse().unwrap_or(1),
+            packed: false,
+        });
+        name.set(String::new());
+        quantity.set("1".to_string());
+        save_add();
+    };
+
+    let handle_save_template = move |e: Event<FormData>| {
+        e.stop_propagation();
+        spawn(async move {
+            match save_packing_template(SavePackingTemplateRequest {
+                token: user_token(),
+                name: template_name(),
+                items: items(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    templates.write().push(response.data);
+                    template_name.set(String::new());
+                }
+                Err(e) => show_error(server_error_message(e)),
+            }
+        });
+    };
+
+    let handle_apply_template = move |template_id: String| {
+        let trip_id = trip_id.clone();
+        spawn(async move {
+            match apply_packing_template(ApplyPackingTemplateRequest {
+                token: user_token(),
+                trip_id,
+                template_id,
+            })
+            .await
+            {
+                Ok(response) => items.set(response.data.items),
+                Err(e) => show_error(server_error_message(e)),
+            }
+        });
+    };
+
+    let handle_delete_template = move |template_id: String| {
+        spawn(async move {
+            if delete_packing_template(DeletePackingTemplateRequest {
+                token: user_token(),
+                template_id: template_id.clone(),
+            })
+            .await
+            .is_ok()
+            {
+                templates.retain(|template| template.id.to_string() != template_id);
+            }
+        });
+    };
+
+    let packed = items().iter().filter(|item| item.packed).count();
+    let total = items().len();
+    let field_class = format!(
+        "mt-1 block w-full p-2 border rounded-md shadow-sm {}",
+        if dark_mode {
+            "bg-gray-900 border-gray-700"
+        } else {
+            "border-gray-300"
+        }
+    );
+
+    rsx! {
+        div {
+            class: format!("p-4 mb-4 rounded-lg shadow {}", if dark_mode { "bg-gray-800" } else { "bg-gray-100" }),
+            div {
+                class: "flex items-center justify-between mb-4",
+                h3 { class: "text-lg font-semibold", "Packing List" }
+                span { class: "text-sm text-gray-400", "{packed} / {total} packed" }
+                button {
+                    class: "bg-blue-500 text-white px-4 py-2 rounded disabled:opacity-50",
+                    disabled: generating(),
+                    onclick: handle_generate,
+                    if generating() { "Generating..." } else if total == 0 { "Generate" } else { "Regenerate" }
+                }
+            }
+
+            for group in PACKING_CATEGORIES {
+                if items().iter().any(|item| item.category == group) {
+                    div {
+                        class: "mb-4",
+                        h4 { class: "font-semibold mb-2", "{group}" }
+                        ul {
+                            class: "space-y-1",
+                            for (index, item) in items().into_iter().enumerate().filter(|(_, item)| item.category == group) {
+                                li {
+                                    class: format!("flex items-center space-x-2 p-2 rounded {}", if dark_mode { "bg-gray-700" } else { "bg-white" }),
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: item.packed,
+                                        onchange: {
+                                            let save = save.clone();
+                                            move |e: Event<FormData>| {
+                                                items.write()[index].packed = e.checked();
+                                                save();
+                                            }
+                                        }
+                                    }
+                                    span {
+                                        class: if item.packed { "flex-1 line-through text-gray-400" } else { "flex-1" },
+                                        "{item.name}"
+                                    }
+                                    input {
+                                        class: "w-16 p-1 rounded border text-right bg-transparent",
+                                        r#type: "number",
+                                        min: "1",
+                                        value: "{item.quantity}",
+                                        onchange: {
+                                            let save = save.clone();
+                                            move |e: Event<FormData>| {
+                                                items.write()[index].quantity = e.value().parse().unwrap_or(1).max(1);
+                                                save();
+                                            }
+                                        }
+                                    }
+                                    button {
+                                        class: "text-red-500 text-sm",
+                                        onclick: {
+                                            let save = save.clone();
+                                            move |_| {
+                                                items.write().remove(index);
+                                                save();
+                                            }
+                                        },
+                                        "Remove"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            form {
+                class: "grid grid-cols-1 md:grid-cols-4 gap-4 mb-6",
+                onsubmit: handle_add,
+                input {
+                    class: field_class.clone(),
+                    placeholder: "Item",
+                    value: "{name}",
+                    oninput: move |e| name.set(e.value()),
+                    required: true
+                }
+                select {
+                    class: field_class.clone(),
+                    value: "{category}",
+                    oninput: move |e| category.set(e.value()),
+                    for option in PACKING_CATEGORIES {
+                        option { value: "{option}", "{option}" }
+                    }
+                }
+                input {
+                    class: field_class.clone(),
+                    r#type: "number",
+                    min: "1",
+                    value: "{quantity}",
+                    oninput: move |e| quantity.set(e.value())
+                }
+                button {
+                    class: "bg-blue-500 text-white px-4 py-2 rounded",
+                    r#type: "submit",
+                    "Add Item"
+                }
+            }
+
+            h4 { class: "font-semibold mb-2", "Templates" }
+            form {
+                class: "flex space-x-2 mb-4",
+                onsubmit: handle_save_template,
+                input {
+                    class: field_class.clone(),
+                    placeholder: "Template name, e.g. Beach weekend",
+                    value: "{template_name}",
+                    oninput: move |e| template_name.set(e.value()),
+                    required: true
+                }
+                button {
+                    class: "bg-gray-500 text-white px-4 py-2 rounded whitespace-nowrap",
+                    r#type: "submit",
+                    disabled: total == 0,
+                    "Save as Template"
+                }
+            }
+            ul {
+                class: "space-y-2",
+                for template in templates() {
+                    li {
+                        class: format!("flex justify-between items-center p-2 rounded {}", if dark_mode { "bg-gray-700" } else { "bg-white" }),
+                        span { "{template.name} ({template.items.len()} items)" }
+                        div {
+                            class: "space-x-2",
+                            button {
+                                class: "text-blue-500 text-sm",
+                                onclick: {
+                                    let handle_apply_template = handle_apply_template.clone();
+                                    let template_id = template.id.to_string();
+                                    move |_| handle_apply_template(template_id.clone())
+                                },
+                                "Add to List"
+                            }
+                            button {
+                                class: "text-red-500 text-sm",
+                                onclick: move |_| handle_delete_template(template.id.to_string()),
+                                "Delete"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,26 @@
+use crate::components::dashboard::trips::budget::BudgetSummary;
+use crate::components::dashboard::trips::expenses::ExpensesPanel;
+use crate::components::dashboard::trips::packing::PackingPanel;
+use crate::components::dashboard::trips::weather::WeatherBadge;
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
+use crate::currency::currency_codes;
+use crate::currency::RateTable;
+use crate::server::auth::controller::about_me;
+use crate::server::auth::controller::update_preferences;
+use crate::server::auth::response::UpdatePreferencesSchema;
+use crate::server::currency::controller::get_rate_table;
 use crate::server::trip::controller::get_details_for_trip;
+use crate::server::trip::controller::get_trip_for_user;
+use crate::server::trip::controller::update_detail_markdown;
 use crate::server::trip::model::Detail;
+use crate::server::trip::model::Trip;
 use crate::server::trip::request::GetDetailContentRequest;
+use crate::server::trip::request::GetTripForUserRequest;
+use crate::server::trip::request::UpdateDetailMarkdownRequest;
+use crate::server::weather::controller::get_trip_weather;
+use crate::server::weather::model::DayWeather;
+use crate::server::weather::request::GetTripWeatherRequest;
 use crate::theme::Theme;
 use crate::theme::THEME;
 use chrono::Utc;
@@ -17,15 +35,67 @@ pub struct CachedDetailData {
     pub timestamp: i64,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReaderTab {
+    Itinerary,
+    Expenses,
+    Packing,
+}
+
 pub const CHAPTERS_CACHE_KEY: &str = "details_cache";
 pub const CHAPTERS_CACHE_TIMEOUT: i64 = 2 * 60 * 60;
 
 #[component]
-pub fn ReadTripPanel(trip_id: String) -> Element {
+pub fn ReadTripPanel(user_token: Signal<String>, trip_id: String) -> Element {
     let dark_mode = *THEME.read() == Theme::Dark;
     let mut selected_detail = use_signal(|| None::<Detail>);
     let mut details = use_signal(Vec::<Detail>::new);
+    let mut trip = use_signal(|| None::<Trip>);
     let mut loading = use_signal(|| true);
+    let mut active_tab = use_signal(|| ReaderTab::Itinerary);
+    let mut rates = use_signal(RateTable::default);
+    let mut home_currency = use_signal(|| "USD".to_string());
+    let mut weather = use_signal(Vec::<DayWeather>::new);
+    let mut editing = use_signal(|| false);
+    let mut draft = use_signal(String::new);
+    let trip_id_clone = trip_id.clone();
+
+    let _ = use_resource(move || {
+        let trip_id = trip_id_clone.clone();
+        async move {
+            if user_token().is_empty() || trip_id.is_empty() {
+                return;
+            }
+
+            if let Ok(response) = about_me(user_token()).await {
+                home_currency.set(response.data.user.preferences.home_currency);
+            }
+            if let Ok(response) = get_rate_table().await {
+                rates.set(response.data);
+            }
+
+            if let Ok(response) = get_trip_for_user(GetTripForUserRequest {
+                token: user_token(),
+                trip_id: trip_id.clone(),
+            })
+            .await
+            {
+                let dated = response.data.start_date.is_some();
+                trip.set(Some(response.data));
+
+                if dated {
+                    if let Ok(response) = get_trip_weather(GetTripWeatherRequest {
+                        token: user_token(),
+                        trip_id,
+                    })
+                    .await
+                    {
+                        weather.set(response.data);
+                    }
+                }
+            }
+        }
+    });
 
     use_effect(move || {
         let trip_id_cloned = trip_id.clone();
@@ -46,6 +116,7 @@ pub fn ReadTripPanel(trip_id: String) -> Element {
             }
 
             if let Ok(response) = get_details_for_trip(GetDetailContentRequest {
+                token: user_token(),
                 trip_id: trip_id_cloned.clone(),
             })
             .await
@@ -69,9 +140,58 @@ pub fn ReadTripPanel(trip_id: String) -> Element {
         });
     });
 
+    let handle_home_currency_change = move |e: Event<FormData>| {
+        let currency = e.value();
+        home_currency.set(currency.clone());
+        spawn(async move {
+            if let Ok(response) = about_me(user_token()).await {
+                let mut preferences = response.data.user.preferences;
+                preferences.home_currency = currency;
+                let _ = update_preferences(UpdatePreferencesSchema {
+                    token: user_token(),
+                    preferences,
+                })
+                .await;
+            }
+        });
+    };
+
+    let handle_save_markdown = move |_| {
+        let Some(detail) = selected_detail() else {
+            return;
+        };
+        spawn(async move {
+            if let Ok(response) = update_detail_markdown(UpdateDetailMarkdownRequest {
+                token: user_token(),
+                detail_id: detail.id.to_string(),
+                markdown: draft(),
+            })
+            .await
+            {
+                let updated = response.data;
+                for item in details.write().iter_mut() {
+                    if item.id == updated.id {
+                        *item = updated.clone();
+                    }
+                }
+                let _ = LocalStorage::set(
+                    CHAPTERS_CACHE_KEY,
+                    &CachedDetailData {
+                        trip_id: updated.trip_id.to_string(),
+                        data: details(),
+                        timestamp: Utc::now().timestamp(),
+                    },
+                );
+                selected_detail.set(Some(updated));
+                editing.set(false);
+            }
+        });
+    };
+
     let mut handle_detail_click = {
         let mut selected_detail = selected_detail.clone();
         move |detail: Detail| {
+            editing.set(false);
             selected_detail.set(Some(detail));
         }
     };
@@ -102,6 +222,12 @@ pub fn ReadTripPanel(trip_id: String) -> Element {
                                 class: "flex-1 hidden sm:block",
                                 h4 { class: "text-lg", "{detail.title}" }
                                 p { class: "text-sm text-blue-500", "{detail.estimated_duration} minutes" }
+                                if let Some(date) = trip().and_then(|trip| trip.date_for_day(detail.day)) {
+                                    p { class: "text-xs text-gray-400", "{date.format(\"%a, %b %-d, %Y\")}" }
+                                    if let Some(day) = weather().into_iter().find(|day| day.date == date) {
+                                        WeatherBadge { weather: day, detailed: false }
+                                    }
+                                }
                             }
                         }
                     }
@@ -110,30 +236,115 @@ pub fn ReadTripPanel(trip_id: String) -> Element {
 
             div {
                 class: "flex-1 p-6 overflow-y-auto",
-                if let Some(detail) = selected_detail() {
-                    h2 { class: "text-2xl font-bold mb-4", "{detail.title}" }
-                    p { class: "text-sm text-blue-500 mb-6", "{detail.estimated_duration} minutes" }
+                if let Some(trip) = trip() {
+                    if let (Some(start), Some(end)) = (trip.start_date, trip.end_date) {
+                        p {
+                            class: "text-sm text-gray-400 mb-4",
+                            "{start.format(\"%B %-d, %Y\")} – {end.format(\"%B %-d, %Y\")}"
+                            if let Some(days) = trip.days() {
+                                " · {days} days"
+                            }
+                            if let Some(timezone) = trip.timezone.clone() {
+                                " · {timezone}"
+                            }
+                        }
+                    }
+                    BudgetSummary { trip: trip.clone(), details: details(), rates: rates(), home_currency: home_currency() }
                     div {
-                        class: "prose dark:prose-invert",
-                        dangerous_inner_html: detail.html,
+                        class: "flex space-x-2 mb-4",
+                        for (tab, label) in [(ReaderTab::Itinerary, "Itinerary"), (ReaderTab::Expenses, "Expenses"), (ReaderTab::Packing, "Packing")] {
+                            button {
+                                class: format!("px-4 py-2 rounded {}", if active_tab() == tab { "bg-blue-500 text-white" } else { "bg-gray-500" }),
+                                onclick: move |_| active_tab.set(tab),
+                                "{label}"
+                            }
+                        }
+                        select {
+                            class: format!("ml-auto p-2 rounded {}", if dark_mode { "bg-gray-700 text-white" } else { "bg-gray-100" }),
+                            title: "Home currency",
+                            value: "{home_currency}",
+                            oninput: handle_home_currency_change,
+                            for code in currency_codes() {
+                                option { value: "{code}", "{code}" }
+                            }
+                        }
+                    }
+                    if active_tab() == ReaderTab::Expenses {
+                        ExpensesPanel { user_token, trip: trip.clone(), details: details(), rates: rates(), home_currency: home_currency() }
                     }
-                } else {
-                    p {
-                        class: "flex items-center space-x-2 px-4 py-2 rounded",
-                        if loading() {
-                            Spinner {
-                                aria_label: "Loading spinner".to_string(),
-                                size: SpinnerSize::Md,
-                                dark_mode: true,
+                    if active_tab() == ReaderTab::Packing {
+                        PackingPanel { user_token, trip_id: trip.id.to_string() }
+                    }
+                }
+                if active_tab() == ReaderTab::Itinerary {
+                    if let Some(detail) = selected_detail() {
+                        h2 { class: "text-2xl font-bold mb-4", "{detail.title}" }
+                        p {
+                            class: "text-sm text-blue-500 mb-6",
+                            "{detail.estimated_duration} minutes"
+                            if let Some(trip) = trip() {
+                                " · {detail.estimated_cost:.2} {trip.currency}"
+                                if let Some(date) = trip.date_for_day(detail.day) {
+                                    " · {date.format(\"%A, %B %-d, %Y\")}"
+                                }
+                            }
+                        }
+                        if let Some(day) = trip().and_then(|trip| trip.date_for_day(detail.day)).and_then(|date| weather().into_iter().find(|day| day.date == date)) {
+                            div { class: "mb-6", WeatherBadge { weather: day, detailed: true } }
+                        }
+                        if editing() {
+                            textarea {
+                                class: format!("w-full h-96 p-2 mb-2 font-mono text-sm border rounded-md {}", if dark_mode { "bg-gray-900 border-gray-700" } else { "border-gray-300" }),
+                                value: "{draft}",
+                                oninput: move |e| draft.set(e.value()),
+                            }
+                            div {
+                                class: "flex space-x-2",
+                                button {
+                                    class: "bg-blue-500 text-white px-4 py-2 rounded",
+                                    onclick: handle_save_markdown,
+                                    "Save"
+                                }
+                                button {
+                                    class: "bg-gray-500 text-white px-4 py-2 rounded",
+                                    onclick: move |_| editing.set(false),
+                                    "Cancel"
+                                }
                             }
-                            span { "Loading trip's details..." }
                         } else {
-                            Spinner {
-                                aria_label: "Loading spinner".to_string(),
-                                size: SpinnerSize::Md,
-                                dark_mode: true,
+                            if !detail.markdown.is_empty() {
+                                button {
+                                    class: "mb-4 text-sm text-blue-500",
+                                    onclick: move |_| {
+                                        draft.set(detail.markdown.clone());
+                                        editing.set(true);
+                                    },
+                                    "Edit"
+                                }
+                            }
+                            div {
+                                class: "prose dark:prose-invert",
+                                dangerous_inner_html: detail.html,
+                            }
+                        }
+                    } else {
+                        p {
+                            class: "flex items-center space-x-2 px-4 py-2 rounded",
+                            if loading() {
+                                Spinner {
+                                    aria_label: "Loading spinner".to_string(),
+                                    size: SpinnerSize::Md,
+                                    dark_mode: true,
+                                }
+                                span { "Loading trip's details..." }
+                            } else {
+                                Spinner {
+                                    aria_label: "Loading spinner".to_string(),
+                                    size: SpinnerSize::Md,
+                                    dark_mode: true,
+                                }
+                                span { "No details found! Generating..." }
                             }
-                            span { "No details found! Generating..." }
                         }
                     }
                 }
@@ -0,0 +1,39 @@
+use crate::server::weather::model::DayWeather;
+use crate::server::weather::model::WeatherSource;
+use dioxus::prelude::*;
+
+fn weather_icon(weather: &DayWeather) -> &'static str {
+    let summary = weather.summary.to_lowercase();
+    if summary.contains("thunder") {
+        "⛈️"
+    } else if summary.contains("snow") {
+        "🌨️"
+    } else if summary.contains("rain") || summary.contains("shower") || summary.contains("drizzle")
+    {
+        "🌧️"
+    } else if summary.contains("cloud") || summary.contains("overcast") || summary.contains("fog") {
+        "☁️"
+    } else {
+        "☀️"
+    }
+}
+
+#[component]
+pub fn WeatherBadge(weather: DayWeather, detailed: bool) -> Element {
+    let label = match weather.source {
+        WeatherSource::Forecast => "Forecast",
+        WeatherSource::Climate => "Typical",
+    };
+
+    rsx! {
+        span {
+            class: "inline-flex items-center space-x-1 text-xs text-gray-400",
+            title: "{label}: {weather.describe()}",
+            span { "{weather_icon(&weather)}" }
+            span { "{weather.temp_max:.0}° / {weather.temp_min:.0}°" }
+            if detailed {
+                span { "· {weather.summary} · {weather.precipitation_mm:.1} mm ({label})" }
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::components::admin::error_message;
+use crate::server::auth::controller::{
+    about_me, begin_two_factor_setup, confirm_two_factor_setup, disable_two_factor,
+    regenerate_backup_codes,
+};
+use crate::server::auth::response::{TwoFactorCodeSchema, TwoFactorSetup};
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// Two-factor authentication settings: enrollment with a QR code, backup codes and turning it
+/// off again.
+#[component]
+pub fn TwoFactorPanel() -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut enabled = use_signal(|| false);
+    let mut backup_codes_left = use_signal(|| 0usize);
+    let mut setup = use_signal(|| None::<TwoFactorSetup>);
+    let mut backup_codes = use_signal(Vec::<String>::new);
+    let mut code = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut refresh = use_signal(|| 0u32);
+
+    let _ = use_resource(move || async move {
+        refresh();
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = about_me(token).await {
+            let two_factor = response.data.user.two_factor;
+            enabled.set(two_factor.as_ref().is_some_and(|tf| tf.enabled));
+            backup_codes_left.set(two_factor.map_or(0, |tf| tf.backup_codes.len()));
+        }
+    });
+
+    let handle_begin = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match begin_two_factor_setup(token).await {
+                Ok(response) => {
+                    error.set(None);
+                    backup_codes.set(Vec::new());
+                    setup.set(Some(response.data));
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let handle_confirm = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match confirm_two_factor_setup(TwoFactorCodeSchema {
+                token,
+                code: code(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    error.set(None);
+                    setup.set(None);
+                    code.set(String::new());
+                    backup_codes.set(response.data);
+                    refresh.set(refresh() + 1);
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let handle_regenerate = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match regenerate_backup_codes(TwoFactorCodeSchema {
+                token,
+                code: code(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    error.set(None);
+                    code.set(String::new());
+                    backup_codes.set(response.data);
+                    refresh.set(refresh() + 1);
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let handle_disable = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match disable_two_factor(TwoFactorCodeSchema {
+                token,
+                code: code(),
+            })
+            .await
+            {
+                Ok(_) => {
+                    error.set(None);
+                    code.set(String::new());
+                    backup_codes.set(Vec::new());
+                    refresh.set(refresh() + 1);
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    let input_class = format!(
+        "p-2 border border-gray-300 rounded-md tracking-widest {}",
+        if dark_mode {
+            "bg-gray-700 text-white"
+        } else {
+            "bg-white text-gray-900"
+        }
+    );
+
+    rsx! {
+        div { class: "mt-6",
+            h3 { class: "text-lg font-semibold mb-4", "Two-Factor Authentication" }
+            if let Some(error) = error() {
+                p { class: "text-red-500 mb-2", "{error}" }
+            }
+            if !backup_codes().is_empty() {
+                div { class: format!("p-3 mb-4 rounded {}", if dark_mode { "bg-gray-900" } else { "bg-gray-100" }),
+                    p { class: "text-sm mb-2",
+                        "Save these backup codes somewhere safe. Each one signs you in once if you lose your authenticator, and they will not be shown again."
+                    }
+                    ul { class: "grid grid-cols-2 gap-1 font-mono",
+                        for backup_code in backup_codes() {
+                            li { key: "{backup_code}", "{backup_code}" }
+                        }
+                    }
+                }
+            }
+            if let Some(setup) = setup() {
+                p { class: "text-sm mb-2",
+                    "Scan this code with your authenticator app, then enter the 6-digit code it shows."
+                }
+                div { class: "mb-2 bg-white inline-block", dangerous_inner_html: "{setup.qr_svg}" }
+                p { class: "text-xs text-gray-500 mb-4 break-all",
+                    "Can't scan it? Enter this key instead: "
+                    span { class: "font-mono", "{setup.secret}" }
+                }
+                div { class: "flex space-x-2",
+                    input {
+                        class: "{input_class}",
+                        r#type: "text",
+                        autocomplete: "one-time-code",
+                        placeholder: "123456",
+                        value: "{code}",
+                        oninput: move |e| code.set(e.value()),
+                    }
+                    button {
+                        class: "px-3 py-1 rounded bg-blue-600 text-white text-sm",
+                        onclick: handle_confirm,
+                        "Confirm"
+                    }
+                }
+            } else if enabled() {
+                p { class: "text-sm mb-4",
+                    span { class: "text-green-500 font-medium", "On" }
+                    " · {backup_codes_left} backup codes left"
+                }
+                p { class: "text-xs text-gray-500 mb-2",
+                    "Enter a current code from your authenticator app or a backup code to make changes."
+                }
+                div { class: "flex flex-wrap gap-2",
+                    input {
+                        class: "{input_class}",
+                        r#type: "text",
+                        autocomplete: "one-time-code",
+                        placeholder: "Authentication code",
+                        value: "{code}",
+                        oninput: move |e| code.set(e.value()),
+                    }
+                    button {
+                        class: "px-3 py-1 rounded bg-gray-200 text-gray-800 text-sm",
+                        onclick: handle_regenerate,
+                        "New backup codes"
+                    }
+                    button {
+                        class: "px-3 py-1 rounded bg-red-600 text-white text-sm",
+                        onclick: handle_disable,
+                        "Turn off"
+                    }
+                }
+            } else {
+                p { class: "text-sm text-gray-500 mb-4",
+                    "Protect your account with a code from an authenticator app in addition to your password."
+                }
+                button {
+                    class: "px-3 py-1 rounded bg-blue-600 text-white text-sm",
+                    onclick: handle_begin,
+                    "Set up two-factor authentication"
+                }
+            }
+        }
+    }
+}
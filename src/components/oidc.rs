@@ -0,0 +1,57 @@
+use crate::components::admin::error_message;
+use crate::server::auth::controller::{oidc_authorize, oidc_providers};
+use crate::server::auth::response::OidcProviderInfo;
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// Where the login page leaves its "Remember me" choice while the browser is at the provider.
+pub const OIDC_REMEMBER_KEY: &str = "oidc_remember";
+
+/// "Continue with ..." buttons for every configured sign-in provider. Renders nothing when no
+/// provider is configured.
+#[component]
+pub fn OidcButtons(remember: bool) -> Element {
+    let mut providers = use_signal(Vec::<OidcProviderInfo>::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        if let Ok(response) = oidc_providers().await {
+            providers.set(response.data);
+        }
+    });
+
+    let handle_continue = move |provider: String| {
+        spawn(async move {
+            let _ = SessionStorage::set(OIDC_REMEMBER_KEY, remember);
+            match oidc_authorize(provider).await {
+                Ok(response) => {
+                    let url = serde_json::to_string(&response.data).unwrap_or_default();
+                    let _ = eval(&format!("window.location.href = {};", url));
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    };
+
+    if providers().is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "flex flex-col md:flex-row gap-4 w-full mb-6",
+            for provider in providers() {
+                button {
+                    key: "{provider.id}",
+                    r#type: "button",
+                    class: "w-full py-2 border rounded-md border-gray-300 bg-gray-100 text-gray-800 hover:bg-gray-200 whitespace-nowrap",
+                    onclick: move |_| handle_continue(provider.id.clone()),
+                    "Continue with {provider.name}"
+                }
+            }
+        }
+        if let Some(error) = error() {
+            p { class: "text-red-500 text-sm mb-4", "{error}" }
+        }
+        div { class: "text-center text-gray-500 mb-6", "or" }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::components::admin::error_message;
+use crate::server::auth::controller::verify_two_factor_login;
+use crate::server::auth::response::TwoFactorLoginSchema;
+use crate::session::store_tokens;
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+
+/// The second sign-in step for accounts with two-factor authentication.
+#[component]
+pub fn TwoFactorPrompt(challenge: String, remember: bool) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let navigator = use_navigator();
+    let mut code = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut loading = use_signal(|| false);
+
+    let handle_submit = move |_| {
+        let challenge = challenge.clone();
+        loading.set(true);
+        spawn(async move {
+            match verify_two_factor_login(TwoFactorLoginSchema {
+                challenge,
+                code: code(),
+            })
+            .await
+            {
+                Ok(response) => match store_tokens(&response.data, remember) {
+                    Some(_) => {
+                        navigator.push("/dashboard");
+                    }
+                    None => error.set(Some("Token not found".into())),
+                },
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+            loading.set(false);
+        });
+    };
+
+    rsx! {
+        form { class: "w-full", onsubmit: handle_submit,
+            p { class: "text-sm text-gray-500 mb-4",
+                "Enter the 6-digit code from your authenticator app, or one of your backup codes."
+            }
+            input {
+                class: format!("w-full p-3 mb-4 border border-gray-300 rounded-md shadow-sm tracking-widest {}",
+                    if dark_mode { "bg-gray-700 text-white" } else { "bg-white text-gray-900" }
+                ),
+                r#type: "text",
+                autocomplete: "one-time-code",
+                placeholder: "Authentication code",
+                value: "{code}",
+                required: true,
+                oninput: move |e| code.set(e.value()),
+            }
+            if let Some(error) = error() {
+                p { class: "text-red-500 text-sm mb-4", "{error}" }
+            }
+            button {
+                class: "w-full py-2 bg-blue-600 hover:bg-blue-700 text-white rounded-md",
+                r#type: "submit",
+                disabled: loading(),
+                if loading() { "Verifying..." } else { "Verify" }
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Currency {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub symbol: &'static str,
+    pub minor_units: u8,
+}
+
+#[rustfmt::skip]
+pub const CURRENCIES: &[Currency] = &[
+    Currency { code: "USD", name: "US Dollar", symbol: "$", minor_units: 2 },
+    Currency { code: "EUR", name: "Euro", symbol: "€", minor_units: 2 },
+    Currency { code: "GBP", name: "Pound Sterling", symbol: "£", minor_units: 2 },
+    Currency { code: "JPY", name: "Yen", symbol: "¥", minor_units: 0 },
+    Currency { code: "CHF", name: "Swiss Franc", symbol: "CHF", minor_units: 2 },
+    Currency { code: "CAD", name: "Canadian Dollar", symbol: "CA$", minor_units: 2 },
+    Currency { code: "AUD", name: "Australian Dollar", symbol: "A$", minor_units: 2 },
+    Currency { code: "NZD", name: "New Zealand Dollar", symbol: "NZ$", minor_units: 2 },
+    Currency { code: "CNY", name: "Yuan Renminbi", symbol: "¥", minor_units: 2 },
+    Currency { code: "HKD", name: "Hong Kong Dollar", symbol: "HK$", minor_units: 2 },
+    Currency { code: "SGD", name: "Singapore Dollar", symbol: "S$", minor_units: 2 },
+    Currency { code: "INR", name: "Indian Rupee", symbol: "₹", minor_units: 2 },
+    Currency { code: "KRW", name: "Won", symbol: "₩", minor_units: 0 },
+    Currency { code: "THB", name: "Baht", symbol: "฿", minor_units: 2 },
+    Currency { code: "IDR", name: "Rupiah", symbol: "Rp", minor_units: 2 },
+    Currency { code: "MYR", name: "Malaysian Ringgit", symbol: "RM", minor_units: 2 },
+    Currency { code: "PHP", name: "Philippine Peso", symbol: "₱", minor_units: 2 },
+    Currency { code: "VND", name: "Dong", symbol: "₫", minor_units: 0 },
+    Currency { code: "AED", name: "UAE Dirham", symbol: "AED", minor_units: 2 },
+    Currency { code: "SAR", name: "Saudi Riyal", symbol: "SAR", minor_units: 2 },
+    Currency { code: "QAR", name: "Qatari Rial", symbol: "QAR", minor_units: 2 },
+    Currency { code: "LBP", name: "Lebanese Pound", symbol: "L£", minor_units: 2 },
+    Currency { code: "EGP", name: "Egyptian Pound", symbol: "E£", minor_units: 2 },
+    Currency { code: "TRY", name: "Turkish Lira", symbol: "₺", minor_units: 2 },
+    Currency { code: "MAD", name: "Moroccan Dirham", symbol: "MAD", minor_units: 2 },
+    Currency { code: "ZAR", name: "Rand", symbol: "R", minor_units: 2 },
+    Currency { code: "KES", name: "Kenyan Shilling", symbol: "KSh", minor_units: 2 },
+    Currency { code: "BRL", name: "Brazilian Real", symbol: "R$", minor_units: 2 },
+    Currency { code: "MXN", name: "Mexican Peso", symbol: "MX$", minor_units: 2 },
+    Currency { code: "ARS", name: "Argentine Peso", symbol: "AR$", minor_units: 2 },
+    Currency { code: "CLP", name: "Chilean Peso", symbol: "CL$", minor_units: 0 },
+    Currency { code: "COP", name: "Colombian Peso", symbol: "CO$", minor_units: 2 },
+    Currency { code: "PEN", name: "Sol", symbol: "S/", minor_units: 2 },
+    Currency { code: "SEK", name: "Swedish Krona", symbol: "kr", minor_units: 2 },
+    Currency { code: "NOK", name: "Norwegian Krone", symbol: "kr", minor_units: 2 },
+    Currency { code: "DKK", name: "Danish Krone", symbol: "kr", minor_units: 2 },
+    Currency { code: "PLN", name: "Zloty", symbol: "zł", minor_units: 2 },
+    Currency { code: "CZK", name: "Czech Koruna", symbol: "Kč", minor_units: 2 },
+    Currency { code: "HUF", name: "Forint", symbol: "Ft", minor_units: 2 },
+    Currency { code: "ISK", name: "Iceland Krona", symbol: "kr", minor_units: 0 },
+];
+
+pub fn find_currency(code: &str) -> Option<&'static Currency> {
+    CURRENCIES
+        .iter()
+        .find(|currency| currency.code.eq_ignore_ascii_case(code))
+}
+
+pub fn currency_codes() -> Vec<&'static str> {
+    CURRENCIES.iter().map(|currency| currency.code).collect()
+}
+
+/// Formats an amount using the currency's minor units, e.g. `1,234.50 EUR` or `1,235 JPY`.
+pub fn format_amount(amount: f64, code: &str) -> String {
+    let minor_units = find_currency(code).map_or(2, |currency| currency.minor_units) as usize;
+    let formatted = format!("{:.*}", minor_units, amount.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let mut grouped = String::new();
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if !fraction.is_empty() {
+        grouped.push('.');
+        grouped.push_str(fraction);
+    }
+
+    format!(
+        "{}{} {}",
+        if amount < 0.0 { "-" } else { "" },
+        grouped,
+        code
+    )
+}
+
+/// Exchange rates expressed as units of each currency per one unit of `base`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateTable {
+    #[serde(alias = "base_code")]
+    pub base: String,
+    pub rates: HashMap<String, f64>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RateTable {
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let mut table: RateTable = serde_json::from_str(json)?;
+        table.base = table.base.to_uppercase();
+        table.rates.insert(table.base.clone(), 1.0);
+        Ok(table)
+    }
+
+    /// Parses `currency,rate` rows relative to `base`. A header row is skipped.
+    pub fn from_csv(csv: &str, base: &str) -> anyhow::Result<Self> {
+        let mut rates = HashMap::new();
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (code, rate) = line
+                .split_once(',')
+                .ok_or_else(|| anyhow::anyhow!("line {}: expected `currency,rate`", index + 1))?;
+            match rate.trim().parse::<f64>() {
+                Ok(rate) => {
+                    rates.insert(code.trim().to_uppercase(), rate);
+                }
+                Err(_) if index == 0 => continue,
+                Err(e) => anyhow::bail!("line {}: {}", index + 1, e),
+            }
+        }
+        rates.insert(base.to_uppercase(), 1.0);
+
+        Ok(RateTable {
+            base: base.to_uppercase(),
+            rates,
+            updated_at: Utc::now(),
+        })
+    }
+
+    pub fn rate(&self, code: &str) -> Option<f64> {
+        self.rates
+            .get(&code.to_uppercase())
+            .copied()
+            .filter(|rate| *rate > 0.0)
+    }
+
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(amount);
+        }
+        Some(amount / self.rate(from)? * self.rate(to)?)
+    }
+}
+
+impl Default for RateTable {
+    fn default() -> Self {
+        RateTable::from_json(include_str!("../data/rates.json"))
+            .expect("bundled rate table must be valid")
+    }
+}
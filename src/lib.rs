@@ -1,11 +1,45 @@
 #[cfg(feature = "server")]
 pub(crate) mod ai;
+#[cfg(feature = "server")]
+pub(crate) mod authz;
+#[cfg(feature = "server")]
+pub mod billing;
 pub mod components;
+pub mod currency;
 #[cfg(feature = "server")]
 pub(crate) mod db;
+#[cfg(feature = "server")]
+pub(crate) mod llm;
+#[cfg(feature = "server")]
+pub(crate) mod mailer;
+#[cfg(feature = "server")]
+pub(crate) mod markdown;
+#[cfg(feature = "server")]
+pub(crate) mod oidc;
 pub(crate) mod pages;
+#[cfg(feature = "server")]
+pub(crate) mod prompts;
+#[cfg(feature = "server")]
+pub(crate) mod quota;
+#[cfg(feature = "server")]
+pub(crate) mod rates;
 pub mod router;
+#[cfg(feature = "server")]
+pub mod sanitize;
+#[cfg(feature = "server")]
+pub(crate) mod security;
 pub(crate) mod server;
+pub(crate) mod session;
+#[cfg(feature = "server")]
+pub mod storage;
 pub mod theme;
 #[cfg(feature = "server")]
+pub(crate) mod tokens;
+#[cfg(feature = "server")]
+pub(crate) mod two_factor;
+#[cfg(feature = "server")]
 pub(crate) mod unsplash;
+#[cfg(feature = "server")]
+pub(crate) mod verification;
+#[cfg(feature = "server")]
+pub(crate) mod weather;
@@ -0,0 +1,199 @@
+use crate::ai::get_ai;
+use crate::db::get_client;
+use crate::quota::{reserve_tokens, settle_tokens};
+use crate::server::auth::model::User;
+use crate::server::conversation::controller::{get_converse_output_text, BedrockConverseError};
+use crate::server::llm::model::ModelInfo;
+use aws_sdk_bedrockruntime::types::{
+    ContentBlock, ConversationRole, InferenceConfiguration, Message,
+};
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use dioxus::prelude::ServerFnError;
+use dioxus_logger::tracing;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::time::Instant;
+use tokio::sync::OnceCell;
+
+/// One model invocation, successful or not, as recorded in the `llm_calls` collection.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LlmCall {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: Option<ObjectId>,
+    /// Prompt template that produced the request, e.g. `trip_outline`.
+    pub purpose: String,
+    pub model: String,
+    pub model_id: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub latency_ms: u64,
+    pub success: bool,
+    pub error: Option<String>,
+    /// Estimated cost in USD from the registry's per-token prices.
+    pub cost: f64,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+static MODELS: OnceCell<Vec<ModelInfo>> = OnceCell::const_new();
+
+fn parse_models(json: &str) -> anyhow::Result<Vec<ModelInfo>> {
+    let models: Vec<ModelInfo> = serde_json::from_str(json)?;
+    if models.is_empty() {
+        anyhow::bail!("model registry is empty");
+    }
+    Ok(models)
+}
+
+fn bundled_models() -> Vec<ModelInfo> {
+    parse_models(include_str!("../data/models.json")).expect("bundled model registry must be valid")
+}
+
+async fn init_models() -> Vec<ModelInfo> {
+    match env::var("MODEL_REGISTRY_FILE") {
+        Ok(path) => std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| parse_models(&json))
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to load model registry from {}: {}", path, e);
+                bundled_models()
+            }),
+        Err(_) => bundled_models(),
+    }
+}
+
+/// All models in the registry; the first entry is the default.
+pub async fn get_models() -> &'static [ModelInfo] {
+    MODELS.get_or_init(init_models).await
+}
+
+/// Looks a model up by display name, alias or provider model ID. An empty name
+/// resolves to the default model.
+pub async fn resolve_model(name: &str) -> Result<ModelInfo, ServerFnError> {
+    let models = get_models().await;
+    if name.trim().is_empty() {
+        return Ok(models[0].clone());
+    }
+    models
+        .iter()
+        .find(|model| model.matches(name))
+        .cloned()
+        .ok_or(ServerFnError::new("Unsupported model"))
+}
+
+async fn record_call(call: LlmCall) {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    if let Err(e) = db.collection::<LlmCall>("llm_calls").insert_one(call).await {
+        tracing::error!("Failed to record LLM call: {}", e);
+    }
+}
+
+fn text_message(role: ConversationRole, text: String) -> Result<Message, ServerFnError> {
+    Message::builder()
+        .role(role)
+        .content(ContentBlock::Text(text))
+        .build()
+        .map_err(|_| ServerFnError::new("failed to build message"))
+}
+
+/// A plain-text message from the user, for `converse`.
+pub fn user_message(text: String) -> Result<Message, ServerFnError> {
+    text_message(ConversationRole::User, text)
+}
+
+/// A plain-text reply from the model, for replaying earlier turns to `converse`.
+pub fn assistant_message(text: String) -> Result<Message, ServerFnError> {
+    text_message(ConversationRole::Assistant, text)
+}
+
+/// Rough prompt size, at about four characters per token.
+fn estimate_input_tokens(messages: &[Message]) -> u64 {
+    let chars: usize = messages
+        .iter()
+        .flat_map(|message| message.content())
+        .filter_map(|block| block.as_text().ok())
+        .map(|text| text.len())
+        .sum();
+    chars.div_ceil(4) as u64
+}
+
+/// Sends `messages` to `model` and returns the text of the reply. Every call is recorded in
+/// `llm_calls` with its token usage, latency, outcome and estimated cost.
+///
+/// The prompt and the largest possible reply are taken from the user's token allowance
+/// before the call, then corrected to the tokens actually used.
+pub async fn converse(
+    user: &User,
+    purpose: &str,
+    model: &ModelInfo,
+    messages: Vec<Message>,
+    max_tokens: i32,
+) -> Result<String, ServerFnError> {
+    let max_tokens = max_tokens.clamp(1, model.max_output_tokens as i32);
+    let base = &get_models().await[0];
+    let reservation = reserve_tokens(
+        user,
+        model.quota_tokens(base, estimate_input_tokens(&messages), max_tokens as u64),
+    )
+    .await?;
+
+    let started = Instant::now();
+    let response = get_ai()
+        .await
+        .lock()
+        .await
+        .converse()
+        .model_id(&model.model_id)
+        .set_messages(Some(messages))
+        .inference_config(
+            InferenceConfiguration::builder()
+                .max_tokens(max_tokens)
+                .build(),
+        )
+        .send()
+        .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let (input_tokens, output_tokens) = response
+        .as_ref()
+        .ok()
+        .and_then(|output| output.usage())
+        .map(|usage| (usage.input_tokens() as u64, usage.output_tokens() as u64))
+        .unwrap_or((0, 0));
+    settle_tokens(
+        reservation,
+        model.quota_tokens(base, input_tokens, output_tokens),
+    )
+    .await;
+
+    let result = match response {
+        Ok(output) => get_converse_output_text(output).map_err(ServerFnError::from),
+        Err(e) => Err(e
+            .as_service_error()
+            .map(BedrockConverseError::from)
+            .unwrap_or_else(|| BedrockConverseError("Unknown service error".into()))
+            .into()),
+    };
+
+    record_call(LlmCall {
+        id: ObjectId::new(),
+        user: Some(user.id),
+        purpose: purpose.to_string(),
+        model: model.name.clone(),
+        model_id: model.model_id.clone(),
+        input_tokens,
+        output_tokens,
+        latency_ms,
+        success: result.is_ok(),
+        error: result.as_ref().err().map(ToString::to_string),
+        cost: model.cost(input_tokens, output_tokens),
+        created_at: Utc::now(),
+    })
+    .await;
+
+    result
+}
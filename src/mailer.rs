@@ -0,0 +1,184 @@
+use crate::tokens::random_hex;
+use anyhow::{bail, Context};
+use chrono::prelude::*;
+use dioxus_logger::tracing;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::env;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::OnceCell;
+
+const DEFAULT_FROM: &str = "Tripper <no-reply@localhost>";
+const DEFAULT_OUTBOX_DIR: &str = "outbox";
+
+/// A plain-text email to a single recipient.
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+}
+
+impl Email {
+    fn message(&self, from: &Mailbox) -> anyhow::Result<Message> {
+        let to: Mailbox = self.to.parse().context("invalid recipient address")?;
+        Ok(Message::builder()
+            .from(from.clone())
+            .to(to)
+            .subject(&self.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(self.text.clone())?)
+    }
+}
+
+fn from_env() -> anyhow::Result<Mailbox> {
+    env::var("MAIL_FROM")
+        .unwrap_or_else(|_| DEFAULT_FROM.to_string())
+        .parse()
+        .context("MAIL_FROM must be an email address")
+}
+
+pub(crate) trait Mailer {
+    async fn send(&self, email: &Email) -> anyhow::Result<()>;
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmtpTls {
+    /// TLS from the first byte, usually on port 465.
+    Implicit,
+    /// Plain connection upgraded with `STARTTLS`, usually on port 587.
+    StartTls,
+    /// No encryption; only for local relays.
+    None,
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn from_env(host: String) -> anyhow::Result<Self> {
+        let tls = match env::var("SMTP_TLS").unwrap_or_default().as_str() {
+            "" | "starttls" => SmtpTls::StartTls,
+            "implicit" | "tls" => SmtpTls::Implicit,
+            "none" => SmtpTls::None,
+            other => bail!("unknown SMTP_TLS mode `{}`", other),
+        };
+        let port = match env::var("SMTP_PORT") {
+            Ok(port) => port.parse().context("SMTP_PORT must be a port number")?,
+            Err(_) if tls == SmtpTls::Implicit => 465,
+            Err(_) => 587,
+        };
+
+        let mut transport = match tls {
+            SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)?,
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+        }
+        .port(port);
+        if let Some(username) = env::var("SMTP_USERNAME")
+            .ok()
+            .filter(|username| !username.is_empty())
+        {
+            transport = transport.credentials(Credentials::new(
+                username,
+                env::var("SMTP_PASSWORD").unwrap_or_default(),
+            ));
+        }
+
+        Ok(Self {
+            transport: transport.build(),
+            from: from_env()?,
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> anyhow::Result<()> {
+        self.transport
+            .send(email.message(&self.from)?)
+            .await
+            .context("SMTP server rejected the message")?;
+        Ok(())
+    }
+}
+
+/// Writes each email to an `.eml` file instead of sending it, for local development.
+pub struct OutboxMailer {
+    dir: PathBuf,
+    from: Mailbox,
+}
+
+impl OutboxMailer {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            dir: env::var("MAIL_OUTBOX_DIR")
+                .unwrap_or_else(|_| DEFAULT_OUTBOX_DIR.to_string())
+                .into(),
+            from: from_env()?,
+        })
+    }
+}
+
+impl Mailer for OutboxMailer {
+    async fn send(&self, email: &Email) -> anyhow::Result<()> {
+        let message = email.message(&self.from)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let recipient: String = message.envelope().to()[0]
+            .to_string()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        // The random suffix keeps mails sent in the same millisecond apart, and `create_new`
+        // makes a collision fail loudly rather than overwrite an earlier mail.
+        let path = self.dir.join(format!(
+            "{}-{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            recipient,
+            random_hex(4)
+        ));
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await?;
+        file.write_all(&message.formatted()).await?;
+        tracing::info!("Wrote email to {}", path.display());
+        Ok(())
+    }
+}
+
+/// The mailer selected by the environment: SMTP when `SMTP_HOST` is set, the outbox otherwise.
+pub enum ConfiguredMailer {
+    Smtp(SmtpMailer),
+    Outbox(OutboxMailer),
+}
+
+impl ConfiguredMailer {
+    pub fn from_env() -> anyhow::Result<Self> {
+        match env::var("SMTP_HOST") {
+            Ok(host) if !host.is_empty() => Ok(Self::Smtp(SmtpMailer::from_env(host)?)),
+            _ => Ok(Self::Outbox(OutboxMailer::from_env()?)),
+        }
+    }
+}
+
+impl Mailer for ConfiguredMailer {
+    async fn send(&self, email: &Email) -> anyhow::Result<()> {
+        match self {
+            Self::Smtp(mailer) => mailer.send(email).await,
+            Self::Outbox(mailer) => mailer.send(email).await,
+        }
+    }
+}
+
+static MAILER: OnceCell<ConfiguredMailer> = OnceCell::const_new();
+
+pub async fn get_mailer() -> anyhow::Result<&'static ConfiguredMailer> {
+    MAILER
+        .get_or_try_init(|| async { ConfiguredMailer::from_env() })
+        .await
+}
@@ -19,6 +19,7 @@ fn main() {
     {
         use axum::http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
         use axum::http::Method;
+        use axum::routing::{get, post};
         use axum::{Extension, Router};
         use dotenv::dotenv;
         use std::sync::Arc;
@@ -30,6 +31,12 @@ fn main() {
         tokio::runtime::Runtime::new()
             .unwrap()
             .block_on(async move {
+                tokio::spawn(async {
+                    if let Err(e) = tripper::sanitize::backfill_sanitized_html().await {
+                        tracing::error!("HTML sanitizing backfill failed: {}", e);
+                    }
+                });
+
                 let cors = CorsLayer::new()
                     .allow_origin(Any)
                     // TODO
@@ -44,6 +51,11 @@ fn main() {
                     .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
 
                 let app = Router::new()
+                    .route(
+                        "/api/billing/webhook",
+                        post(tripper::billing::billing_webhook),
+                    )
+                    .route("/media/*key", get(tripper::storage::serve_media))
                     .layer(cors)
                     .serve_dioxus_application(ServeConfig::builder().build(), || {
                         VirtualDom::new(App)
@@ -53,9 +65,13 @@ fn main() {
                 let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 3000));
                 let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
 
-                axum::serve(listener, app.into_make_service())
-                    .await
-                    .unwrap();
+                // The peer address is used to rate limit sign-in attempts.
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+                )
+                .await
+                .unwrap();
             });
     }
 }
@@ -0,0 +1,66 @@
+use crate::sanitize::sanitize_html;
+use pulldown_cmark::{html, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use std::collections::HashMap;
+
+fn heading_class(level: HeadingLevel) -> &'static str {
+    match level {
+        HeadingLevel::H1 => "text-3xl font-bold mt-6 mb-4",
+        HeadingLevel::H2 => "text-2xl font-semibold mt-6 mb-3",
+        HeadingLevel::H3 => "text-xl font-semibold mt-4 mb-2",
+        _ => "text-lg font-medium mt-4 mb-2",
+    }
+}
+
+/// Lowercase, dash-separated anchor for a heading, e.g. `Day 1: Old Town` -> `day-1-old-town`.
+fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Renders stored detail markdown to sanitized HTML. Headings get consistent classes and
+/// unique `id` anchors so they can be linked to.
+pub fn render_markdown(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut events: Vec<Event> = Parser::new_ext(markdown, options).collect();
+    let mut used: HashMap<String, usize> = HashMap::new();
+
+    let mut index = 0;
+    while index < events.len() {
+        if let Event::Start(Tag::Heading { level, .. }) = events[index] {
+            let text: String = events[index + 1..]
+                .iter()
+                .take_while(|event| !matches!(event, Event::End(TagEnd::Heading(_))))
+                .filter_map(|event| match event {
+                    Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+                    _ => None,
+                })
+                .collect();
+
+            let mut slug = slugify(&text);
+            if slug.is_empty() {
+                slug = "section".to_string();
+            }
+            let count = used.entry(slug.clone()).or_insert(0);
+            if *count > 0 {
+                slug = format!("{}-{}", slug, count);
+            }
+            *count += 1;
+
+            events[index] = Event::Start(Tag::Heading {
+                level,
+                id: Some(CowStr::from(slug)),
+                classes: heading_class(level).split(' ').map(CowStr::from).collect(),
+                attrs: Vec::new(),
+            });
+        }
+        index += 1;
+    }
+
+    let mut output = String::new();
+    html::push_html(&mut output, events.into_iter());
+    sanitize_html(&output)
+}
@@ -0,0 +1,587 @@
+//! Sign-in through external identity providers with the authorization code flow and PKCE.
+//!
+//! Providers are listed in `OIDC_PROVIDERS` and configured with `OIDC_<ID>_*` variables.
+//! `google` and `github` have built-in endpoints; any other ID is a generic OpenID Connect
+//! provider discovered from `OIDC_<ID>_ISSUER`, which is also how a local mock IdP is used.
+
+use crate::server::auth::model::{Identity, User, UserPreferences, ROLE_USER};
+use crate::server::plan::model::Plan;
+use axum::http::header::SET_COOKIE;
+use axum::http::HeaderValue;
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bson::{doc, oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use chrono::Duration;
+use dioxus::prelude::{server_context, ServerFnError};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use mongodb::Database;
+use rand_core::{OsRng, RngCore};
+use reqwest::header::{ACCEPT, USER_AGENT};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use tokio::sync::OnceCell;
+
+/// How long a user has to finish signing in at the provider.
+const LOGIN_MINUTES: i64 = 10;
+/// Holds the state of the sign-in started by this browser, so a callback carrying another
+/// browser's state is refused.
+const LOGIN_COOKIE: &str = "oidc_state";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProviderKind {
+    /// OpenID Connect with discovery, ID tokens and a JWKS.
+    Oidc,
+    /// GitHub's plain OAuth2, with the profile and emails read from its REST API.
+    GitHub,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+    jwks_uri: String,
+}
+
+pub struct Provider {
+    pub id: String,
+    pub name: String,
+    kind: ProviderKind,
+    client_id: String,
+    client_secret: String,
+    issuer: String,
+    scopes: String,
+    discovery: OnceCell<Discovery>,
+}
+
+/// The identity a provider vouched for.
+struct ExternalIdentity {
+    subject: String,
+    email: Option<String>,
+    email_verified: bool,
+    name: Option<String>,
+    picture: Option<String>,
+}
+
+/// An authorization request waiting for the provider to redirect back.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OidcLogin {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub state: String,
+    pub provider: String,
+    #[serde(rename = "codeVerifier")]
+    pub code_verifier: String,
+    pub nonce: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    id_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+    picture: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UserInfo {
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+    picture: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: u64,
+    login: String,
+    name: Option<String>,
+    avatar_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+fn provider_var(id: &str, key: &str) -> Option<String> {
+    env::var(format!("OIDC_{}_{}", id.to_uppercase(), key))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+impl Provider {
+    fn from_env(id: &str) -> Option<Self> {
+        let (kind, name, issuer, scopes) = match id {
+            "google" => (
+                ProviderKind::Oidc,
+                "Google",
+                "https://accounts.google.com",
+                "openid email profile",
+            ),
+            "github" => (
+                ProviderKind::GitHub,
+                "GitHub",
+                "https://github.com",
+                "read:user user:email",
+            ),
+            _ => (ProviderKind::Oidc, id, "", "openid email profile"),
+        };
+        let issuer = provider_var(id, "ISSUER").unwrap_or_else(|| issuer.to_string());
+        if issuer.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            id: id.to_string(),
+            name: provider_var(id, "NAME").unwrap_or_else(|| name.to_string()),
+            kind,
+            client_id: provider_var(id, "CLIENT_ID")?,
+            client_secret: provider_var(id, "CLIENT_SECRET").unwrap_or_default(),
+            issuer: issuer.trim_end_matches('/').to_string(),
+            scopes: provider_var(id, "SCOPES").unwrap_or_else(|| scopes.to_string()),
+            discovery: OnceCell::new(),
+        })
+    }
+
+    fn redirect_uri(&self) -> String {
+        let app_url = env::var("APP_URL").unwrap_or_else(|_| "http://localhost:3000".into());
+        format!("{}/oauth/{}/callback", app_url, self.id)
+    }
+
+    async fn discovery(&self) -> Result<&Discovery, ServerFnError> {
+        self.discovery
+            .get_or_try_init(|| async {
+                if self.kind == ProviderKind::GitHub {
+                    return Ok(Discovery {
+                        issuer: self.issuer.clone(),
+                        authorization_endpoint: format!("{}/login/oauth/authorize", self.issuer),
+                        token_endpoint: format!("{}/login/oauth/access_token", self.issuer),
+                        userinfo_endpoint: None,
+                        jwks_uri: String::new(),
+                    });
+                }
+                let url = format!("{}/.well-known/openid-configuration", self.issuer);
+                let discovery = reqwest::get(&url)
+                    .await?
+                    .error_for_status()?
+                    .json::<Discovery>()
+                    .await?;
+                // OpenID Connect Discovery 4.3: a document naming another issuer must not be
+                // used, or whoever serves it could point us at their own keys and endpoints.
+                // The configured issuer is stored without a trailing slash.
+                if discovery.issuer.trim_end_matches('/') != self.issuer {
+                    anyhow::bail!(
+                        "discovery document names issuer {}, expected {}",
+                        discovery.issuer,
+                        self.issuer
+                    );
+                }
+                Ok(discovery)
+            })
+            .await
+            .map_err(|e| ServerFnError::new(format!("{} is unavailable: {}", self.name, e)))
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<TokenResponse, ServerFnError> {
+        let discovery = self.discovery().await?;
+        let response = reqwest::Client::new()
+            .post(&discovery.token_endpoint)
+            .header(ACCEPT, "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.redirect_uri()),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(ServerFnError::new(format!(
+                "{} rejected the sign-in",
+                self.name
+            )));
+        }
+        response
+            .json::<TokenResponse>()
+            .await
+            .map_err(|_| ServerFnError::new(format!("{} rejected the sign-in", self.name)))
+    }
+
+    /// Checks the ID token's signature against the provider's keys, then its issuer, audience,
+    /// expiry and nonce.
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, ServerFnError> {
+        let invalid = || ServerFnError::new("Invalid ID token");
+        let discovery = self.discovery().await?;
+        let header = jsonwebtoken::decode_header(id_token).map_err(|_| invalid())?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(invalid());
+        }
+
+        let jwks = reqwest::get(&discovery.jwks_uri)
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or_else(invalid)?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| invalid())?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_issuer(&[&discovery.issuer]);
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|_| invalid())?
+            .claims;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid());
+        }
+        Ok(claims)
+    }
+
+    async fn oidc_identity(
+        &self,
+        tokens: &TokenResponse,
+        nonce: &str,
+    ) -> Result<ExternalIdentity, ServerFnError> {
+        let id_token = tokens
+            .id_token
+            .as_deref()
+            .ok_or(ServerFnError::new("Invalid ID token"))?;
+        let claims = self.verify_id_token(id_token, nonce).await?;
+        let mut identity = ExternalIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            name: claims.name,
+            picture: claims.picture,
+        };
+
+        // Some providers only put the profile in the userinfo response.
+        if identity.email.is_none() {
+            if let Some(endpoint) = &self.discovery().await?.userinfo_endpoint {
+                let info = reqwest::Client::new()
+                    .get(endpoint)
+                    .bearer_auth(&tokens.access_token)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<UserInfo>()
+                    .await?;
+                identity.email = info.email;
+                identity.email_verified = info.email_verified;
+                identity.name = identity.name.or(info.name);
+                identity.picture = identity.picture.or(info.picture);
+            }
+        }
+        Ok(identity)
+    }
+
+    async fn github_identity(
+        &self,
+        tokens: &TokenResponse,
+    ) -> Result<ExternalIdentity, ServerFnError> {
+        let api = provider_var(&self.id, "API_URL").unwrap_or("https://api.github.com".into());
+        let client = reqwest::Client::new();
+        let get = |path: &str| {
+            client
+                .get(format!("{}{}", api, path))
+                .bearer_auth(&tokens.access_token)
+                .header(USER_AGENT, "tripper")
+                .header(ACCEPT, "application/vnd.github+json")
+        };
+
+        let user = get("/user")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<GitHubUser>()
+            .await?;
+        let email = get("/user/emails")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<GitHubEmail>>()
+            .await?
+            .into_iter()
+            .filter(|email| email.verified)
+            .max_by_key(|email| email.primary);
+
+        Ok(ExternalIdentity {
+            subject: user.id.to_string(),
+            email_verified: email.is_some(),
+            email: email.map(|email| email.email),
+            name: user.name.or(Some(user.login)),
+            picture: user.avatar_url,
+        })
+    }
+}
+
+static PROVIDERS: OnceCell<Vec<Provider>> = OnceCell::const_new();
+
+/// The providers listed in `OIDC_PROVIDERS` that have a client ID configured.
+pub async fn providers() -> &'static [Provider] {
+    PROVIDERS
+        .get_or_init(|| async {
+            env::var("OIDC_PROVIDERS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|id| id.trim().to_lowercase())
+                .filter(|id| !id.is_empty())
+                .filter_map(|id| Provider::from_env(&id))
+                .collect()
+        })
+        .await
+}
+
+async fn find_provider(id: &str) -> Result<&'static Provider, ServerFnError> {
+    providers()
+        .await
+        .iter()
+        .find(|provider| provider.id == id)
+        .ok_or(ServerFnError::new("Unknown sign-in provider"))
+}
+
+fn random_token() -> String {
+    let mut buf = [0u8; 32];
+    OsRng.fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+/// Sets or, with an empty `state`, clears the sign-in cookie on the current response.
+fn set_login_cookie(state: &str) -> Result<(), ServerFnError> {
+    let max_age = if state.is_empty() {
+        time::Duration::ZERO
+    } else {
+        time::Duration::minutes(LOGIN_MINUTES)
+    };
+    let cookie = Cookie::build((LOGIN_COOKIE, state.to_string()))
+        .path("/")
+        .max_age(max_age)
+        .same_site(SameSite::Lax)
+        .http_only(true);
+    let value = HeaderValue::from_str(&cookie.to_string())
+        .map_err(|_| ServerFnError::new("Invalid sign-in state"))?;
+
+    let context = server_context();
+    context
+        .response_parts_mut()
+        .map_err(|_| ServerFnError::new("Failed to set the sign-in cookie"))?
+        .headers
+        .append(SET_COOKIE, value);
+    Ok(())
+}
+
+async fn login_cookie() -> Option<String> {
+    let context = server_context();
+    let parts = context.request_parts().await;
+    CookieJar::from_headers(&parts.headers)
+        .get(LOGIN_COOKIE)
+        .map(|cookie| cookie.value().to_string())
+}
+
+/// Starts a sign-in and returns the provider URL to send the browser to.
+pub async fn authorization_url(db: &Database, provider_id: &str) -> Result<String, ServerFnError> {
+    let provider = find_provider(provider_id).await?;
+    let discovery = provider.discovery().await?;
+
+    let login = OidcLogin {
+        id: ObjectId::new(),
+        state: random_token(),
+        provider: provider.id.clone(),
+        code_verifier: random_token(),
+        nonce: random_token(),
+        expires_at: Utc::now() + Duration::minutes(LOGIN_MINUTES),
+    };
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(login.code_verifier.as_bytes()));
+    let url = Url::parse_with_params(
+        &discovery.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", &provider.client_id),
+            ("redirect_uri", &provider.redirect_uri()),
+            ("scope", &provider.scopes),
+            ("state", &login.state),
+            ("nonce", &login.nonce),
+            ("code_challenge", &challenge),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|_| ServerFnError::new(format!("{} is misconfigured", provider.name)))?;
+
+    db.collection::<OidcLogin>("oidc_logins")
+        .insert_one(&login)
+        .await?;
+    set_login_cookie(&login.state)?;
+    Ok(url.into())
+}
+
+/// Finishes a sign-in from the query string the provider redirected back with, and returns the
+/// Tripper account it belongs to.
+pub async fn complete_login(
+    db: &Database,
+    provider_id: &str,
+    query: &str,
+) -> Result<User, ServerFnError> {
+    let provider = find_provider(provider_id).await?;
+    let params = Url::parse(&format!(
+        "http://callback/?{}",
+        query.trim_start_matches('?')
+    ))
+    .map_err(|_| ServerFnError::new("Invalid callback"))?
+    .query_pairs()
+    .into_owned()
+    .collect::<Vec<_>>();
+    let param = |key: &str| {
+        params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.clone())
+    };
+    if let Some(error) = param("error") {
+        let description = param("error_description").unwrap_or(error);
+        return Err(ServerFnError::new(format!(
+            "{} sign-in failed: {}",
+            provider.name, description
+        )));
+    }
+    let state = param("state").ok_or(ServerFnError::new("Invalid callback"))?;
+    let code = param("code").ok_or(ServerFnError::new("Invalid callback"))?;
+    if login_cookie().await.as_deref() != Some(state.as_str()) {
+        return Err(ServerFnError::new(
+            "Sign-in was started in another browser, please try again",
+        ));
+    }
+    set_login_cookie("")?;
+
+    // Deleting the pending login makes each state usable once.
+    let login = db
+        .collection::<OidcLogin>("oidc_logins")
+        .find_one_and_delete(doc! {
+            "state": &state,
+            "provider": &provider.id,
+            "expiresAt": { "$gt": Utc::now() },
+        })
+        .await?
+        .ok_or(ServerFnError::new("Sign-in expired, please try again"))?;
+
+    let tokens = provider.exchange_code(&code, &login.code_verifier).await?;
+    let identity = match provider.kind {
+        ProviderKind::Oidc => provider.oidc_identity(&tokens, &login.nonce).await?,
+        ProviderKind::GitHub => provider.github_identity(&tokens).await?,
+    };
+
+    link_or_create_user(db, provider, identity).await
+}
+
+/// Finds the account already linked to `identity`, links it to the verified account with the
+/// same email, or creates a new account.
+async fn link_or_create_user(
+    db: &Database,
+    provider: &Provider,
+    identity: ExternalIdentity,
+) -> Result<User, ServerFnError> {
+    let users = db.collection::<User>("users");
+    let linked = doc! {
+        "identities": { "$elemMatch": { "provider": &provider.id, "subject": &identity.subject } }
+    };
+    if let Some(user) = users.find_one(linked).await? {
+        return Ok(user);
+    }
+
+    // Linking by an address the provider has not confirmed would hand the account to whoever
+    // typed it in there.
+    let email = identity
+        .email
+        .filter(|_| identity.email_verified)
+        .map(|email| email.trim().to_lowercase())
+        .ok_or(ServerFnError::new(format!(
+            "{} did not share a verified email address",
+            provider.name
+        )))?;
+    let link = Identity {
+        provider: provider.id.clone(),
+        subject: identity.subject,
+    };
+
+    if let Some(mut user) = users.find_one(doc! { "email": &email }).await? {
+        // Whoever registered an unverified account never proved they own the address, so
+        // linking it would let them sign in as the provider's user, or the other way round.
+        if !user.verified {
+            return Err(ServerFnError::new(format!(
+                "An account with this email already exists. Sign in with your password and verify your email address before continuing with {}.",
+                provider.name
+            )));
+        }
+        users
+            .update_one(
+                doc! { "_id": user.id },
+                doc! {
+                    "$push": { "identities": bson::to_bson(&link)? },
+                    "$set": { "updatedAt": Utc::now() },
+                },
+            )
+            .await?;
+        user.identities.push(link);
+        return Ok(user);
+    }
+
+    let user = User {
+        id: ObjectId::new(),
+        name: identity
+            .name
+            .unwrap_or_else(|| email.split('@').next().unwrap_or_default().to_string()),
+        email,
+        // Accounts created here have no password until one is set through a reset link.
+        password: String::new(),
+        role: ROLE_USER.into(),
+        plan: Plan::Free,
+        photo: identity.picture.unwrap_or_default(),
+        verified: true,
+        suspended: false,
+        preferences: UserPreferences::default(),
+        identities: vec![link],
+        two_factor: None,
+        pending_email: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    users.insert_one(&user).await?;
+    Ok(user)
+}
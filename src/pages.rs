@@ -1,5 +1,9 @@
+pub(crate) mod admin;
 pub(crate) mod dashboard;
 pub(crate) mod home;
 pub(crate) mod login;
+pub(crate) mod oauth;
+pub(crate) mod password;
 pub(crate) mod signup;
 pub(crate) mod trip;
+pub(crate) mod verify;
@@ -0,0 +1,76 @@
+use crate::components::admin::audit::AuditLogPanel;
+use crate::components::admin::overview::OverviewPanel;
+use crate::components::admin::users::UsersPanel;
+use crate::components::dashboard::navbar::Navbar;
+use crate::server::auth::controller::about_me;
+use crate::session::{restore_session, use_token_refresh};
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+
+#[derive(PartialEq, Clone, Copy)]
+enum AdminTab {
+    Overview,
+    Users,
+    AuditLog,
+}
+
+#[component]
+pub fn Admin() -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut active_tab = use_signal(|| AdminTab::Overview);
+    let mut user_token = use_signal(|| "".to_string());
+    let navigator = use_navigator();
+
+    use_effect(move || {
+        spawn(async move {
+            let Some(token) = restore_session().await else {
+                navigator.push("/login");
+                return;
+            };
+            match about_me(token.clone()).await {
+                Ok(data) if data.data.user.is_admin() => {
+                    user_token.set(token);
+                }
+                _ => {
+                    navigator.push("/dashboard");
+                }
+            }
+        });
+    });
+    use_token_refresh(user_token);
+
+    let tab_style = move |tab: AdminTab| {
+        if active_tab() == tab {
+            "px-4 py-2 rounded bg-blue-500 text-white"
+        } else if dark_mode {
+            "px-4 py-2 rounded text-gray-400 hover:bg-gray-700"
+        } else {
+            "px-4 py-2 rounded text-gray-600 hover:bg-gray-100"
+        }
+    };
+
+    rsx! {
+        div { class: format!("min-h-screen {}", if dark_mode { "bg-gray-900 text-white" } else { "bg-white text-gray-900" }),
+            div { class: "p-4 md:p-8",
+                Navbar { dark_mode }
+
+                div { class: "flex items-center space-x-2 mb-4",
+                    Link { to: "/dashboard", class: "px-4 py-2 text-blue-500", "← Dashboard" }
+                    button { class: tab_style(AdminTab::Overview), onclick: move |_| active_tab.set(AdminTab::Overview), "Overview" }
+                    button { class: tab_style(AdminTab::Users), onclick: move |_| active_tab.set(AdminTab::Users), "Users" }
+                    button { class: tab_style(AdminTab::AuditLog), onclick: move |_| active_tab.set(AdminTab::AuditLog), "Audit Log" }
+                }
+
+                div { class: format!("p-4 shadow rounded-lg {}", if dark_mode { "bg-gray-800" } else { "bg-white" }),
+                    if !user_token().is_empty() {
+                        match active_tab() {
+                            AdminTab::Overview => rsx! { OverviewPanel { user_token } },
+                            AdminTab::Users => rsx! { UsersPanel { user_token } },
+                            AdminTab::AuditLog => rsx! { AuditLogPanel { user_token } },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
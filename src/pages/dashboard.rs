@@ -6,11 +6,9 @@ use crate::components::dashboard::sidebar::Tab;
 use crate::components::dashboard::trips::create::CreateTripPanel;
 use crate::components::dashboard::trips::list::TripsPanel;
 use crate::components::dashboard::trips::read::ReadTripPanel;
-use crate::server::auth::controller::about_me;
+use crate::session::{restore_session, use_token_refresh};
 use crate::theme::{Theme, THEME};
 use dioxus::prelude::*;
-use gloo_storage::SessionStorage;
-use gloo_storage::Storage;
 
 pub fn toggle_theme() {
     let current_theme = *THEME.read();
@@ -30,29 +28,22 @@ pub fn Dashboard() -> Element {
     let current_tab = match active_tab() {
         Tab::Trips => rsx! { TripsPanel { user_token } },
         Tab::CreateTrip => rsx! { CreateTripPanel { user_token } },
-        Tab::ReadTrip => rsx! { ReadTripPanel { trip_id: "" } },
+        Tab::ReadTrip => rsx! { ReadTripPanel { user_token, trip_id: "" } },
         Tab::EditProfile => rsx! { EditProfilePanel {} },
         Tab::Chat => rsx! { ChatPanelPage { user_token, trip_id: ""} },
     };
 
     use_effect(move || {
         spawn(async move {
-            let token: String = SessionStorage::get("jwt").unwrap_or_default();
-            if token.is_empty() {
-                navigator.push("/login");
-            } else {
-                match about_me(token.clone()).await {
-                    Ok(data) => {
-                        let _user = data.data.user;
-                        user_token.set(token.clone());
-                    }
-                    Err(_) => {
-                        navigator.push("/login");
-                    }
+            match restore_session().await {
+                Some(token) => user_token.set(token),
+                None => {
+                    navigator.push("/login");
                 }
             }
         });
     });
+    use_token_refresh(user_token);
 
     rsx! {
         div { class: format!("min-h-screen flex {}", if dark_mode { "bg-gray-900 text-white" } else { "bg-white text-gray-900" }),
@@ -1,25 +1,21 @@
+use crate::components::oidc::OidcButtons;
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
 use crate::components::toast::manager::ToastManager;
 use crate::components::toast::manager::ToastType;
+use crate::components::two_factor::TwoFactorPrompt;
 use crate::router::Route;
 use crate::server::auth::controller::{about_me, login_user};
 use crate::server::auth::response::LoginUserSchema;
+use crate::session::{restore_session, store_tokens};
 use crate::theme::Theme;
 use crate::theme::THEME;
 use chrono::Duration;
 use dioxus::prelude::*;
 use dioxus_free_icons::icons::fa_regular_icons::{FaEye, FaEyeSlash};
 use dioxus_free_icons::Icon;
-use gloo_storage::SessionStorage;
-use gloo_storage::Storage;
 use regex::Regex;
 
-fn extract_token(cookie_str: &str) -> Option<String> {
-    let re = Regex::new(r"token=([^;]+)").unwrap();
-    re.captures(cookie_str).map(|caps| caps[1].to_string())
-}
-
 #[component]
 pub fn Login() -> Element {
     let navigator = use_navigator();
@@ -35,6 +31,7 @@ pub fn Login() -> Element {
     let mut show_password = use_signal(|| false);
     let mut remember_me = use_signal(|| false);
     let mut loading = use_signal(|| false);
+    let mut two_factor_challenge = use_signal(|| None::<String>);
 
     let validate_email = |email: &str| {
         let pattern = Regex::new(r"^[^ ]+@[^ ]+\.[a-z]{2,3}$").unwrap();
@@ -45,17 +42,8 @@ pub fn Login() -> Element {
 
     use_effect(move || {
         spawn(async move {
-            let token: String = SessionStorage::get("jwt").unwrap_or_default();
-            if !token.is_empty() {
-                match about_me(token.clone()).await {
-                    Ok(data) => {
-                        let _user = data.data.user;
-                        navigator.push("/dashboard");
-                    }
-                    Err(e) => {
-                        error_message.set(Some(e.to_string()));
-                    }
-                }
+            if restore_session().await.is_some() {
+                navigator.push("/dashboard");
             }
         });
     });
@@ -79,12 +67,14 @@ pub fn Login() -> Element {
             let password = password_value.clone();
             async move {
                 match login_user(LoginUserSchema { email, password }).await {
-                    Ok(data) => match extract_token(&data.data.token) {
+                    Ok(data) if data.data.two_factor_challenge.is_some() => {
+                        two_factor_challenge.set(data.data.two_factor_challenge);
+                        loading.set(false);
+                    }
+                    Ok(data) => match store_tokens(&data.data, remember_me()) {
                         Some(token) => match about_me(token.clone()).await {
                             Ok(data) => {
                                 let _user = data.data.user;
-                                SessionStorage::set("jwt", token.clone())
-                                    .expect("Failed to store JWT in session storage");
                                 navigator.push("/dashboard");
                                 toasts_manager.set(
                                     toasts_manager()
@@ -162,133 +152,123 @@ pub fn Login() -> Element {
                 if dark_mode == Theme::Dark { "bg-blue-500 text-white" } else { "bg-blue-900 text-gray-900" }
             ),
             style: "background-image: linear-gradient(90deg, rgba(0,0,0,0.05) 1px, transparent 1px), linear-gradient(rgba(0,0,0,0.05) 1px, transparent 1px); background-size: 40px 40px;",
-            form {
-                style: if dark_mode == Theme::Dark { "background-color: #1f2937; color: white;" } else { "background-color: white; color: black;" },
-                class: "w-full max-w-md flex flex-col items-center p-6 bg-white shadow-lg rounded-lg transform transition-all duration-300 hover:shadow-2xl",
-                onsubmit: handle_login,
-                Link {
-                    to: Route::Home {},
-                    class: "text-gray-400 text-sm mb-4",
-                    "← Back to Home"
+            if let Some(challenge) = two_factor_challenge() {
+                div {
+                    style: if dark_mode == Theme::Dark { "background-color: #1f2937; color: white;" } else { "background-color: white; color: black;" },
+                    class: "w-full max-w-md flex flex-col items-center p-6 bg-white shadow-lg rounded-lg",
+                    h1 { class: "text-3xl font-semibold mb-6 mt-4", "Two-Factor Authentication" }
+                    TwoFactorPrompt { challenge, remember: remember_me() }
                 }
-                h1 { class: "text-3xl font-semibold mb-6 mt-4", "Sign In" },
-                div { class: "flex flex-col md:flex-row gap-4 w-full mb-6",
-                    div { class: "flex flex-col items-start w-full",
-                        span { class: "text-xs text-gray-500 mb-1", "Coming Soon" },
-                        button {
-                            class: "w-full py-2 border rounded-md border-gray-300 bg-gray-100 text-gray-400 cursor-not-allowed whitespace-nowrap",
-                            disabled: "true",
-                            "Login with Google"
-                        }
+            } else {
+                form {
+                    style: if dark_mode == Theme::Dark { "background-color: #1f2937; color: white;" } else { "background-color: white; color: black;" },
+                    class: "w-full max-w-md flex flex-col items-center p-6 bg-white shadow-lg rounded-lg transform transition-all duration-300 hover:shadow-2xl",
+                    onsubmit: handle_login,
+                    Link {
+                        to: Route::Home {},
+                        class: "text-gray-400 text-sm mb-4",
+                        "← Back to Home"
                     }
-                    div { class: "flex flex-col items-start w-full",
-                        span { class: "text-xs text-gray-500 mb-1", "Coming Soon" },
-                        button {
-                            class: "w-full py-2 border rounded-md border-gray-300 bg-gray-100 text-gray-400 cursor-not-allowed whitespace-nowrap",
-                            disabled: "true",
-                            "Login with Github"
-                        }
-                    }
-                }
-
-                div { class: "text-center text-gray-500 mb-6", "or" }
+                    h1 { class: "text-3xl font-semibold mb-6 mt-4", "Sign In" },
+                    OidcButtons { remember: remember_me() }
 
-                div {
-                    class: "relative mb-4 w-full",
-                    input {
-                        class: format!(
-                            "w-full p-3 border rounded-md shadow-sm transition-all {} {}",
-                            if dark_mode == Theme::Dark { "bg-gray-700 text-white" } else { "bg-white text-gray-900" },
-                            if email_valid() { "border-gray-300" } else { "border-red-500" }
-                        ),
-                        r#type: "text",
-                        placeholder: "Email",
-                        value: "{email}",
-                        required: true,
-                        oninput: move |e| {
-                            let value = e.value().clone();
-                            email.set(value.clone());
-                            email_valid.set(validate_email(&value));
+                    div {
+                        class: "relative mb-4 w-full",
+                        input {
+                            class: format!(
+                                "w-full p-3 border rounded-md shadow-sm transition-all {} {}",
+                                if dark_mode == Theme::Dark { "bg-gray-700 text-white" } else { "bg-white text-gray-900" },
+                                if email_valid() { "border-gray-300" } else { "border-red-500" }
+                            ),
+                            r#type: "text",
+                            placeholder: "Email",
+                            value: "{email}",
+                            required: true,
+                            oninput: move |e| {
+                                let value = e.value().clone();
+                                email.set(value.clone());
+                                email_valid.set(validate_email(&value));
+                            }
+                        },
+                        if !email_valid() {
+                            p { class: "text-red-500 text-sm mt-1", "Enter a valid email address" }
                         }
                     },
-                    if !email_valid() {
-                        p { class: "text-red-500 text-sm mt-1", "Enter a valid email address" }
-                    }
-                },
 
-                div {
-                    class: "relative mb-4 w-full",
-                    input {
-                        class: format!(
-                            "w-full p-3 border rounded-md shadow-sm transition-all {} {}",
-                            if dark_mode == Theme::Dark { "bg-gray-700 text-white" } else { "bg-white text-gray-900" },
-                            if password_valid() { "border-gray-300" } else { "border-red-500" }
-                        ),
-                        r#type: if show_password() { "text" } else { "password" },
-                        placeholder: "Password",
-                        value: "{password}",
-                        required: true,
-                        oninput: move |e| {
-                            let value = e.value().clone();
-                            password.set(value.clone());
-                            password_valid.set(validate_password(&value));
+                    div {
+                        class: "relative mb-4 w-full",
+                        input {
+                            class: format!(
+                                "w-full p-3 border rounded-md shadow-sm transition-all {} {}",
+                                if dark_mode == Theme::Dark { "bg-gray-700 text-white" } else { "bg-white text-gray-900" },
+                                if password_valid() { "border-gray-300" } else { "border-red-500" }
+                            ),
+                            r#type: if show_password() { "text" } else { "password" },
+                            placeholder: "Password",
+                            value: "{password}",
+                            required: true,
+                            oninput: move |e| {
+                                let value = e.value().clone();
+                                password.set(value.clone());
+                                password_valid.set(validate_password(&value));
+                            }
+                        },
+                        button {
+                            onclick: move |_| show_password.set(!show_password()),
+                            class: "absolute inset-y-0 right-0 pr-3 text-gray-500 hover:text-gray-700",
+                            if show_password() {
+                                Icon { icon: FaEye, width: 20, height: 20 }
+                            } else {
+                                Icon { icon: FaEyeSlash, width: 20, height: 20 }
+                            }
                         }
-                    },
-                    button {
-                        onclick: move |_| show_password.set(!show_password()),
-                        class: "absolute inset-y-0 right-0 pr-3 text-gray-500 hover:text-gray-700",
-                        if show_password() {
-                            Icon { icon: FaEye, width: 20, height: 20 }
-                        } else {
-                            Icon { icon: FaEyeSlash, width: 20, height: 20 }
+                        if !password_valid() {
+                            p { class: "text-red-500 text-sm mt-1", "Password can't be blank" }
                         }
-                    }
-                    if !password_valid() {
-                        p { class: "text-red-500 text-sm mt-1", "Password can't be blank" }
-                    }
-                },
+                    },
 
-                div {
-                    class: "flex items-center justify-between w-full mb-6",
-                    label {
-                        class: "text-gray-500 cursor-pointer",
-                        input {
-                            r#type: "checkbox",
-                            class: "mr-2 cursor-pointer",
-                            onchange: move |_| remember_me.set(!remember_me()),
+                    div {
+                        class: "flex items-center justify-between w-full mb-6",
+                        label {
+                            class: "text-gray-500 cursor-pointer",
+                            input {
+                                r#type: "checkbox",
+                                class: "mr-2 cursor-pointer",
+                                onchange: move |_| remember_me.set(!remember_me()),
+                            },
+                            "Remember me"
                         },
-                        "Remember me"
+                        Link {
+                            to: Route::ForgotPassword {},
+                            class: "text-blue-500 text-sm hover:underline transition duration-200",
+                            "Forgot password?"
+                        }
                     },
-                    a {
-                        href: "#",
-                        class: "text-blue-500 text-sm hover:underline transition duration-200",
-                        "Forgot password?"
-                    }
-                },
 
-                button {
-                    class: "flex items-center justify-center space-x-2 w-full py-2 mt-4 bg-blue-600 hover:bg-blue-700 text-white rounded-md whitespace-nowrap",
-                    r#type: "submit",
-                    disabled: loading(),
-                    if loading() {
-                        Spinner {
-                            aria_label: "Loading spinner".to_string(),
-                            size: SpinnerSize::Md,
-                            dark_mode: true,
+                    button {
+                        class: "flex items-center justify-center space-x-2 w-full py-2 mt-4 bg-blue-600 hover:bg-blue-700 text-white rounded-md whitespace-nowrap",
+                        r#type: "submit",
+                        disabled: loading(),
+                        if loading() {
+                            Spinner {
+                                aria_label: "Loading spinner".to_string(),
+                                size: SpinnerSize::Md,
+                                dark_mode: true,
+                            }
+                            span { "Signing In..." }
+                        } else {
+                            span { "Sign In" }
                         }
-                        span { "Signing In..." }
-                    } else {
-                        span { "Sign In" }
                     }
-                }
 
-                div {
-                    class: "text-gray-500 mt-4",
-                    "Don't have an account? ",
-                    a {
-                        href: "#",
-                        class: "text-blue-500 font-semibold hover:underline",
-                        "Sign up"
+                    div {
+                        class: "text-gray-500 mt-4",
+                        "Don't have an account? ",
+                        a {
+                            href: "#",
+                            class: "text-blue-500 font-semibold hover:underline",
+                            "Sign up"
+                        }
                     }
                 }
             }
@@ -0,0 +1,68 @@
+use crate::components::admin::error_message;
+use crate::components::oidc::OIDC_REMEMBER_KEY;
+use crate::components::two_factor::TwoFactorPrompt;
+use crate::router::Route;
+use crate::server::auth::controller::oidc_login;
+use crate::server::auth::response::OidcLoginSchema;
+use crate::session::store_tokens;
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// Where sign-in providers redirect back to; finishes the sign-in and opens the dashboard.
+#[component]
+pub fn OAuthCallback(provider: String, query: String) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let navigator = use_navigator();
+    let mut error = use_signal(|| None::<String>);
+    let mut remember = use_signal(|| false);
+    let mut two_factor_challenge = use_signal(|| None::<String>);
+
+    use_effect(move || {
+        let provider = provider.clone();
+        let query = query.clone();
+        spawn(async move {
+            remember.set(SessionStorage::get(OIDC_REMEMBER_KEY).unwrap_or_default());
+            SessionStorage::delete(OIDC_REMEMBER_KEY);
+            match oidc_login(OidcLoginSchema { provider, query }).await {
+                Ok(response) if response.data.two_factor_challenge.is_some() => {
+                    two_factor_challenge.set(response.data.two_factor_challenge);
+                }
+                Ok(response) => match store_tokens(&response.data, remember()) {
+                    Some(_) => {
+                        navigator.push("/dashboard");
+                    }
+                    None => error.set(Some("Token not found".into())),
+                },
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+        });
+    });
+
+    rsx! {
+        div {
+            class: format!("min-h-screen flex items-center justify-center {}",
+                if dark_mode { "bg-blue-500 text-white" } else { "bg-blue-900 text-gray-900" }
+            ),
+            div {
+                class: format!("w-full max-w-md flex flex-col items-center p-6 shadow-lg rounded-lg {}",
+                    if dark_mode { "bg-gray-800 text-white" } else { "bg-white text-black" }
+                ),
+                if let Some(error) = error() {
+                    h1 { class: "text-2xl font-semibold mb-4", "Sign-in Failed" }
+                    p { class: "text-red-500 mb-6", "{error}" }
+                    Link {
+                        to: Route::Login {},
+                        class: "px-4 py-2 rounded bg-blue-600 text-white",
+                        "Back to Sign In"
+                    }
+                } else if let Some(challenge) = two_factor_challenge() {
+                    h1 { class: "text-2xl font-semibold mb-4", "Two-Factor Authentication" }
+                    TwoFactorPrompt { challenge, remember: remember() }
+                } else {
+                    p { class: "text-gray-500", "Signing you in..." }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::components::admin::error_message;
+use crate::components::spinner::Spinner;
+use crate::components::spinner::SpinnerSize;
+use crate::router::Route;
+use crate::server::auth::controller::{request_password_reset, reset_password};
+use crate::server::auth::response::ResetPasswordSchema;
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+
+#[component]
+fn PasswordCard(title: String, children: Element) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+
+    rsx! {
+        div {
+            class: format!("min-h-screen flex items-center justify-center {}",
+                if dark_mode { "bg-blue-500 text-white" } else { "bg-blue-900 text-gray-900" }
+            ),
+            style: "background-image: linear-gradient(90deg, rgba(0,0,0,0.05) 1px, transparent 1px), linear-gradient(rgba(0,0,0,0.05) 1px, transparent 1px); background-size: 40px 40px;",
+            div {
+                class: format!("w-full max-w-md flex flex-col items-center p-6 shadow-lg rounded-lg {}",
+                    if dark_mode { "bg-gray-800 text-white" } else { "bg-white text-black" }
+                ),
+                Link {
+                    to: Route::Login {},
+                    class: "text-gray-400 text-sm mb-4",
+                    "← Back to Sign In"
+                }
+                h1 { class: "text-3xl font-semibold mb-6 mt-4", "{title}" }
+                {children}
+            }
+        }
+    }
+}
+
+/// Asks for an email address and sends a reset link to it.
+#[component]
+pub fn ForgotPassword() -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut email = use_signal(String::new);
+    let mut message = use_signal(|| None::<Result<String, String>>);
+    let mut loading = use_signal(|| false);
+
+    let handle_submit = move |_| {
+        loading.set(true);
+        spawn(async move {
+            match request_password_reset(email()).await {
+                Ok(response) => message.set(Some(Ok(response.data))),
+                Err(e) => message.set(Some(Err(error_message(&e)))),
+            }
+            loading.set(false);
+        });
+    };
+
+    rsx! {
+        PasswordCard { title: "Forgot Password",
+            form { class: "w-full", onsubmit: handle_submit,
+                p { class: "text-sm text-gray-500 mb-4",
+                    "Enter the email address you signed up with and we'll send you a link to choose a new password."
+                }
+                input {
+                    class: format!("w-full p-3 mb-4 border border-gray-300 rounded-md shadow-sm {}",
+                        if dark_mode { "bg-gray-700 text-white" } else { "bg-white text-gray-900" }
+                    ),
+                    r#type: "email",
+                    placeholder: "Email",
+                    value: "{email}",
+                    required: true,
+                    oninput: move |e| email.set(e.value()),
+                }
+                match message() {
+                    Some(Ok(message)) => rsx! { p { class: "text-green-500 text-sm mb-4", "{message}" } },
+                    Some(Err(error)) => rsx! { p { class: "text-red-500 text-sm mb-4", "{error}" } },
+                    None => rsx! {},
+                }
+                button {
+                    class: "flex items-center justify-center space-x-2 w-full py-2 bg-blue-600 hover:bg-blue-700 text-white rounded-md",
+                    r#type: "submit",
+                    disabled: loading(),
+                    if loading() {
+                        Spinner {
+                            aria_label: "Loading spinner".to_string(),
+                            size: SpinnerSize::Md,
+                            dark_mode: true,
+                        }
+                        span { "Sending..." }
+                    } else {
+                        span { "Send Reset Link" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Landing page for the link in password reset emails.
+#[component]
+pub fn ResetPassword(token: String) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let navigator = use_navigator();
+    let mut password = use_signal(String::new);
+    let mut confirm = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut done = use_signal(|| false);
+    let mut loading = use_signal(|| false);
+
+    let handle_submit = move |_| {
+        if password().is_empty() {
+            error.set(Some("Password can't be blank".into()));
+            return;
+        }
+        if password() != confirm() {
+            error.set(Some("Passwords do not match".into()));
+            return;
+        }
+        let token = token.clone();
+        loading.set(true);
+        spawn(async move {
+            match reset_password(ResetPasswordSchema {
+                token,
+                password: password(),
+            })
+            .await
+            {
+                Ok(_) => {
+                    error.set(None);
+                    done.set(true);
+                }
+                Err(e) => error.set(Some(error_message(&e))),
+            }
+            loading.set(false);
+        });
+    };
+
+    let input_class = format!(
+        "w-full p-3 mb-4 border border-gray-300 rounded-md shadow-sm {}",
+        if dark_mode {
+            "bg-gray-700 text-white"
+        } else {
+            "bg-white text-gray-900"
+        }
+    );
+
+    rsx! {
+        PasswordCard { title: "Choose a New Password",
+            if done() {
+                p { class: "text-green-500 mb-6",
+                    "Your password has been updated and every device has been signed out."
+                }
+                button {
+                    class: "w-full py-2 bg-blue-600 hover:bg-blue-700 text-white rounded-md",
+                    onclick: move |_| {
+                        navigator.push("/login");
+                    },
+                    "Sign In"
+                }
+            } else {
+                form { class: "w-full", onsubmit: handle_submit,
+                    input {
+                        class: "{input_class}",
+                        r#type: "password",
+                        placeholder: "New password",
+                        value: "{password}",
+                        required: true,
+                        oninput: move |e| password.set(e.value()),
+                    }
+                    input {
+                        class: "{input_class}",
+                        r#type: "password",
+                        placeholder: "Confirm new password",
+                        value: "{confirm}",
+                        required: true,
+                        oninput: move |e| confirm.set(e.value()),
+                    }
+                    if let Some(error) = error() {
+                        p { class: "text-red-500 text-sm mb-4", "{error}" }
+                    }
+                    button {
+                        class: "flex items-center justify-center space-x-2 w-full py-2 bg-blue-600 hover:bg-blue-700 text-white rounded-md",
+                        r#type: "submit",
+                        disabled: loading(),
+                        if loading() {
+                            Spinner {
+                                aria_label: "Loading spinner".to_string(),
+                                size: SpinnerSize::Md,
+                                dark_mode: true,
+                            }
+                            span { "Saving..." }
+                        } else {
+                            span { "Reset Password" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,19 +1,19 @@
 // use crate::components::common::server::JWT_TOKEN;
+use crate::components::oidc::OidcButtons;
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
 use crate::components::toast::manager::ToastManager;
 use crate::components::toast::manager::ToastType;
 use crate::router::Route;
-use crate::server::auth::controller::{about_me, register_user};
+use crate::server::auth::controller::register_user;
 use crate::server::auth::response::RegisterUserSchema;
+use crate::session::restore_session;
 use crate::theme::Theme;
 use crate::theme::THEME;
 use chrono::Duration;
 use dioxus::prelude::*;
 use dioxus_free_icons::icons::fa_regular_icons::{FaEye, FaEyeSlash};
 use dioxus_free_icons::Icon;
-use gloo_storage::SessionStorage;
-use gloo_storage::Storage;
 use regex::Regex;
 
 #[component]
@@ -45,17 +45,8 @@ pub fn Register() -> Element {
 
     use_effect(move || {
         spawn(async move {
-            let token: String = SessionStorage::get("jwt").unwrap_or_default();
-            if !token.is_empty() {
-                match about_me(token.clone()).await {
-                    Ok(data) => {
-                        let _user = data.data.user;
-                        navigator.push("/dashboard");
-                    }
-                    Err(e) => {
-                        error_message.set(Some(e.to_string()));
-                    }
-                }
+            if restore_session().await.is_some() {
+                navigator.push("/dashboard");
             }
         });
     });
@@ -146,26 +137,7 @@ pub fn Register() -> Element {
                     "← Back to Home"
                 }
                 h1 { class: "text-3xl font-semibold mb-6 mt-4", "Sign Up" },
-                div { class: "flex flex-col md:flex-row gap-4 w-full mb-6",
-                    div { class: "flex flex-col items-start w-full",
-                        span { class: "text-xs text-gray-500 mb-1", "Coming Soon" },
-                        button {
-                            class: "w-full py-2 border rounded-md border-gray-300 bg-gray-100 text-gray-400 cursor-not-allowed whitespace-nowrap",
-                            disabled: "true",
-                            "Register with Google"
-                        }
-                    }
-                    div { class: "flex flex-col items-start w-full",
-                        span { class: "text-xs text-gray-500 mb-1", "Coming Soon" },
-                        button {
-                            class: "w-full py-2 border rounded-md border-gray-300 bg-gray-100 text-gray-400 cursor-not-allowed whitespace-nowrap",
-                            disabled: "true",
-                            "Register with Github"
-                        }
-                    }
-                }
-
-                div { class: "text-center text-gray-500 mb-6", "or" }
+                OidcButtons { remember: false }
 
                 div { class: "relative mb-4 w-full",
                     input {
@@ -0,0 +1,65 @@
+use crate::components::admin::error_message;
+use crate::router::Route;
+use crate::server::auth::controller::verify_email;
+use crate::theme::{Theme, THEME};
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq)]
+enum VerifyState {
+    Pending,
+    Verified(String),
+    Failed(String),
+}
+
+/// Landing page for the link in verification emails.
+#[component]
+pub fn VerifyEmail(token: String) -> Element {
+    let dark_mode = *THEME.read() == Theme::Dark;
+    let mut state = use_signal(|| VerifyState::Pending);
+
+    use_effect(move || {
+        let token = token.clone();
+        spawn(async move {
+            match verify_email(token).await {
+                Ok(response) => state.set(VerifyState::Verified(response.data)),
+                Err(e) => state.set(VerifyState::Failed(error_message(&e))),
+            }
+        });
+    });
+
+    rsx! {
+        div {
+            class: format!("min-h-screen flex items-center justify-center {}",
+                if dark_mode { "bg-blue-500 text-white" } else { "bg-blue-900 text-gray-900" }
+            ),
+            div {
+                class: format!("w-full max-w-md flex flex-col items-center p-6 shadow-lg rounded-lg {}",
+                    if dark_mode { "bg-gray-800 text-white" } else { "bg-white text-black" }
+                ),
+                h1 { class: "text-3xl font-semibold mb-6", "Email Verification" }
+                match state() {
+                    VerifyState::Pending => rsx! {
+                        p { class: "text-gray-500", "Verifying your email address..." }
+                    },
+                    VerifyState::Verified(email) => rsx! {
+                        p { class: "text-green-500 mb-6", "{email} is verified. Thanks!" }
+                        Link {
+                            to: Route::Dashboard {},
+                            class: "px-4 py-2 rounded bg-blue-600 text-white",
+                            "Go to Dashboard"
+                        }
+                    },
+                    VerifyState::Failed(error) => rsx! {
+                        p { class: "text-red-500 mb-6", "{error}" }
+                        p { class: "text-sm text-gray-500", "Sign in and request a new link from the dashboard." }
+                        Link {
+                            to: Route::Login {},
+                            class: "mt-4 px-4 py-2 rounded bg-blue-600 text-white",
+                            "Sign In"
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
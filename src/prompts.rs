@@ -0,0 +1,259 @@
+use crate::server::auth::model::UserPreferences;
+use crate::server::common::model::PromptRef;
+use dioxus::prelude::ServerFnError;
+use dioxus_logger::tracing;
+use regex::Regex;
+use std::collections::HashMap;
+use std::env;
+use tokio::sync::OnceCell;
+
+const EMBEDDED: &[(&str, &str)] = &[
+    ("trip_outline", include_str!("../prompts/trip_outline.txt")),
+    (
+        "trip_outline_fix",
+        include_str!("../prompts/trip_outline_fix.txt"),
+    ),
+    (
+        "detail_markdown",
+        include_str!("../prompts/detail_markdown.txt"),
+    ),
+    ("chat_answer", include_str!("../prompts/chat_answer.txt")),
+    ("packing_list", include_str!("../prompts/packing_list.txt")),
+];
+
+#[derive(Debug, Clone)]
+struct Template {
+    version: u32,
+    body: String,
+}
+
+/// Parses a template file: a `version: N` header, a `---` line, then the body.
+fn parse_template(source: &str) -> anyhow::Result<Template> {
+    let (header, body) = source
+        .split_once("\n---\n")
+        .ok_or_else(|| anyhow::anyhow!("missing `---` after the header"))?;
+    let version = header
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim() == "version")
+        .ok_or_else(|| anyhow::anyhow!("missing `version` in the header"))?
+        .1
+        .trim()
+        .parse()?;
+
+    Ok(Template {
+        version,
+        body: body.trim().to_string(),
+    })
+}
+
+async fn init_templates() -> HashMap<&'static str, Template> {
+    let override_dir = env::var("PROMPT_TEMPLATES_DIR").ok();
+    let mut templates = HashMap::new();
+
+    for (name, source) in EMBEDDED {
+        let overridden = override_dir.as_ref().and_then(|dir| {
+            let path = std::path::Path::new(dir).join(format!("{}.txt", name));
+            let source = std::fs::read_to_string(&path).ok()?;
+            parse_template(&source)
+                .map_err(|e| tracing::error!("Ignoring prompt template {}: {}", path.display(), e))
+                .ok()
+        });
+        let template = overridden.unwrap_or_else(|| {
+            parse_template(source).expect("embedded prompt templates must be valid")
+        });
+        templates.insert(*name, template);
+    }
+
+    templates
+}
+
+static TEMPLATES: OnceCell<HashMap<&'static str, Template>> = OnceCell::const_new();
+
+/// A prompt with typed variables, rendered from the template called `NAME`.
+pub trait Prompt {
+    const NAME: &'static str;
+
+    fn variables(&self) -> Vec<(&'static str, String)>;
+}
+
+pub struct RenderedPrompt {
+    pub text: String,
+    pub reference: PromptRef,
+}
+
+/// Fills `{{variable}}` placeholders. Fails if the template uses a variable the prompt
+/// does not provide, so an override cannot silently send a half-filled prompt.
+pub async fn render<P: Prompt>(prompt: &P) -> Result<RenderedPrompt, ServerFnError> {
+    let templates = TEMPLATES.get_or_init(init_templates).await;
+    let template = templates
+        .get(P::NAME)
+        .ok_or_else(|| ServerFnError::new(format!("Unknown prompt template '{}'", P::NAME)))?;
+    let variables: HashMap<&str, String> = prompt.variables().into_iter().collect();
+
+    let placeholder = Regex::new(r"\{\{\s*(\w+)\s*\}\}").unwrap();
+    if let Some(caps) = placeholder
+        .captures_iter(&template.body)
+        .find(|caps| !variables.contains_key(&caps[1]))
+    {
+        return Err(ServerFnError::new(format!(
+            "Prompt template '{}' v{} uses unknown variable '{}'",
+            P::NAME,
+            template.version,
+            &caps[1]
+        )));
+    }
+
+    let text = placeholder
+        .replace_all(&template.body, |caps: &regex::Captures| {
+            variables[&caps[1]].clone()
+        })
+        .into_owned();
+
+    Ok(RenderedPrompt {
+        text,
+        reference: PromptRef {
+            name: P::NAME.to_string(),
+            version: template.version,
+        },
+    })
+}
+
+/// Describes the traveller's preferences for the `traveller` prompt variable.
+pub fn traveller_profile(preferences: &UserPreferences) -> String {
+    let mut lines = vec![
+        format!("- Travel pace: {}", preferences.pace.label()),
+        format!("- Budget style: {}", preferences.budget_style.label()),
+    ];
+    let optional = [
+        ("Home city", preferences.home_city.clone()),
+        ("Interests", preferences.interests.join(", ")),
+        ("Dietary needs", preferences.dietary_needs.clone()),
+        ("Mobility constraints", preferences.mobility.clone()),
+    ];
+    for (label, value) in optional {
+        if !value.trim().is_empty() {
+            lines.push(format!("- {}: {}", label, value.trim()));
+        }
+    }
+    format!(
+        "**Traveller (T):** Tailor the plan to this traveller.\n{}\n",
+        lines.join("\n")
+    )
+}
+
+pub struct TripOutlinePrompt {
+    pub title: String,
+    pub destination: String,
+    pub language: String,
+    pub budget: u64,
+    pub currency: String,
+    pub schedule: String,
+    pub places: u64,
+    pub traveller: String,
+}
+
+impl Prompt for TripOutlinePrompt {
+    const NAME: &'static str = "trip_outline";
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("title", self.title.clone()),
+            ("destination", self.destination.clone()),
+            ("language", self.language.clone()),
+            ("budget", self.budget.to_string()),
+            ("currency", self.currency.clone()),
+            ("schedule", self.schedule.clone()),
+            ("places", self.places.to_string()),
+            ("traveller", self.traveller.clone()),
+        ]
+    }
+}
+
+pub struct TripOutlineFixPrompt {
+    pub problem: String,
+    pub days: u64,
+    pub places: u64,
+}
+
+impl Prompt for TripOutlineFixPrompt {
+    const NAME: &'static str = "trip_outline_fix";
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("problem", self.problem.clone()),
+            ("days", self.days.to_string()),
+            ("places", self.places.to_string()),
+        ]
+    }
+}
+
+pub struct DetailMarkdownPrompt {
+    pub detail_title: String,
+    pub trip_title: String,
+    pub language: String,
+    pub weather: String,
+    pub traveller: String,
+}
+
+impl Prompt for DetailMarkdownPrompt {
+    const NAME: &'static str = "detail_markdown";
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("detail_title", self.detail_title.clone()),
+            ("trip_title", self.trip_title.clone()),
+            ("language", self.language.clone()),
+            ("weather", self.weather.clone()),
+            ("traveller", self.traveller.clone()),
+        ]
+    }
+}
+
+pub struct ChatAnswerPrompt {
+    pub trip_title: String,
+    pub detail: String,
+    pub user_query: String,
+    pub language: String,
+    pub traveller: String,
+}
+
+impl Prompt for ChatAnswerPrompt {
+    const NAME: &'static str = "chat_answer";
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("trip_title", self.trip_title.clone()),
+            ("detail", self.detail.clone()),
+            ("user_query", self.user_query.clone()),
+            ("language", self.language.clone()),
+            ("traveller", self.traveller.clone()),
+        ]
+    }
+}
+
+pub struct PackingListPrompt {
+    pub language: String,
+    pub title: String,
+    pub destination: String,
+    pub dates: String,
+    pub activities: String,
+    pub weather: String,
+    pub categories: String,
+}
+
+impl Prompt for PackingListPrompt {
+    const NAME: &'static str = "packing_list";
+
+    fn variables(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("language", self.language.clone()),
+            ("title", self.title.clone()),
+            ("destination", self.destination.clone()),
+            ("dates", self.dates.clone()),
+            ("activities", self.activities.clone()),
+            ("weather", self.weather.clone()),
+            ("categories", self.categories.clone()),
+        ]
+    }
+}
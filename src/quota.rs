@@ -0,0 +1,309 @@
+use crate::db::get_client;
+use crate::llm::{get_models, LlmCall};
+use crate::server::auth::model::User;
+use crate::server::plan::model::{PlanLimits, QuotaError, QuotaMetric, Usage};
+use crate::server::trip::model::Trip;
+use bson::{doc, oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use dioxus::prelude::ServerFnError;
+use dioxus_logger::tracing;
+use futures_util::StreamExt;
+use mongodb::options::ReturnDocument;
+use mongodb::{Collection, Database};
+use serde::{Deserialize, Serialize};
+
+/// Midnight UTC on the first day of the month containing `now`; quotas reset at this instant.
+pub fn period_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+/// One user's consumption in one month, in the `quota_usage` collection.
+///
+/// Every reservation is a single conditional `$inc`, so concurrent requests cannot push a
+/// counter past its limit by more than one request's worth.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct QuotaCounter {
+    /// `<user id>:<YYYY-MM>`.
+    #[serde(rename = "_id")]
+    pub key: String,
+    pub user: ObjectId,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "periodStart")]
+    pub period_start: DateTime<Utc>,
+    pub trips: i64,
+    #[serde(rename = "chatMessages")]
+    pub chat_messages: i64,
+    /// Tokens weighted by model price, see [`ModelInfo::quota_tokens`](crate::server::llm::model::ModelInfo::quota_tokens).
+    pub tokens: i64,
+}
+
+impl QuotaCounter {
+    fn used(&self) -> PlanLimits {
+        PlanLimits {
+            trips: self.trips.max(0) as u64,
+            chat_messages: self.chat_messages.max(0) as u64,
+            tokens: self.tokens.max(0) as u64,
+        }
+    }
+}
+
+fn field(metric: QuotaMetric) -> &'static str {
+    match metric {
+        QuotaMetric::Trips => "trips",
+        QuotaMetric::ChatMessages => "chatMessages",
+        QuotaMetric::Tokens => "tokens",
+    }
+}
+
+async fn counters() -> Collection<QuotaCounter> {
+    let client = get_client().await;
+    client
+        .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."))
+        .collection::<QuotaCounter>("quota_usage")
+}
+
+/// Usage recorded before counters existed: generated trips are the ones carrying a prompt
+/// reference, chat messages are successful `chat_answer` calls and tokens cover every
+/// recorded model call, weighted by its model's price.
+async fn recorded_usage(
+    db: &Database,
+    user: &User,
+    start: DateTime<Utc>,
+) -> Result<PlanLimits, ServerFnError> {
+    let trips = db
+        .collection::<Trip>("trips")
+        .count_documents(doc! {
+            "user": user.id,
+            "prompt": { "$ne": null },
+            "createdAt": { "$gte": start },
+        })
+        .await?;
+
+    let mut calls = db
+        .collection::<LlmCall>("llm_calls")
+        .aggregate(vec![
+            doc! { "$match": { "user": user.id, "createdAt": { "$gte": start } } },
+            doc! { "$group": {
+                "_id": "$model_id",
+                "chat_messages": { "$sum": { "$cond": [
+                    { "$and": ["$success", { "$eq": ["$purpose", "chat_answer"] }] }, 1, 0
+                ] } },
+                "input_tokens": { "$sum": "$input_tokens" },
+                "output_tokens": { "$sum": "$output_tokens" },
+            } },
+        ])
+        .await?;
+
+    // Tokens are weighted by model price as `converse` counts them; models no longer in the
+    // registry count at the default model's price.
+    let models = get_models().await;
+    let base = &models[0];
+    let mut usage = PlanLimits {
+        trips,
+        chat_messages: 0,
+        tokens: 0,
+    };
+    while let Some(group) = calls.next().await.transpose()? {
+        let number = |key: &str| -> u64 {
+            match group.get(key) {
+                Some(bson::Bson::Int32(value)) => *value as u64,
+                Some(bson::Bson::Int64(value)) => *value as u64,
+                Some(bson::Bson::Double(value)) => *value as u64,
+                _ => 0,
+            }
+        };
+        let model = group
+            .get_str("_id")
+            .ok()
+            .and_then(|model_id| models.iter().find(|model| model.matches(model_id)))
+            .unwrap_or(base);
+        usage.chat_messages += number("chat_messages");
+        usage.tokens += model.quota_tokens(base, number("input_tokens"), number("output_tokens"));
+    }
+    Ok(usage)
+}
+
+/// The key of `user`'s counter for the current month, creating the counter from recorded
+/// usage the first time it is needed.
+async fn counter_key(user: &User) -> Result<(String, DateTime<Utc>), ServerFnError> {
+    let start = period_start(Utc::now());
+    let key = format!("{}:{}", user.id.to_hex(), start.format("%Y-%m"));
+    let collection = counters().await;
+    if collection.find_one(doc! { "_id": &key }).await?.is_none() {
+        let client = get_client().await;
+        let db = client
+            .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+        let used = recorded_usage(&db, user, start).await?;
+        // `$setOnInsert` leaves a counter created concurrently by another request alone.
+        collection
+            .update_one(
+                doc! { "_id": &key },
+                doc! { "$setOnInsert": {
+                    "user": user.id,
+                    "periodStart": start,
+                    "trips": used.trips as i64,
+                    "chatMessages": used.chat_messages as i64,
+                    "tokens": used.tokens as i64,
+                } },
+            )
+            .upsert(true)
+            .await?;
+    }
+    Ok((key, start))
+}
+
+async fn load_counter(key: &str) -> Result<QuotaCounter, ServerFnError> {
+    counters()
+        .await
+        .find_one(doc! { "_id": key })
+        .await?
+        .ok_or(ServerFnError::new("Usage counter not found"))
+}
+
+/// What `user` has consumed since the start of the current month.
+pub async fn current_usage(user: &User) -> Result<Usage, ServerFnError> {
+    let (key, start) = counter_key(user).await?;
+    let counter = load_counter(&key).await?;
+
+    Ok(Usage {
+        plan: user.plan,
+        limits: user.plan.limits(),
+        used: counter.used(),
+        period_start: start,
+    })
+}
+
+/// The first of `metrics` that `counter` has used up, as a [`QuotaError`].
+fn exceeded(user: &User, counter: &QuotaCounter, metrics: &[QuotaMetric]) -> ServerFnError {
+    let limits = user.plan.limits();
+    let used = counter.used();
+    let metric = metrics
+        .iter()
+        .copied()
+        .find(|metric| used.get(*metric) >= limits.get(*metric))
+        .unwrap_or(metrics[0]);
+    QuotaError {
+        plan: user.plan,
+        metric,
+        used: used.get(metric),
+        limit: limits.get(metric),
+    }
+    .into()
+}
+
+/// Trips and chat messages taken by [`reserve_quota`], handed back if generation fails.
+#[must_use]
+pub struct QuotaReservation {
+    key: String,
+    metrics: Vec<QuotaMetric>,
+}
+
+impl QuotaReservation {
+    pub async fn release(self) {
+        if self.metrics.is_empty() {
+            return;
+        }
+        let mut inc = doc! {};
+        for metric in &self.metrics {
+            inc.insert(field(*metric), -1i64);
+        }
+        if let Err(e) = counters()
+            .await
+            .update_one(doc! { "_id": &self.key }, doc! { "$inc": inc })
+            .await
+        {
+            tracing::error!("Failed to release quota {}: {}", self.key, e);
+        }
+    }
+}
+
+/// Fails with a [`QuotaError`] if any of `metrics` has reached the limit of the user's plan,
+/// and otherwise takes one trip or chat message from the allowance in the same atomic update.
+/// Called by generation endpoints before they contact the model.
+///
+/// Tokens are only checked here; [`reserve_tokens`] takes them for each model call.
+pub async fn reserve_quota(
+    user: &User,
+    metrics: &[QuotaMetric],
+) -> Result<QuotaReservation, ServerFnError> {
+    let (key, _) = counter_key(user).await?;
+    let limits = user.plan.limits();
+
+    let mut filter = doc! { "_id": &key };
+    let mut inc = doc! {};
+    let mut reserved = Vec::new();
+    for &metric in metrics {
+        filter.insert(field(metric), doc! { "$lt": limits.get(metric) as i64 });
+        if metric != QuotaMetric::Tokens {
+            inc.insert(field(metric), 1i64);
+            reserved.push(metric);
+        }
+    }
+
+    let collection = counters().await;
+    let matched = if inc.is_empty() {
+        collection.find_one(filter).await?
+    } else {
+        collection
+            .find_one_and_update(filter, doc! { "$inc": inc })
+            .return_document(ReturnDocument::After)
+            .await?
+    };
+    if matched.is_none() {
+        return Err(exceeded(user, &load_counter(&key).await?, metrics));
+    }
+
+    Ok(QuotaReservation {
+        key,
+        metrics: reserved,
+    })
+}
+
+/// Weighted tokens set aside for one model call, corrected by [`settle_tokens`].
+pub struct TokenReservation {
+    key: String,
+    tokens: i64,
+}
+
+/// Takes `tokens` from the user's allowance before a model call, refusing once it is used up.
+pub async fn reserve_tokens(user: &User, tokens: u64) -> Result<TokenReservation, ServerFnError> {
+    let (key, _) = counter_key(user).await?;
+    let limit = user.plan.limits().tokens as i64;
+    let tokens = tokens as i64;
+
+    let updated = counters()
+        .await
+        .update_one(
+            doc! { "_id": &key, "tokens": { "$lt": limit } },
+            doc! { "$inc": { "tokens": tokens } },
+        )
+        .await?;
+    if updated.matched_count == 0 {
+        return Err(exceeded(
+            user,
+            &load_counter(&key).await?,
+            &[QuotaMetric::Tokens],
+        ));
+    }
+
+    Ok(TokenReservation { key, tokens })
+}
+
+/// Replaces a reservation with the weighted tokens the call actually used.
+pub async fn settle_tokens(reservation: TokenReservation, used: u64) {
+    let delta = used as i64 - reservation.tokens;
+    if delta == 0 {
+        return;
+    }
+    if let Err(e) = counters()
+        .await
+        .update_one(
+            doc! { "_id": &reservation.key },
+            doc! { "$inc": { "tokens": delta } },
+        )
+        .await
+    {
+        tracing::error!("Failed to settle tokens for {}: {}", reservation.key, e);
+    }
+}
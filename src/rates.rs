@@ -0,0 +1,77 @@
+use crate::currency::RateTable;
+use chrono::prelude::*;
+use dioxus_logger::tracing;
+use std::env;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use tokio::sync::{OnceCell, RwLock};
+
+static RATES: OnceCell<RwLock<RateTable>> = OnceCell::const_new();
+/// Set while a background refresh is running, so concurrent callers don't start another.
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+/// Unix time of the last refresh attempt, used to back off after failures.
+static LAST_ATTEMPT: AtomicI64 = AtomicI64::new(0);
+
+const RETRY_SECS: i64 = 5 * 60;
+
+fn load_rate_file(path: &str) -> anyhow::Result<RateTable> {
+    let content = std::fs::read_to_string(path)?;
+    if path.ends_with(".csv") {
+        let base = env::var("CURRENCY_RATES_BASE").unwrap_or_else(|_| "USD".to_string());
+        RateTable::from_csv(&content, &base)
+    } else {
+        RateTable::from_json(&content)
+    }
+}
+
+async fn init_rates() -> RwLock<RateTable> {
+    let table = match env::var("CURRENCY_RATES_FILE") {
+        Ok(path) => load_rate_file(&path).unwrap_or_else(|e| {
+            tracing::error!("Failed to load rate table from {}: {}", path, e);
+            RateTable::default()
+        }),
+        Err(_) => RateTable::default(),
+    };
+    RwLock::new(table)
+}
+
+/// Fetches a fresh table from `CURRENCY_RATES_URL`. The endpoint must return JSON
+/// shaped like `{ "base": "USD", "rates": { "EUR": 0.95, ... } }`.
+pub async fn refresh_rates() -> anyhow::Result<RateTable> {
+    let url = env::var("CURRENCY_RATES_URL")?;
+    let body = reqwest::get(&url).await?.error_for_status()?.text().await?;
+    let mut table = RateTable::from_json(&body)?;
+    table.updated_at = Utc::now();
+
+    *RATES.get_or_init(init_rates).await.write().await = table.clone();
+    Ok(table)
+}
+
+/// Returns the current table straight away. A stale table starts one background refresh and
+/// keeps being served until it succeeds; failed refreshes are retried after a few minutes.
+pub async fn get_rates() -> RateTable {
+    let table = RATES.get_or_init(init_rates).await.read().await.clone();
+
+    let ttl = env::var("CURRENCY_RATES_TTL_SECS")
+        .ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .unwrap_or(24 * 60 * 60);
+    let now = Utc::now();
+    let stale = now - table.updated_at > chrono::Duration::seconds(ttl);
+    let retry_due = now.timestamp() - LAST_ATTEMPT.load(Ordering::Relaxed) >= RETRY_SECS;
+
+    if stale
+        && retry_due
+        && env::var("CURRENCY_RATES_URL").is_ok()
+        && !REFRESHING.swap(true, Ordering::AcqRel)
+    {
+        LAST_ATTEMPT.store(now.timestamp(), Ordering::Relaxed);
+        tokio::spawn(async {
+            if let Err(e) = refresh_rates().await {
+                tracing::error!("Failed to refresh rate table: {}", e);
+            }
+            REFRESHING.store(false, Ordering::Release);
+        });
+    }
+
+    table
+}
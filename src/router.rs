@@ -2,12 +2,16 @@
 
 use crate::components::navbar::HomeNavBar;
 use crate::components::navbar::LoginNavBar;
+use crate::pages::admin::Admin;
 use crate::pages::dashboard::Dashboard;
 use crate::pages::home::Home;
 use crate::pages::login::Login;
+use crate::pages::oauth::OAuthCallback;
+use crate::pages::password::{ForgotPassword, ResetPassword};
 use crate::pages::signup::Register;
 use crate::pages::trip::EditTrip;
 use crate::pages::trip::ReadTrip;
+use crate::pages::verify::VerifyEmail;
 use dioxus::prelude::*;
 
 #[derive(Clone, Routable, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -22,6 +26,10 @@ pub enum Route {
     Login {},
     #[route("/signup")]
     Register {},
+    #[route("/forgot-password")]
+    ForgotPassword {},
+    #[route("/reset-password/:token")]
+    ResetPassword { token: String },
     #[end_layout]
     #[route("/dashboard/trip/read/:id")]
     ReadTrip { id: String },
@@ -29,4 +37,10 @@ pub enum Route {
     EditTrip { id: String },
     #[route("/dashboard")]
     Dashboard {},
+    #[route("/admin")]
+    Admin {},
+    #[route("/verify/:token")]
+    VerifyEmail { token: String },
+    #[route("/oauth/:provider/callback?:query")]
+    OAuthCallback { provider: String, query: String },
 }
@@ -0,0 +1,105 @@
+use crate::db::get_client;
+use crate::server::conversation::model::Message;
+use crate::server::trip::model::Detail;
+use ammonia::Builder;
+use bson::doc;
+use chrono::prelude::*;
+use dioxus_logger::tracing;
+use futures_util::TryStreamExt;
+use std::collections::HashSet;
+
+/// Bump when the allowlist changes so stored content is cleaned again on the next start.
+/// v2 covers outline details, which were stored without sanitizing until then.
+const BACKFILL_ID: &str = "sanitize_html_v2";
+
+#[rustfmt::skip]
+const ALLOWED_TAGS: &[&str] = &[
+    "h1", "h2", "h3", "h4", "h5", "h6", "p", "br", "hr", "div", "span",
+    "strong", "b", "em", "i", "u", "s", "small", "mark", "sub", "sup",
+    "ul", "ol", "li", "dl", "dt", "dd", "blockquote", "code", "pre",
+    "a", "img", "figure", "figcaption",
+    "table", "thead", "tbody", "tfoot", "tr", "th", "td", "caption",
+    "input",
+];
+
+/// Strips everything outside the allowlist: scripts, styles, event handlers, iframes and
+/// `javascript:` or `data:` URLs. Links are forced to open without access to the opener, and
+/// inputs are forced to be disabled checkboxes, as markdown task lists render them.
+pub fn sanitize_html(html: &str) -> String {
+    Builder::new()
+        .tags(ALLOWED_TAGS.iter().copied().collect())
+        .generic_attributes(HashSet::from(["class", "id", "title"]))
+        .tag_attributes(
+            [
+                ("a", HashSet::from(["href"])),
+                ("img", HashSet::from(["src", "alt", "width", "height"])),
+                ("th", HashSet::from(["colspan", "rowspan"])),
+                ("td", HashSet::from(["colspan", "rowspan"])),
+                ("input", HashSet::from(["checked"])),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "")
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(html)
+        .to_string()
+}
+
+/// Re-sanitizes detail and message HTML stored before sanitizing was in place. Runs once
+/// per `BACKFILL_ID`, recorded in the `migrations` collection.
+pub async fn backfill_sanitized_html() -> anyhow::Result<()> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let migrations = db.collection::<bson::Document>("migrations");
+
+    if migrations
+        .find_one(doc! { "_id": BACKFILL_ID })
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let details = db.collection::<Detail>("details");
+    let mut cursor = details.find(doc! { "html": { "$ne": "" } }).await?;
+    let mut cleaned_details = 0;
+    while let Some(detail) = cursor.try_next().await? {
+        let html = sanitize_html(&detail.html);
+        if html != detail.html {
+            details
+                .update_one(doc! { "_id": detail.id }, doc! { "$set": { "html": html } })
+                .await?;
+            cleaned_details += 1;
+        }
+    }
+
+    let messages = db.collection::<Message>("messages");
+    let mut cursor = messages.find(doc! { "sender": { "$ne": "user" } }).await?;
+    let mut cleaned_messages = 0;
+    while let Some(message) = cursor.try_next().await? {
+        let content = sanitize_html(&message.content);
+        if content != message.content {
+            messages
+                .update_one(
+                    doc! { "_id": message.id },
+                    doc! { "$set": { "content": content } },
+                )
+                .await?;
+            cleaned_messages += 1;
+        }
+    }
+
+    migrations
+        .insert_one(doc! { "_id": BACKFILL_ID, "completedAt": Utc::now() })
+        .await?;
+    tracing::info!(
+        "Sanitized {} details and {} messages",
+        cleaned_details,
+        cleaned_messages
+    );
+    Ok(())
+}
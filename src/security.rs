@@ -0,0 +1,434 @@
+//! Brute-force protection for the auth server functions and the security event log.
+//!
+//! [`guard`] wraps the body of a server function. Attempts are counted per client IP and, for
+//! sign-ins, per account. Past a free allowance each further attempt has to wait longer than
+//! the last, and too many lock the key out for a while. Every outcome is written to the
+//! `security_events` collection.
+
+use axum::extract::ConnectInfo;
+use bson::{
+    doc,
+    oid::ObjectId,
+    serde_helpers::{chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional},
+};
+use chrono::prelude::*;
+use chrono::Duration;
+use dioxus::prelude::{server_context, ServerFnError};
+use dioxus_logger::tracing;
+use mongodb::options::ReturnDocument;
+use mongodb::Database;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::SocketAddr;
+
+/// How attempts against one key are limited.
+struct Policy {
+    /// Attempts allowed before delays start.
+    free: i32,
+    /// Attempts after which the key is locked out.
+    lockout_after: i32,
+    lockout: Duration,
+    max_delay: Duration,
+    /// Counters are forgotten this long after the last counted attempt.
+    window: Duration,
+}
+
+const ACCOUNT_POLICY: Policy = Policy {
+    free: 3,
+    lockout_after: 10,
+    lockout: Duration::minutes(15),
+    max_delay: Duration::seconds(60),
+    window: Duration::hours(1),
+};
+
+/// Looser than the account policy, since several people can share an address.
+const IP_POLICY: Policy = Policy {
+    free: 10,
+    lockout_after: 50,
+    lockout: Duration::minutes(15),
+    max_delay: Duration::seconds(30),
+    window: Duration::hours(1),
+};
+
+/// Every registration or reset request counts, successful or not.
+const SIGNUP_POLICY: Policy = Policy {
+    free: 5,
+    lockout_after: 5,
+    lockout: Duration::hours(1),
+    max_delay: Duration::zero(),
+    window: Duration::hours(1),
+};
+
+/// The auth operations that are guarded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Login,
+    Register,
+    PasswordReset,
+    /// Checking the current password before changing it or the email address.
+    PasswordChange,
+    /// The second sign-in step for accounts with 2FA.
+    TwoFactor,
+}
+
+impl Action {
+    fn name(self) -> &'static str {
+        match self {
+            Action::Login => "login",
+            Action::Register => "register",
+            Action::PasswordReset => "password_reset",
+            Action::PasswordChange => "password_change",
+            Action::TwoFactor => "two_factor",
+        }
+    }
+
+    /// Password and code checks, which are limited per account and only count failures.
+    fn checks_password(self) -> bool {
+        matches!(
+            self,
+            Action::Login | Action::PasswordChange | Action::TwoFactor
+        )
+    }
+
+    /// Whether a success proves who the caller is. A password sign-in may still owe its second
+    /// factor, so its failures are only forgotten through [`clear_failures`].
+    fn clears_on_success(self) -> bool {
+        matches!(self, Action::PasswordChange | Action::TwoFactor)
+    }
+}
+
+/// Attempts recorded against an IP or account.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AttemptCounter {
+    /// `<action>:ip:<address>` or `<action>:account:<email>`.
+    #[serde(rename = "_id")]
+    pub key: String,
+    pub attempts: i32,
+    #[serde(
+        default,
+        with = "chrono_datetime_as_bson_datetime_optional",
+        rename = "nextAttemptAt"
+    )]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        with = "chrono_datetime_as_bson_datetime_optional",
+        rename = "lockedUntil"
+    )]
+    pub locked_until: Option<DateTime<Utc>>,
+    /// The last attempt counted, so [`reserve`] can tell whether its own update went through.
+    #[serde(default)]
+    pub attempt: Option<ObjectId>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SecurityEvent {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    /// `<action>_succeeded`, `<action>_failed`, `<action>_throttled` or `<action>_locked`.
+    pub kind: String,
+    pub ip: String,
+    pub account: Option<String>,
+    pub detail: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// How many proxies in front of the server append to `X-Forwarded-For`, from `TRUST_PROXY`:
+/// `true` for one, a number for several, and none otherwise.
+fn trusted_proxies() -> usize {
+    match std::env::var("TRUST_PROXY").as_deref() {
+        Ok("1" | "true" | "yes") => 1,
+        Ok(hops) => hops.parse().unwrap_or(0),
+        Err(_) => 0,
+    }
+}
+
+/// The caller's address. Clients can send `X-Forwarded-For` themselves, so only the entries
+/// appended by trusted proxies count: the address the outermost one saw is the entry that many
+/// places from the right.
+async fn client_ip() -> String {
+    let context = server_context();
+    let parts = context.request_parts().await;
+    let hops = trusted_proxies();
+    if hops > 0 {
+        let forwarded = parts
+            .headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        let ip = forwarded
+            .len()
+            .checked_sub(hops)
+            .and_then(|index| forwarded.get(index))
+            .filter(|ip| !ip.is_empty());
+        if let Some(ip) = ip {
+            return ip.to_string();
+        }
+    }
+    parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".into())
+}
+
+fn describe_wait(wait: Duration) -> String {
+    if wait.num_seconds() < 60 {
+        format!("{} seconds", wait.num_seconds().max(1))
+    } else {
+        format!("{} minutes", (wait.num_seconds() + 59) / 60)
+    }
+}
+
+/// The outcome of [`reserve`].
+enum Reservation {
+    /// The key is locked out or still inside its delay. Holds the wait and whether it is a lockout.
+    Refused(String, bool),
+    /// The attempt was counted under this id, and whether it locked the key out.
+    Counted(ObjectId, bool),
+}
+
+/// Counts an attempt against `key` unless it is locked out or inside its delay, and sets the next
+/// delay or lockout.
+///
+/// Everything happens in one update, so parallel requests cannot all pass a check before any of
+/// them is counted. An expired counter starts over. The attempt's id is stored on the counter so
+/// the caller can tell whether its own update went through.
+async fn reserve(db: &Database, key: &str, policy: &Policy) -> Result<Reservation, ServerFnError> {
+    let now = Utc::now();
+    let until = now + policy.lockout;
+    let id = ObjectId::new();
+    let expired = doc! { "$not": [{ "$gt": ["$expiresAt", now] }] };
+    let over = doc! { "$subtract": ["$attempts", policy.free + 1] };
+    let delay_ms = doc! { "$min": [
+        policy.max_delay.num_milliseconds(),
+        { "$multiply": [1000, { "$pow": [2, { "$min": [16, over] }] }] },
+    ] };
+    let locks = doc! { "$and": ["$allowed", { "$gte": ["$attempts", policy.lockout_after] }] };
+    let pipeline = vec![
+        doc! { "$set": {
+            "attempts": { "$cond": [expired.clone(), 0, "$attempts"] },
+            "lockedUntil": { "$cond": [expired.clone(), null, "$lockedUntil"] },
+            "nextAttemptAt": { "$cond": [expired, null, "$nextAttemptAt"] },
+        } },
+        doc! { "$set": { "allowed": { "$and": [
+            { "$not": [{ "$gt": ["$lockedUntil", now] }] },
+            { "$not": [{ "$gt": ["$nextAttemptAt", now] }] },
+        ] } } },
+        doc! { "$set": {
+            "attempts": { "$cond": ["$allowed", { "$add": ["$attempts", 1] }, "$attempts"] },
+            "attempt": { "$cond": ["$allowed", id, "$attempt"] },
+        } },
+        doc! { "$set": {
+            "attempts": { "$cond": [locks.clone(), 0, "$attempts"] },
+            "lockedUntil": { "$cond": [locks.clone(), until, "$lockedUntil"] },
+            "nextAttemptAt": { "$cond": [
+                { "$and": ["$allowed", { "$gt": ["$attempts", policy.free] }] },
+                { "$add": [now, delay_ms] },
+                "$nextAttemptAt",
+            ] },
+            "expiresAt": { "$cond": [
+                locks,
+                until + policy.window,
+                { "$cond": ["$allowed", now + policy.window, "$expiresAt"] },
+            ] },
+        } },
+        doc! { "$unset": "allowed" },
+    ];
+    let counter = db
+        .collection::<AttemptCounter>("auth_attempts")
+        .find_one_and_update(doc! { "_id": key }, pipeline)
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or(ServerFnError::new("Failed to record attempt"))?;
+
+    if counter.attempt == Some(id) {
+        let locked = counter.locked_until.is_some_and(|until| until > now);
+        return Ok(Reservation::Counted(id, locked));
+    }
+    if let Some(until) = counter.locked_until.filter(|until| *until > now) {
+        return Ok(Reservation::Refused(describe_wait(until - now), true));
+    }
+    let next = counter.next_attempt_at.unwrap_or(now);
+    Ok(Reservation::Refused(describe_wait(next - now), false))
+}
+
+/// Takes back an attempt that [`reserve`] counted but that turned out not to count, such as a
+/// correct password. The delay or lockout it set is lifted too, unless a later attempt has been
+/// counted since.
+async fn refund(db: &Database, key: &str, id: ObjectId) -> Result<(), ServerFnError> {
+    let collection = db.collection::<AttemptCounter>("auth_attempts");
+    collection
+        .update_one(
+            doc! { "_id": key, "attempt": id },
+            doc! { "$unset": { "nextAttemptAt": "", "lockedUntil": "" } },
+        )
+        .await?;
+    collection
+        .update_one(
+            doc! { "_id": key, "attempts": { "$gt": 0 } },
+            doc! { "$inc": { "attempts": -1 } },
+        )
+        .await?;
+    Ok(())
+}
+
+async fn clear(db: &Database, key: &str) -> Result<(), ServerFnError> {
+    db.collection::<AttemptCounter>("auth_attempts")
+        .delete_one(doc! { "_id": key })
+        .await?;
+    Ok(())
+}
+
+/// Forgets the failures counted against `account` for `action`, once the caller has proved who
+/// they are.
+pub async fn clear_failures(action: Action, account: &str) -> Result<(), ServerFnError> {
+    let client = crate::db::get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let key = format!(
+        "{}:account:{}",
+        action.name(),
+        account.trim().to_lowercase()
+    );
+    clear(&db, &key).await
+}
+
+/// Appends to the security event log. Failures are only logged, so the log cannot block sign-ins.
+pub async fn log_event(
+    db: &Database,
+    kind: String,
+    ip: &str,
+    account: Option<&str>,
+    detail: Option<String>,
+) {
+    tracing::info!(
+        "security event {} ip={} account={}",
+        kind,
+        ip,
+        account.unwrap_or("-")
+    );
+    let event = SecurityEvent {
+        id: ObjectId::new(),
+        kind,
+        ip: ip.to_string(),
+        account: account.map(str::to_string),
+        detail,
+        created_at: Utc::now(),
+    };
+    if let Err(e) = db
+        .collection::<SecurityEvent>("security_events")
+        .insert_one(event)
+        .await
+    {
+        tracing::error!("Failed to write security event: {}", e);
+    }
+}
+
+/// Runs `attempt` for `account` unless the caller's IP or the account is throttled.
+///
+/// The attempt is counted before it runs, so a burst of parallel requests is limited like a
+/// sequence of them. Sign-ins, second factors and password changes are counted per IP and per
+/// account and only failures stay counted: a success takes its attempt back, or clears the
+/// account's counter when it proves who the caller is. Registrations and reset
+/// requests count every attempt per IP.
+pub async fn guard<T, F>(action: Action, account: &str, attempt: F) -> Result<T, ServerFnError>
+where
+    F: Future<Output = Result<T, ServerFnError>>,
+{
+    let client = crate::db::get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let ip = client_ip().await;
+    let account = account.trim().to_lowercase();
+    let ip_key = format!("{}:ip:{}", action.name(), ip);
+    let account_key = format!("{}:account:{}", action.name(), account);
+
+    let mut keys: Vec<(&str, &Policy)> = Vec::new();
+    match action {
+        Action::Login | Action::PasswordChange | Action::TwoFactor => {
+            keys.push((&ip_key, &IP_POLICY));
+            keys.push((&account_key, &ACCOUNT_POLICY));
+        }
+        Action::Register | Action::PasswordReset => keys.push((&ip_key, &SIGNUP_POLICY)),
+    }
+
+    let mut reserved = Vec::new();
+    for (key, policy) in &keys {
+        match reserve(&db, key, policy).await? {
+            Reservation::Counted(id, locked) => {
+                if locked {
+                    log_event(
+                        &db,
+                        format!("{}_locked", action.name()),
+                        &ip,
+                        Some(&account),
+                        Some((*key).to_string()),
+                    )
+                    .await;
+                }
+                reserved.push((*key, id));
+            }
+            Reservation::Refused(wait, locked) => {
+                // The keys reserved so far were never tried.
+                for (key, id) in reserved {
+                    refund(&db, key, id).await?;
+                }
+                log_event(
+                    &db,
+                    format!("{}_throttled", action.name()),
+                    &ip,
+                    Some(&account),
+                    Some((*key).to_string()),
+                )
+                .await;
+                return Err(ServerFnError::new(if locked {
+                    format!("Too many attempts. Try again in {}.", wait)
+                } else {
+                    format!("Please wait {} before trying again.", wait)
+                }));
+            }
+        }
+    }
+
+    let result = attempt.await;
+    match &result {
+        Ok(_) => {
+            log_event(
+                &db,
+                format!("{}_succeeded", action.name()),
+                &ip,
+                Some(&account),
+                None,
+            )
+            .await;
+            if action.checks_password() {
+                for (key, id) in reserved {
+                    if action.clears_on_success() && key == account_key {
+                        clear(&db, key).await?;
+                    } else {
+                        refund(&db, key, id).await?;
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            log_event(
+                &db,
+                format!("{}_failed", action.name()),
+                &ip,
+                Some(&account),
+                Some(e.to_string()),
+            )
+            .await
+        }
+    }
+    result
+}
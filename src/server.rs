@@ -1,4 +1,13 @@
+pub(crate) mod admin;
+pub(crate) mod analytics;
 pub(crate) mod auth;
+pub(crate) mod billing;
 pub(crate) mod common;
 pub(crate) mod conversation;
+pub(crate) mod currency;
+pub(crate) mod expense;
+pub(crate) mod llm;
+pub(crate) mod packing;
+pub(crate) mod plan;
 pub(crate) mod trip;
+pub(crate) mod weather;
@@ -0,0 +1,470 @@
+use bson::doc;
+use dioxus::prelude::*;
+
+use crate::server::admin::model::AuditEntry;
+use crate::server::admin::request::{
+    AdminListUsersRequest, AdminSetRoleRequest, AdminSetSuspendedRequest, AdminUserRequest,
+};
+use crate::server::admin::response::{
+    AdminUserDetail, AdminUserSummary, DashboardResponse, GenerationStats, UserListResponse,
+};
+use crate::server::common::response::SuccessResponse;
+
+#[cfg(feature = "server")]
+use {
+    crate::billing::{payment_provider, PaymentProvider},
+    crate::db::get_client,
+    crate::llm::LlmCall,
+    crate::quota::{current_usage, QuotaCounter},
+    crate::server::admin::model::AdminAction,
+    crate::server::admin::response::{FailedCall, PurposeStats},
+    crate::server::auth::controller::require_admin,
+    crate::server::auth::model::{User, ROLES, ROLE_ADMIN},
+    crate::server::billing::model::Subscription,
+    crate::server::conversation::model::{Conversation, Message},
+    crate::server::expense::model::Expense,
+    crate::server::packing::model::{PackingList, PackingTemplate},
+    crate::server::plan::model::Plan,
+    crate::server::trip::model::{Detail, Trip},
+    crate::storage::delete_avatars,
+    crate::tokens::{revoke_sessions, PasswordReset, Session},
+    crate::two_factor::LoginChallenge,
+    bson::{oid::ObjectId, Document},
+    chrono::prelude::*,
+    futures_util::TryStreamExt,
+    mongodb::Database,
+};
+
+#[cfg(feature = "server")]
+const PAGE_SIZE: u64 = 20;
+#[cfg(feature = "server")]
+const STATS_WINDOW_DAYS: i64 = 30;
+#[cfg(feature = "server")]
+const RECENT_FAILURES: i64 = 20;
+#[cfg(feature = "server")]
+const AUDIT_LOG_LIMIT: i64 = 100;
+
+#[cfg(feature = "server")]
+async fn get_db() -> Database {
+    let client = get_client().await;
+    client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."))
+}
+
+/// Appends an entry to `admin_audit_log`. Mutating actions are recorded before they run,
+/// so an action that cannot be recorded does not happen.
+#[cfg(feature = "server")]
+async fn record_action(
+    admin: &User,
+    action: AdminAction,
+    target: Option<ObjectId>,
+    details: impl Into<String>,
+) -> Result<(), ServerFnError> {
+    get_db()
+        .await
+        .collection::<AuditEntry>("admin_audit_log")
+        .insert_one(AuditEntry {
+            id: ObjectId::new(),
+            admin: admin.id,
+            admin_email: admin.email.clone(),
+            action,
+            target,
+            details: details.into(),
+            created_at: Utc::now(),
+        })
+        .await?;
+    Ok(())
+}
+
+/// Looks up the user an admin action targets, refusing actions on the admin's own account.
+#[cfg(feature = "server")]
+async fn target_user(admin: &User, user_id: &str, allow_self: bool) -> Result<User, ServerFnError> {
+    let user_id =
+        ObjectId::parse_str(user_id).map_err(|_| ServerFnError::new("Invalid user ID"))?;
+    if !allow_self && user_id == admin.id {
+        return Err(ServerFnError::new(
+            "Admins cannot do this to their own account",
+        ));
+    }
+    get_db()
+        .await
+        .collection::<User>("users")
+        .find_one(doc! { "_id": user_id })
+        .await?
+        .ok_or(ServerFnError::new("User not found"))
+}
+
+#[server]
+pub async fn dashboard_overview(
+    token: String,
+) -> Result<SuccessResponse<DashboardResponse>, ServerFnError> {
+    let admin = require_admin(token).await?;
+    record_action(&admin, AdminAction::ViewOverview, None, "").await?;
+
+    let db = get_db().await;
+    let user_collection = db.collection::<User>("users");
+    let trip_collection = db.collection::<Trip>("trips");
+
+    let users = user_collection.estimated_document_count().await?;
+    let trips = trip_collection.estimated_document_count().await?;
+    let paid_users = user_collection
+        .count_documents(doc! { "plan": { "$in": [Plan::Pro.name(), Plan::Team.name()] } })
+        .await?;
+    let suspended_users = user_collection
+        .count_documents(doc! { "suspended": true })
+        .await?;
+    let admins = user_collection
+        .count_documents(doc! { "role": ROLE_ADMIN })
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: DashboardResponse {
+            users,
+            trips,
+            paid_users,
+            suspended_users,
+            admins,
+        },
+    })
+}
+
+#[server]
+pub async fn admin_list_users(
+    req: AdminListUsersRequest,
+) -> Result<SuccessResponse<UserListResponse>, ServerFnError> {
+    let admin = require_admin(req.token).await?;
+    record_action(&admin, AdminAction::ListUsers, None, req.query.trim()).await?;
+
+    let query = req.query.trim();
+    let filter = if query.is_empty() {
+        doc! {}
+    } else {
+        let pattern = regex::escape(query);
+        doc! { "$or": [
+            { "name": { "$regex": &pattern, "$options": "i" } },
+            { "email": { "$regex": &pattern, "$options": "i" } },
+        ] }
+    };
+
+    let user_collection = get_db().await.collection::<User>("users");
+    let total = user_collection.count_documents(filter.clone()).await?;
+    let users = user_collection
+        .find(filter)
+        .sort(doc! { "createdAt": -1 })
+        .skip(req.page * PAGE_SIZE)
+        .limit(PAGE_SIZE as i64)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .map(AdminUserSummary::from)
+        .collect();
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: UserListResponse {
+            users,
+            total,
+            page: req.page,
+            page_size: PAGE_SIZE,
+        },
+    })
+}
+
+#[server]
+pub async fn admin_get_user(
+    req: AdminUserRequest,
+) -> Result<SuccessResponse<AdminUserDetail>, ServerFnError> {
+    let admin = require_admin(req.token).await?;
+    let user = target_user(&admin, &req.user_id, true).await?;
+    record_action(&admin, AdminAction::ViewUser, Some(user.id), &user.email).await?;
+
+    let trips = get_db()
+        .await
+        .collection::<Trip>("trips")
+        .find(doc! { "user": user.id })
+        .sort(doc! { "createdAt": -1 })
+        .await?
+        .try_collect()
+        .await?;
+    let usage = current_usage(&user).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: AdminUserDetail {
+            user: user.into(),
+            trips,
+            usage,
+        },
+    })
+}
+
+#[server]
+pub async fn admin_set_suspended(
+    req: AdminSetSuspendedRequest,
+) -> Result<SuccessResponse<AdminUserSummary>, ServerFnError> {
+    let admin = require_admin(req.token).await?;
+    let mut user = target_user(&admin, &req.user_id, false).await?;
+    let action = if req.suspended {
+        AdminAction::SuspendUser
+    } else {
+        AdminAction::UnsuspendUser
+    };
+    record_action(&admin, action, Some(user.id), &user.email).await?;
+
+    get_db()
+        .await
+        .collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": { "suspended": req.suspended, "updatedAt": Utc::now() } },
+        )
+        .await?;
+    user.suspended = req.suspended;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: user.into(),
+    })
+}
+
+#[server]
+pub async fn admin_set_role(
+    req: AdminSetRoleRequest,
+) -> Result<SuccessResponse<AdminUserSummary>, ServerFnError> {
+    let admin = require_admin(req.token).await?;
+    if !ROLES.contains(&req.role.as_str()) {
+        return Err(ServerFnError::new("Unknown role"));
+    }
+    let mut user = target_user(&admin, &req.user_id, false).await?;
+    record_action(
+        &admin,
+        AdminAction::ChangeRole,
+        Some(user.id),
+        format!("{}: {} -> {}", user.email, user.role, req.role),
+    )
+    .await?;
+
+    get_db()
+        .await
+        .collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": { "role": &req.role, "updatedAt": Utc::now() } },
+        )
+        .await?;
+    user.role = req.role;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: user.into(),
+    })
+}
+
+/// Deletes a user with their trips, details, conversations, expenses, packing lists and
+/// avatar. Model call and billing records are kept for accounting.
+#[server]
+pub async fn admin_delete_user(
+    req: AdminUserRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let admin = require_admin(req.token).await?;
+    let user = target_user(&admin, &req.user_id, false).await?;
+    record_action(&admin, AdminAction::DeleteUser, Some(user.id), &user.email).await?;
+
+    let db = get_db().await;
+    let owned = doc! { "user": user.id };
+
+    // Cancel billing first, so a failure leaves the account in place to retry rather than a
+    // subscription that keeps charging a user who no longer exists.
+    let subscriptions = db
+        .collection::<Subscription>("subscriptions")
+        .find(doc! { "user": user.id, "status": { "$ne": "canceled" } })
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let provider = payment_provider().await;
+    for subscription in subscriptions {
+        provider
+            .cancel_subscription(&subscription.provider_subscription_id)
+            .await
+            .map_err(|e| ServerFnError::new(format!("Failed to cancel subscription: {}", e)))?;
+        record_action(
+            &admin,
+            AdminAction::CancelSubscription,
+            Some(user.id),
+            format!(
+                "{} subscription {} of {}",
+                subscription.provider, subscription.provider_subscription_id, user.email
+            ),
+        )
+        .await?;
+    }
+    db.collection::<Subscription>("subscriptions")
+        .delete_many(owned.clone())
+        .await?;
+
+    let trip_ids: Vec<ObjectId> = db
+        .collection::<Trip>("trips")
+        .find(owned.clone())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .map(|trip| trip.id)
+        .collect();
+    let conversation_ids: Vec<ObjectId> = db
+        .collection::<Conversation>("conversations")
+        .find(owned.clone())
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .map(|conversation| conversation.id)
+        .collect();
+
+    db.collection::<Detail>("details")
+        .delete_many(doc! { "trip_id": { "$in": trip_ids } })
+        .await?;
+    db.collection::<Message>("messages")
+        .delete_many(doc! { "conversation": { "$in": conversation_ids } })
+        .await?;
+    db.collection::<Conversation>("conversations")
+        .delete_many(owned.clone())
+        .await?;
+    db.collection::<Expense>("expenses")
+        .delete_many(owned.clone())
+        .await?;
+    db.collection::<PackingList>("packing_lists")
+        .delete_many(owned.clone())
+        .await?;
+    db.collection::<PackingTemplate>("packing_templates")
+        .delete_many(owned.clone())
+        .await?;
+    db.collection::<Trip>("trips")
+        .delete_many(owned.clone())
+        .await?;
+    db.collection::<QuotaCounter>("quota_usage")
+        .delete_many(owned.clone())
+        .await?;
+    db.collection::<PasswordReset>("password_resets")
+        .delete_many(owned.clone())
+        .await?;
+    db.collection::<LoginChallenge>("login_challenges")
+        .delete_many(owned.clone())
+        .await?;
+    delete_avatars(user.id, &user.photo)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to delete avatar: {}", e)))?;
+    revoke_sessions(&db, owned.clone()).await?;
+    db.collection::<Session>("sessions")
+        .delete_many(owned)
+        .await?;
+    db.collection::<User>("users")
+        .delete_one(doc! { "_id": user.id })
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "User deleted".into(),
+    })
+}
+
+#[server]
+pub async fn admin_generation_stats(
+    token: String,
+) -> Result<SuccessResponse<GenerationStats>, ServerFnError> {
+    let admin = require_admin(token).await?;
+    record_action(&admin, AdminAction::ViewStats, None, "").await?;
+
+    let since = Utc::now() - chrono::Duration::days(STATS_WINDOW_DAYS);
+    let calls = get_db().await.collection::<LlmCall>("llm_calls");
+
+    let purposes: Vec<PurposeStats> = calls
+        .aggregate(vec![
+            doc! { "$match": { "createdAt": { "$gte": since } } },
+            doc! { "$group": {
+                "_id": "$purpose",
+                "calls": { "$sum": 1 },
+                "failures": { "$sum": { "$cond": ["$success", 0, 1] } },
+                "latency_ms": { "$avg": "$latency_ms" },
+                "tokens": { "$sum": { "$add": ["$input_tokens", "$output_tokens"] } },
+                "cost": { "$sum": "$cost" },
+            } },
+            doc! { "$sort": { "calls": -1 } },
+        ])
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .into_iter()
+        .map(|group| {
+            let number = |key: &str| -> f64 {
+                match group.get(key) {
+                    Some(bson::Bson::Int32(value)) => *value as f64,
+                    Some(bson::Bson::Int64(value)) => *value as f64,
+                    Some(bson::Bson::Double(value)) => *value,
+                    _ => 0.0,
+                }
+            };
+            PurposeStats {
+                purpose: group.get_str("_id").unwrap_or("unknown").to_string(),
+                calls: number("calls") as u64,
+                failures: number("failures") as u64,
+                avg_latency_ms: number("latency_ms"),
+                tokens: number("tokens") as u64,
+                cost: number("cost"),
+            }
+        })
+        .collect();
+
+    let recent_failures = calls
+        .find(doc! { "success": false, "createdAt": { "$gte": since } })
+        .sort(doc! { "createdAt": -1 })
+        .limit(RECENT_FAILURES)
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .map(|call| FailedCall {
+            user: call.user,
+            purpose: call.purpose,
+            model: call.model,
+            error: call.error.unwrap_or_default(),
+            created_at: call.created_at,
+        })
+        .collect();
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: GenerationStats {
+            since,
+            total_calls: purposes.iter().map(|purpose| purpose.calls).sum(),
+            failed_calls: purposes.iter().map(|purpose| purpose.failures).sum(),
+            total_tokens: purposes.iter().map(|purpose| purpose.tokens).sum(),
+            total_cost: purposes.iter().map(|purpose| purpose.cost).sum(),
+            purposes,
+            recent_failures,
+        },
+    })
+}
+
+#[server]
+pub async fn admin_audit_log(
+    token: String,
+) -> Result<SuccessResponse<Vec<AuditEntry>>, ServerFnError> {
+    require_admin(token).await?;
+
+    let entries = get_db()
+        .await
+        .collection::<AuditEntry>("admin_audit_log")
+        .find(doc! {})
+        .sort(doc! { "createdAt": -1 })
+        .limit(AUDIT_LOG_LIMIT)
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: entries,
+    })
+}
@@ -0,0 +1,48 @@
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    ViewOverview,
+    ViewStats,
+    ListUsers,
+    ViewUser,
+    SuspendUser,
+    UnsuspendUser,
+    ChangeRole,
+    DeleteUser,
+    CancelSubscription,
+}
+
+impl AdminAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AdminAction::ViewOverview => "Viewed overview",
+            AdminAction::ViewStats => "Viewed generation stats",
+            AdminAction::ListUsers => "Listed users",
+            AdminAction::ViewUser => "Viewed user",
+            AdminAction::SuspendUser => "Suspended user",
+            AdminAction::UnsuspendUser => "Unsuspended user",
+            AdminAction::ChangeRole => "Changed role",
+            AdminAction::DeleteUser => "Deleted user",
+            AdminAction::CancelSubscription => "Canceled subscription",
+        }
+    }
+}
+
+/// One admin action, as recorded in the `admin_audit_log` collection.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AuditEntry {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub admin: ObjectId,
+    pub admin_email: String,
+    pub action: AdminAction,
+    pub target: Option<ObjectId>,
+    /// Free-form context such as the search query or the new role.
+    pub details: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminListUsersRequest {
+    pub token: String,
+    /// Matched against name and email; empty lists everyone.
+    pub query: String,
+    pub page: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminUserRequest {
+    pub token: String,
+    pub user_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminSetSuspendedRequest {
+    pub token: String,
+    pub user_id: String,
+    pub suspended: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminSetRoleRequest {
+    pub token: String,
+    pub user_id: String,
+    pub role: String,
+}
@@ -0,0 +1,88 @@
+use crate::server::auth::model::User;
+use crate::server::plan::model::{Plan, Usage};
+use crate::server::trip::model::Trip;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DashboardResponse {
+    pub users: u64,
+    pub trips: u64,
+    pub paid_users: u64,
+    pub suspended_users: u64,
+    pub admins: u64,
+}
+
+/// What the admin console shows about a user; leaves out credentials.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AdminUserSummary {
+    pub id: ObjectId,
+    pub name: String,
+    pub email: String,
+    pub role: String,
+    pub plan: Plan,
+    pub verified: bool,
+    pub suspended: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<User> for AdminUserSummary {
+    fn from(user: User) -> Self {
+        AdminUserSummary {
+            id: user.id,
+            name: user.name,
+            email: user.email,
+            role: user.role,
+            plan: user.plan,
+            verified: user.verified,
+            suspended: user.suspended,
+            created_at: user.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct UserListResponse {
+    pub users: Vec<AdminUserSummary>,
+    pub total: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AdminUserDetail {
+    pub user: AdminUserSummary,
+    pub trips: Vec<Trip>,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GenerationStats {
+    pub since: DateTime<Utc>,
+    pub total_calls: u64,
+    pub failed_calls: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub purposes: Vec<PurposeStats>,
+    pub recent_failures: Vec<FailedCall>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PurposeStats {
+    pub purpose: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub avg_latency_ms: f64,
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FailedCall {
+    pub user: Option<ObjectId>,
+    pub purpose: String,
+    pub model: String,
+    pub error: String,
+    pub created_at: DateTime<Utc>,
+}
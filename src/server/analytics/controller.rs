@@ -0,0 +1,272 @@
+use bson::doc;
+use dioxus::prelude::*;
+
+use crate::server::analytics::request::AnalyticsRequest;
+use crate::server::analytics::response::AnalyticsData;
+use crate::server::common::response::SuccessResponse;
+
+#[cfg(feature = "server")]
+use {
+    crate::db::get_client,
+    crate::llm::LlmCall,
+    crate::server::analytics::response::{
+        AIUsageStats, EngagementStats, PredictiveStats, SeriesPoint,
+    },
+    crate::server::auth::controller::auth,
+    crate::server::conversation::model::{Conversation, Message},
+    crate::server::trip::model::{Detail, Trip},
+    bson::Document,
+    chrono::prelude::*,
+    futures_util::TryStreamExt,
+    std::collections::HashMap,
+};
+
+/// Reads a numeric aggregation result regardless of the BSON type MongoDB picked for it.
+#[cfg(feature = "server")]
+fn number(document: &Document, key: &str) -> f64 {
+    match document.get(key) {
+        Some(bson::Bson::Int32(value)) => *value as f64,
+        Some(bson::Bson::Int64(value)) => *value as f64,
+        Some(bson::Bson::Double(value)) => *value,
+        _ => 0.0,
+    }
+}
+
+/// Groups aggregation results by their `_id` bucket key (`%Y-%m-%d`).
+#[cfg(feature = "server")]
+fn by_period(documents: Vec<Document>) -> HashMap<NaiveDate, Document> {
+    documents
+        .into_iter()
+        .filter_map(|document| {
+            let period =
+                NaiveDate::parse_from_str(document.get_str("_id").ok()?, "%Y-%m-%d").ok()?;
+            Some((period, document))
+        })
+        .collect()
+}
+
+#[server]
+pub async fn fetch_analytics_data(
+    req: AnalyticsRequest,
+) -> Result<SuccessResponse<AnalyticsData>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let trips_collection = db.collection::<Trip>("trips");
+
+    // Days are counted in the user's timezone, both for the buckets and for where today is.
+    let timezone = user
+        .preferences
+        .timezone
+        .parse::<chrono_tz::Tz>()
+        .unwrap_or(chrono_tz::UTC);
+    let periods = req
+        .range
+        .periods(Utc::now().with_timezone(&timezone).date_naive());
+    let start = periods
+        .first()
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .and_then(|start| timezone.from_local_datetime(&start).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    let bucket_by = |date: bson::Bson| {
+        doc! { "$dateToString": {
+            "format": req.range.granularity().bucket_format(),
+            "date": date,
+            "timezone": timezone.name(),
+        } }
+    };
+    let bucket = bucket_by("$createdAt".into());
+
+    // Trips created and completed per bucket. `Trip::completed` is stored as `mainTopic`.
+    let trip_buckets = by_period(
+        trips_collection
+            .aggregate(vec![
+                doc! { "$match": { "user": user.id, "createdAt": { "$gte": start } } },
+                doc! { "$group": {
+                    "_id": bucket.clone(),
+                    "trips": { "$sum": 1 },
+                    "completed": { "$sum": { "$cond": ["$mainTopic", 1, 0] } },
+                } },
+            ])
+            .await?
+            .try_collect()
+            .await?,
+    );
+
+    // Generated details and chat answers per bucket, counted from the records they left
+    // behind. Details are created with the outline and carry a prompt once generated; ones
+    // generated before `generatedAt` was stored fall back to their creation time.
+    let user_trips = trips_collection
+        .distinct("_id", doc! { "user": user.id })
+        .await?;
+    let detail_buckets = by_period(
+        db.collection::<Detail>("details")
+            .aggregate(vec![
+                doc! { "$match": {
+                    "trip_id": { "$in": user_trips },
+                    "prompt": { "$ne": null },
+                    "$or": [
+                        { "generatedAt": { "$gte": start } },
+                        { "generatedAt": null, "createdAt": { "$gte": start } },
+                    ],
+                } },
+                doc! { "$group": {
+                    "_id": bucket_by(doc! { "$ifNull": ["$generatedAt", "$createdAt"] }.into()),
+                    "details": { "$sum": 1 },
+                } },
+            ])
+            .await?
+            .try_collect()
+            .await?,
+    );
+    let user_conversations = db
+        .collection::<Conversation>("conversations")
+        .distinct("_id", doc! { "user": user.id })
+        .await?;
+    let message_buckets = by_period(
+        db.collection::<Message>("messages")
+            .aggregate(vec![
+                doc! { "$match": {
+                    "conversation": { "$in": user_conversations },
+                    "sender": "bedrock",
+                    "timestamp": { "$gte": start },
+                } },
+                doc! { "$group": {
+                    "_id": bucket_by("$timestamp".into()),
+                    "chat_messages": { "$sum": 1 },
+                } },
+            ])
+            .await?
+            .try_collect()
+            .await?,
+    );
+
+    // Model calls per bucket.
+    let call_buckets = by_period(
+        db.collection::<LlmCall>("llm_calls")
+            .aggregate(vec![
+                doc! { "$match": { "user": user.id, "createdAt": { "$gte": start } } },
+                doc! { "$group": {
+                    "_id": bucket,
+                    "calls": { "$sum": 1 },
+                    "successes": { "$sum": { "$cond": ["$success", 1, 0] } },
+                    "latency_ms": { "$sum": { "$cond": ["$success", "$latency_ms", 0] } },
+                    "input_tokens": { "$sum": "$input_tokens" },
+                    "output_tokens": { "$sum": "$output_tokens" },
+                    "cost": { "$sum": "$cost" },
+                } },
+            ])
+            .await?
+            .try_collect()
+            .await?,
+    );
+
+    let empty = Document::new();
+    let series: Vec<SeriesPoint> = periods
+        .iter()
+        .map(|period| {
+            let trips = trip_buckets.get(period).unwrap_or(&empty);
+            let details = detail_buckets.get(period).unwrap_or(&empty);
+            let messages = message_buckets.get(period).unwrap_or(&empty);
+            let calls = call_buckets.get(period).unwrap_or(&empty);
+            let created = number(trips, "trips");
+            SeriesPoint {
+                period: *period,
+                trips_created: created as u64,
+                details_generated: number(details, "details") as u64,
+                chat_messages: number(messages, "chat_messages") as u64,
+                tokens: (number(calls, "input_tokens") + number(calls, "output_tokens")) as u64,
+                completion_rate: if created > 0.0 {
+                    number(trips, "completed") / created * 100.0
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+
+    let total = |documents: &HashMap<NaiveDate, Document>, key: &str| -> f64 {
+        documents
+            .values()
+            .map(|document| number(document, key))
+            .sum()
+    };
+
+    // Engagement Metrics
+    let trip_ids: Vec<_> = trips_collection
+        .find(doc! { "user": user.id, "createdAt": { "$gte": start } })
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .map(|trip| trip.id)
+        .collect();
+    let total_trips = trip_ids.len() as u64;
+    let total_details = db
+        .collection::<Detail>("details")
+        .count_documents(doc! { "trip_id": { "$in": trip_ids } })
+        .await?;
+    let ratio = |part: f64, whole: f64| if whole > 0.0 { part / whole } else { 0.0 };
+
+    // AI Usage Metrics
+    let total_calls = total(&call_buckets, "calls");
+    let successes = total(&call_buckets, "successes");
+
+    // Trending Topic
+    let trending_topic = trips_collection
+        .aggregate(vec![
+            doc! { "$match": { "user": user.id, "createdAt": { "$gte": start } } },
+            doc! { "$group": { "_id": "$title", "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1 } },
+            doc! { "$limit": 1 },
+        ])
+        .await?
+        .try_next()
+        .await?
+        .and_then(|doc| doc.get_str("_id").ok().map(|s| s.to_string()))
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    // Projected Growth: change in trips created between the last two buckets.
+    let projected_growth = match series.as_slice() {
+        [.., previous, current] if previous.trips_created > 0 => {
+            (current.trips_created as f64 - previous.trips_created as f64)
+                / previous.trips_created as f64
+                * 100.0
+        }
+        _ => 0.0,
+    };
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: AnalyticsData {
+            range: req.range,
+            engagement: EngagementStats {
+                total_trips,
+                total_details,
+                avg_details_per_trip: ratio(total_details as f64, total_trips as f64),
+                completion_rate: ratio(total(&trip_buckets, "completed"), total_trips as f64)
+                    * 100.0,
+            },
+            ai_usage: AIUsageStats {
+                total_ai_details: total(&detail_buckets, "details") as u64,
+                avg_gen_time: ratio(total(&call_buckets, "latency_ms"), successes) / 1000.0,
+                success_rate: ratio(successes, total_calls) * 100.0,
+                total_calls: total_calls as u64,
+                chat_messages: total(&message_buckets, "chat_messages") as u64,
+                input_tokens: total(&call_buckets, "input_tokens") as u64,
+                output_tokens: total(&call_buckets, "output_tokens") as u64,
+                total_cost: total(&call_buckets, "cost"),
+            },
+            predictions: PredictiveStats {
+                trending_genre: trending_topic,
+                projected_growth,
+            },
+            series,
+        },
+    })
+}
@@ -0,0 +1,87 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Time window shown on the analytics page.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsRange {
+    Last7Days,
+    #[default]
+    Last30Days,
+    Last90Days,
+    Last12Months,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Day,
+    Month,
+}
+
+impl AnalyticsRange {
+    pub const ALL: [AnalyticsRange; 4] = [
+        AnalyticsRange::Last7Days,
+        AnalyticsRange::Last30Days,
+        AnalyticsRange::Last90Days,
+        AnalyticsRange::Last12Months,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnalyticsRange::Last7Days => "7 days",
+            AnalyticsRange::Last30Days => "30 days",
+            AnalyticsRange::Last90Days => "90 days",
+            AnalyticsRange::Last12Months => "12 months",
+        }
+    }
+
+    pub fn granularity(&self) -> Granularity {
+        match self {
+            AnalyticsRange::Last12Months => Granularity::Month,
+            _ => Granularity::Day,
+        }
+    }
+
+    /// First day of every bucket in the range ending on `today`, oldest first.
+    #[cfg(feature = "server")]
+    pub fn periods(&self, today: NaiveDate) -> Vec<NaiveDate> {
+        match self {
+            AnalyticsRange::Last12Months => {
+                let month = today.with_day(1).unwrap_or(today);
+                (0..12)
+                    .rev()
+                    .filter_map(|i| month.checked_sub_months(chrono::Months::new(i)))
+                    .collect()
+            }
+            _ => {
+                let days = match self {
+                    AnalyticsRange::Last7Days => 7,
+                    AnalyticsRange::Last90Days => 90,
+                    _ => 30,
+                };
+                (0..days)
+                    .rev()
+                    .filter_map(|i| today.checked_sub_days(chrono::Days::new(i)))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Granularity {
+    /// `$dateToString` format producing the bucket's first day as `%Y-%m-%d`.
+    #[cfg(feature = "server")]
+    pub fn bucket_format(&self) -> &'static str {
+        match self {
+            Granularity::Day => "%Y-%m-%d",
+            Granularity::Month => "%Y-%m-01",
+        }
+    }
+
+    /// Short axis label for a bucket, e.g. `Mar 14` or `Mar 2025`.
+    pub fn label(&self, period: NaiveDate) -> String {
+        match self {
+            Granularity::Day => period.format("%b %-d").to_string(),
+            Granularity::Month => period.format("%b %Y").to_string(),
+        }
+    }
+}
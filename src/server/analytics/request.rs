@@ -0,0 +1,8 @@
+use crate::server::analytics::model::AnalyticsRange;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalyticsRequest {
+    pub token: String,
+    pub range: AnalyticsRange,
+}
@@ -0,0 +1,57 @@
+use crate::server::analytics::model::{AnalyticsRange, Granularity};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AnalyticsData {
+    pub range: AnalyticsRange,
+    pub engagement: EngagementStats,
+    pub ai_usage: AIUsageStats,
+    pub predictions: PredictiveStats,
+    /// One point per day or month of the range, oldest first, with empty buckets included.
+    pub series: Vec<SeriesPoint>,
+}
+
+impl AnalyticsData {
+    pub fn granularity(&self) -> Granularity {
+        self.range.granularity()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct EngagementStats {
+    pub total_trips: u64,
+    pub total_details: u64,
+    pub avg_details_per_trip: f64,
+    pub completion_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AIUsageStats {
+    pub total_ai_details: u64,
+    pub avg_gen_time: f64,
+    pub success_rate: f64,
+    pub total_calls: u64,
+    pub chat_messages: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_cost: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PredictiveStats {
+    pub trending_genre: String,
+    pub projected_growth: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SeriesPoint {
+    /// First day of the bucket.
+    pub period: NaiveDate,
+    pub trips_created: u64,
+    pub details_generated: u64,
+    pub chat_messages: u64,
+    pub tokens: u64,
+    /// Share of the trips created in the bucket that are completed, from 0 to 100.
+    pub completion_rate: f64,
+}
@@ -8,18 +8,55 @@ use chrono::prelude::*;
 use chrono::Duration;
 use dioxus::prelude::*;
 
-use crate::server::auth::model::{TokenClaims, User};
+use crate::currency::find_currency;
+use crate::server::auth::model::{TokenClaims, TwoFactor, User, UserPreferences, ROLE_USER};
 use crate::server::auth::response::{
-    AuthResponse, DashboardResponse, LoginUserSchema, RegisterUserSchema, UserResponse,
+    AuthResponse, ChangePasswordSchema, LoginUserSchema, OidcLoginSchema, OidcProviderInfo,
+    RegisterUserSchema, ResetPasswordSchema, RevokeSessionSchema, SessionInfo, TwoFactorCodeSchema,
+    TwoFactorLoginSchema, TwoFactorSetup, UpdatePreferencesSchema, UpdateProfileSchema,
+    UploadAvatarSchema, UserResponse,
 };
 use crate::server::common::response::SuccessResponse;
-use crate::server::trip::model::Trip;
+use crate::server::plan::model::Plan;
+
+/// Limits on uploaded avatars, checked before the image is decoded.
+#[cfg(feature = "server")]
+const MAX_AVATAR_BYTES: usize = 512 * 1024;
+#[cfg(feature = "server")]
+const MAX_AVATAR_SIZE: u32 = 1024;
+/// Avatars are stored as JPEGs this many pixels square.
+#[cfg(feature = "server")]
+const AVATAR_SIZE: u32 = 256;
+#[cfg(feature = "server")]
+const MAX_PREFERENCE_LENGTH: usize = 100;
+#[cfg(feature = "server")]
+const MAX_INTERESTS: usize = 10;
 
 #[cfg(feature = "server")]
 use {
     crate::db::get_client,
+    crate::oidc::{authorization_url, complete_login, providers},
+    crate::security::{clear_failures, guard, Action},
+    crate::storage::{
+        get_storage, inspect_image, key_from_url, square_jpeg, Storage, StoredObject, MEDIA_PREFIX,
+    },
+    crate::tokens::{
+        consume_password_reset, create_password_reset, decode_claims, is_revoked, random_hex,
+        revoke_sessions, rotate_session, start_session, Session,
+    },
+    crate::two_factor::{
+        challenge_account, check_enrollment_code, complete_login_challenge, consume_code,
+        create_login_challenge, generate_backup_codes, generate_secret, otpauth_uri, qr_code_svg,
+    },
+    crate::verification::{
+        decode_email_token, send_email_change_email, send_password_reset_email,
+        send_verification_email, EmailTokenPurpose,
+    },
     argon2::{password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier},
     axum_extra::extract::cookie::{Cookie, SameSite},
+    base64::{engine::general_purpose::STANDARD as BASE64, Engine},
+    dioxus_logger::tracing,
+    futures_util::TryStreamExt,
     jsonwebtoken::{encode, DecodingKey, EncodingKey, Header, Validation},
     rand_core::OsRng,
 };
@@ -27,6 +64,14 @@ use {
 #[server]
 pub async fn register_user(
     body: RegisterUserSchema,
+) -> Result<SuccessResponse<UserResponse>, ServerFnError> {
+    let account = body.email.clone();
+    guard(Action::Register, &account, register(body)).await
+}
+
+#[cfg(feature = "server")]
+async fn register(
+    body: RegisterUserSchema,
 ) -> Result<SuccessResponse<UserResponse>, ServerFnError> {
     let client = get_client().await;
     let db =
@@ -55,17 +100,95 @@ pub async fn register_user(
         name: body.name,
         email: body.email.to_lowercase(),
         password: hashed_password,
-        role: "user".into(),
+        role: ROLE_USER.into(),
+        plan: Plan::Free,
         photo: "".into(),
         verified: false,
+        suspended: false,
+        preferences: UserPreferences::default(),
+        identities: Vec::new(),
+        two_factor: None,
+        pending_email: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
     user_collection.insert_one(new_user.clone()).await?;
 
+    // The account is usable without verification, and the link can be resent later.
+    if let Err(e) = send_verification_email(&new_user).await {
+        tracing::error!("Verification email to {}: {}", new_user.email, e);
+    }
+
     Ok(SuccessResponse {
         status: "success".into(),
-        data: UserResponse { user: new_user },
+        data: UserResponse {
+            user: new_user.redacted(),
+        },
+    })
+}
+
+/// Marks the account a verification link was sent for as verified.
+#[server]
+pub async fn verify_email(token: String) -> Result<SuccessResponse<String>, ServerFnError> {
+    let claims = decode_email_token(&token, EmailTokenPurpose::VerifyEmail)?;
+    let user_id = ObjectId::parse_str(&claims.sub)
+        .map_err(|_| ServerFnError::new("This link is invalid or has expired"))?;
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let users = db.collection::<User>("users");
+    let result = users
+        .update_one(
+            doc! { "_id": user_id, "email": &claims.email },
+            doc! { "$set": { "verified": true, "updatedAt": Utc::now() } },
+        )
+        .await?;
+    if result.matched_count == 0 {
+        // A link sent to a requested new address moves the account there.
+        if users
+            .find_one(doc! { "email": &claims.email })
+            .await?
+            .is_some()
+        {
+            return Err(ServerFnError::new("User with that email already exists"));
+        }
+        let result = users
+            .update_one(
+                doc! { "_id": user_id, "pendingEmail": &claims.email },
+                doc! {
+                    "$set": { "email": &claims.email, "verified": true, "updatedAt": Utc::now() },
+                    "$unset": { "pendingEmail": "" },
+                },
+            )
+            .await?;
+        if result.matched_count == 0 {
+            return Err(ServerFnError::new("This link is invalid or has expired"));
+        }
+    }
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: claims.email,
+    })
+}
+
+#[server]
+pub async fn resend_verification_email(
+    token: String,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    if user.verified {
+        return Err(ServerFnError::new("Email address already verified"));
+    }
+
+    send_verification_email(&user).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: user.email,
     })
 }
 
@@ -73,6 +196,17 @@ pub async fn register_user(
 pub async fn login_user(
     body: LoginUserSchema,
 ) -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
+    let account = body.email.clone();
+    let response = guard(Action::Login, &account, login(body)).await?;
+    // With 2FA the sign-in is only complete once the second step succeeds.
+    if response.data.two_factor_challenge.is_none() {
+        clear_failures(Action::Login, &account).await?;
+    }
+    Ok(response)
+}
+
+#[cfg(feature = "server")]
+async fn login(body: LoginUserSchema) -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
@@ -84,6 +218,11 @@ pub async fn login_user(
         .await?
         .ok_or(ServerFnError::new("Invalid email or password"))?;
 
+    // Accounts created through a sign-in provider have no password
+    if user.password.is_empty() {
+        return Err(ServerFnError::new("Invalid email or password"));
+    }
+
     // Verify the password
     let parsed_hash = PasswordHash::new(&user.password)
         .map_err(|_| ServerFnError::new("Password verification error"))?;
@@ -93,38 +232,167 @@ pub async fn login_user(
     {
         return Err(ServerFnError::new("Invalid email or password"));
     }
+    if user.suspended {
+        return Err(ServerFnError::new("Account suspended"));
+    }
 
-    // Generate a JWT token
-    let now = Utc::now();
-    let claims = TokenClaims {
-        sub: user.id.to_hex(),
-        iat: now.timestamp() as usize,
-        exp: (now + Duration::minutes(60)).timestamp() as usize,
-    };
+    let data = finish_login(&db, &user).await?;
 
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(jwt_secret.as_ref()),
-    )?;
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data,
+    })
+}
 
-    let cookie = Cookie::build(("token", token.to_owned()))
-        .path("/")
-        .max_age(time::Duration::hours(1).into())
-        .same_site(SameSite::Lax)
-        .http_only(true);
+/// Emails a reset link if `email` belongs to an account. Always reports success so the
+/// response does not reveal which addresses are registered.
+#[server]
+pub async fn request_password_reset(
+    email: String,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let account = email.clone();
+    guard(Action::PasswordReset, &account, send_password_reset(email)).await
+}
+
+#[cfg(feature = "server")]
+async fn send_password_reset(email: String) -> Result<SuccessResponse<String>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let user = db
+        .collection::<User>("users")
+        .find_one(doc! { "email": email.trim().to_lowercase() })
+        .await?;
+    if let Some(user) = user.filter(|user| !user.suspended) {
+        let token = create_password_reset(&db, &user).await?;
+        if let Err(e) = send_password_reset_email(&user, &token).await {
+            tracing::error!("Password reset email to {}: {}", user.email, e);
+        }
+    }
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: AuthResponse {
-            token: cookie.to_string().parse().unwrap(),
-        },
+        data: "If an account exists for that address, a reset link is on its way.".into(),
+    })
+}
+
+/// Sets a new password from a reset link and signs the account out everywhere.
+#[server]
+pub async fn reset_password(
+    body: ResetPasswordSchema,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    if body.password.is_empty() {
+        return Err(ServerFnError::new("Password can't be blank"));
+    }
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let user_id = consume_password_reset(&db, &body.token).await?;
+
+    let salt = SaltString::generate(&mut OsRng);
+    let hashed_password = Argon2::default()
+        .hash_password(body.password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| ServerFnError::new("Error while hashing password"))?;
+
+    // Following the emailed link also proves the address belongs to the user.
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user_id },
+            doc! { "$set": {
+                "password": hashed_password,
+                "verified": true,
+                "updatedAt": Utc::now(),
+            } },
+        )
+        .await?;
+    revoke_sessions(&db, doc! { "user": user_id }).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Password updated".into(),
+    })
+}
+
+#[server]
+pub async fn oidc_providers() -> Result<SuccessResponse<Vec<OidcProviderInfo>>, ServerFnError> {
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: providers()
+            .await
+            .iter()
+            .map(|provider| OidcProviderInfo {
+                id: provider.id.clone(),
+                name: provider.name.clone(),
+            })
+            .collect(),
+    })
+}
+
+/// Starts signing in with `provider` and returns the URL to send the browser to.
+#[server]
+pub async fn oidc_authorize(provider: String) -> Result<SuccessResponse<String>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let url = authorization_url(&db, &provider).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: url,
+    })
+}
+
+/// Finishes signing in with a provider, linking or creating the account, and opens a session.
+#[server]
+pub async fn oidc_login(
+    body: OidcLoginSchema,
+) -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let user = complete_login(&db, &body.provider, &body.query).await?;
+    if user.suspended {
+        return Err(ServerFnError::new("Account suspended"));
+    }
+    let data = finish_login(&db, &user).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data,
     })
 }
 
 #[server]
-async fn logout() -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
+pub async fn refresh_session(
+    refresh_token: String,
+) -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let data = rotate_session(&db, &refresh_token).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data,
+    })
+}
+
+#[server]
+pub async fn logout(token: String) -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
+    let (_, claims) = authenticate(&token).await?;
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    if let Ok(session_id) = ObjectId::parse_str(&claims.sid) {
+        revoke_sessions(&db, doc! { "_id": session_id }).await?;
+    }
+
     let cookie = Cookie::build(("token", ""))
         .path("/")
         .max_age(time::Duration::hours(-1))
@@ -135,109 +403,710 @@ async fn logout() -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
         status: "success".into(),
         data: AuthResponse {
             token: cookie.to_string().parse().unwrap(),
+            refresh_token: String::new(),
+            two_factor_challenge: None,
         },
     })
 }
 
 #[server]
-pub async fn about_me(token: String) -> Result<SuccessResponse<UserResponse>, ServerFnError> {
+pub async fn list_sessions(
+    token: String,
+) -> Result<SuccessResponse<Vec<SessionInfo>>, ServerFnError> {
+    let (user, claims) = authenticate(&token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
-    let user_collection = db.collection::<User>("users");
-
-    let claims = jsonwebtoken::decode::<TokenClaims>(
-        &token,
-        &DecodingKey::from_secret(
-            env::var("JWT_SECRET")
-                .expect("JWT_SECRET must be set")
-                .as_ref(),
-        ),
-        &Validation::default(),
-    )
-    .map_err(|_| ServerFnError::new("Invalid token"))?;
 
-    let user_id = ObjectId::from_str(&claims.claims.sub)
-        .map_err(|_| ServerFnError::new("Invalid user ID"))?;
-    let user = user_collection
-        .find_one(doc! { "_id": user_id })
+    let sessions: Vec<Session> = db
+        .collection::<Session>("sessions")
+        .find(doc! { "user": user.id, "revokedAt": null, "expiresAt": { "$gt": Utc::now() } })
+        .sort(doc! { "lastUsedAt": -1 })
         .await?
-        .ok_or(ServerFnError::new("User not found"))?;
+        .try_collect()
+        .await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: UserResponse { user },
+        data: sessions
+            .into_iter()
+            .map(|session| SessionInfo {
+                id: session.id.to_hex(),
+                current: session.id.to_hex() == claims.sid,
+                device: session.device,
+                user_agent: session.user_agent,
+                created_at: session.created_at,
+                last_used_at: session.last_used_at,
+            })
+            .collect(),
+    })
+}
+
+#[server]
+pub async fn revoke_session(
+    body: RevokeSessionSchema,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let session_id = ObjectId::parse_str(&body.session_id)
+        .map_err(|_| ServerFnError::new("Invalid session ID"))?;
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    if revoke_sessions(&db, doc! { "_id": session_id, "user": user.id }).await? == 0 {
+        return Err(ServerFnError::new("Session not found"));
+    }
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Session revoked".into(),
+    })
+}
+
+/// Signs the caller out on every device, including this one.
+#[server]
+pub async fn revoke_all_sessions(token: String) -> Result<SuccessResponse<u64>, ServerFnError> {
+    let user = auth(token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let revoked = revoke_sessions(&db, doc! { "user": user.id }).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: revoked,
+    })
+}
+
+#[server]
+pub async fn about_me(token: String) -> Result<SuccessResponse<UserResponse>, ServerFnError> {
+    let (user, _) = authenticate(&token).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: UserResponse {
+            user: user.redacted(),
+        },
     })
 }
 
 #[server]
 pub async fn auth(token: String) -> Result<User, ServerFnError> {
+    let (user, _) = authenticate(&token).await?;
+
+    Ok(user.redacted())
+}
+
+/// Opens a session, or asks for a second factor first when the account has 2FA enabled.
+#[cfg(feature = "server")]
+async fn finish_login(db: &mongodb::Database, user: &User) -> Result<AuthResponse, ServerFnError> {
+    if user.two_factor_enabled() {
+        return Ok(AuthResponse {
+            token: String::new(),
+            refresh_token: String::new(),
+            two_factor_challenge: Some(create_login_challenge(db, user).await?),
+        });
+    }
+    start_session(db, user).await
+}
+
+/// The second sign-in step for accounts with 2FA: accepts a TOTP or backup code.
+#[server]
+pub async fn verify_two_factor_login(
+    body: TwoFactorLoginSchema,
+) -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
-    let user_collection = db.collection::<User>("users");
 
-    let claims = jsonwebtoken::decode::<TokenClaims>(
-        &token,
-        &DecodingKey::from_secret(
-            env::var("JWT_SECRET")
-                .expect("JWT_SECRET must be set")
-                .as_ref(),
-        ),
-        &Validation::default(),
+    // Codes are limited per account as well as per IP, however many challenges are opened.
+    let account = challenge_account(&db, &body.challenge)
+        .await?
+        .unwrap_or_default();
+    let response = guard(Action::TwoFactor, &account, verify_two_factor(&db, body)).await?;
+    clear_failures(Action::Login, &account).await?;
+    Ok(response)
+}
+
+#[cfg(feature = "server")]
+async fn verify_two_factor(
+    db: &mongodb::Database,
+    body: TwoFactorLoginSchema,
+) -> Result<SuccessResponse<AuthResponse>, ServerFnError> {
+    let user = complete_login_challenge(db, &body.challenge, &body.code).await?;
+    if user.suspended {
+        return Err(ServerFnError::new("Account suspended"));
+    }
+    let data = start_session(db, &user).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data,
+    })
+}
+
+/// Starts 2FA enrollment with a new secret. It takes effect once confirmed with a code.
+#[server]
+pub async fn begin_two_factor_setup(
+    token: String,
+) -> Result<SuccessResponse<TwoFactorSetup>, ServerFnError> {
+    let (user, _) = authenticate(&token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    if user.two_factor_enabled() {
+        return Err(ServerFnError::new(
+            "Two-factor authentication is already on",
+        ));
+    }
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let two_factor = TwoFactor {
+        secret: generate_secret(),
+        enabled: false,
+        backup_codes: Vec::new(),
+        last_step: 0,
+    };
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": { "twoFactor": bson::to_bson(&two_factor)?, "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    let uri = otpauth_uri(&two_factor.secret, &user.email);
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: TwoFactorSetup {
+            qr_svg: qr_code_svg(&uri)?,
+            otpauth_uri: uri,
+            secret: two_factor.secret,
+        },
+    })
+}
+
+/// Turns 2FA on once the authenticator app produces a valid code. Returns the backup codes,
+/// which are not shown again.
+#[server]
+pub async fn confirm_two_factor_setup(
+    body: TwoFactorCodeSchema,
+) -> Result<SuccessResponse<Vec<String>>, ServerFnError> {
+    let (user, _) = authenticate(&body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let two_factor = user
+        .two_factor
+        .as_ref()
+        .filter(|tf| !tf.enabled)
+        .ok_or(ServerFnError::new("Start two-factor setup first"))?;
+    let step = check_enrollment_code(two_factor, &body.code)?;
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let (codes, hashes) = generate_backup_codes();
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id, "twoFactor.secret": &two_factor.secret },
+            doc! { "$set": {
+                "twoFactor.enabled": true,
+                "twoFactor.backupCodes": hashes,
+                "twoFactor.lastStep": step as i64,
+                "updatedAt": Utc::now(),
+            } },
+        )
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: codes,
+    })
+}
+
+/// Replaces the backup codes after checking a current code.
+#[server]
+pub async fn regenerate_backup_codes(
+    body: TwoFactorCodeSchema,
+) -> Result<SuccessResponse<Vec<String>>, ServerFnError> {
+    let (user, _) = authenticate(&body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    // A stolen access token must not give unlimited guesses at the code.
+    guard(
+        Action::TwoFactor,
+        &user.email,
+        consume_code(&db, &user, &body.code),
     )
-    .map_err(|_| ServerFnError::new("Invalid token"))?;
+    .await?;
+    let (codes, hashes) = generate_backup_codes();
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": { "twoFactor.backupCodes": hashes, "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: codes,
+    })
+}
+
+/// Turns 2FA off after checking a current TOTP or backup code.
+#[server]
+pub async fn disable_two_factor(
+    body: TwoFactorCodeSchema,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let (user, _) = authenticate(&body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    guard(
+        Action::TwoFactor,
+        &user.email,
+        consume_code(&db, &user, &body.code),
+    )
+    .await?;
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$unset": { "twoFactor": "" }, "$set": { "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Two-factor authentication turned off".into(),
+    })
+}
+
+/// Verifies `token`, refuses denylisted tokens, tokens of revoked sessions and suspended
+/// accounts, and loads the user.
+#[cfg(feature = "server")]
+async fn authenticate(token: &str) -> Result<(User, TokenClaims), ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let user_collection = db.collection::<User>("users");
+
+    let claims = decode_claims(token)?;
+    if claims.jti.is_empty() || claims.sid.is_empty() {
+        return Err(ServerFnError::new("Invalid token"));
+    }
+    if is_revoked(&db, &claims.jti).await? {
+        return Err(ServerFnError::new("Token revoked"));
+    }
+
+    let user_id =
+        ObjectId::from_str(&claims.sub).map_err(|_| ServerFnError::new("Invalid user ID"))?;
+    // The denylist only holds each session's latest access token, so the session itself
+    // decides whether older tokens issued for it are still good.
+    let session_id =
+        ObjectId::from_str(&claims.sid).map_err(|_| ServerFnError::new("Invalid token"))?;
+    db.collection::<Session>("sessions")
+        .find_one(doc! { "_id": session_id, "user": user_id, "revokedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Session revoked"))?;
 
-    let user_id = ObjectId::from_str(&claims.claims.sub)
-        .map_err(|_| ServerFnError::new("Invalid user ID"))?;
     let user = user_collection
         .find_one(doc! { "_id": user_id })
         .await?
         .ok_or(ServerFnError::new("User not found"))?;
+    if user.suspended {
+        return Err(ServerFnError::new("Account suspended"));
+    }
 
+    Ok((user, claims))
+}
+
+/// Authenticates `token` and checks that it belongs to an admin.
+#[cfg(feature = "server")]
+pub async fn require_admin(token: String) -> Result<User, ServerFnError> {
+    let user = auth(token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    if !user.is_admin() {
+        return Err(ServerFnError::new("Not Authorized"));
+    }
     Ok(user)
 }
 
 #[server]
-pub async fn get_user_info(user_id: ObjectId) -> Result<SuccessResponse<User>, ServerFnError> {
+pub async fn update_preferences(
+    body: UpdatePreferencesSchema,
+) -> Result<SuccessResponse<UserPreferences>, ServerFnError> {
+    let user = auth(body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let mut preferences = body.preferences;
+    preferences.home_currency = find_currency(&preferences.home_currency)
+        .ok_or(ServerFnError::new("Unsupported home currency"))?
+        .code
+        .to_string();
+
+    // These are copied into prompts, so keep them short.
+    for (label, value) in [
+        ("Home city", &mut preferences.home_city),
+        ("Language", &mut preferences.language),
+        ("Dietary needs", &mut preferences.dietary_needs),
+        ("Mobility constraints", &mut preferences.mobility),
+    ] {
+        *value = value.trim().to_string();
+        if value.chars().count() > MAX_PREFERENCE_LENGTH {
+            return Err(ServerFnError::new(format!(
+                "{} must be at most {} characters",
+                label, MAX_PREFERENCE_LENGTH
+            )));
+        }
+    }
+    if preferences.language.is_empty() {
+        preferences.language = UserPreferences::default().language;
+    }
+    preferences.timezone = match preferences.timezone.trim() {
+        "" => UserPreferences::default().timezone,
+        tz => tz
+            .parse::<chrono_tz::Tz>()
+            .map_err(|_| ServerFnError::new("Invalid IANA timezone"))?
+            .name()
+            .to_string(),
+    };
+    let mut interests: Vec<String> = Vec::new();
+    for interest in &preferences.interests {
+        let interest = interest.trim();
+        if !interest.is_empty() && !interests.iter().any(|i| i.eq_ignore_ascii_case(interest)) {
+            interests.push(interest.chars().take(MAX_PREFERENCE_LENGTH).collect());
+        }
+    }
+    if interests.len() > MAX_INTERESTS {
+        return Err(ServerFnError::new(format!(
+            "Pick at most {} interests",
+            MAX_INTERESTS
+        )));
+    }
+    preferences.interests = interests;
+
     let client = get_client().await;
     let db =
-        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set"));
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let user_collection = db.collection::<User>("users");
 
-    let filter = doc! { "_id": user_id };
-    let user = user_collection
-        .find_one(filter)
-        .await
-        .map_err(|_| ServerFnError::new("Error fetching user data"))?
-        .ok_or(ServerFnError::new("User not found"))?;
+    user_collection
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": {
+                "preferences": bson::to_bson(&preferences)?,
+                "updatedAt": Utc::now(),
+            } },
+        )
+        .await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: user,
+        data: preferences,
     })
 }
 
+/// Updates the caller's name and email. A new email address needs the current password and
+/// only replaces the old one once its verification link is opened.
 #[server]
-pub async fn dashboard_overview() -> Result<SuccessResponse<DashboardResponse>, ServerFnError> {
+pub async fn update_profile(
+    body: UpdateProfileSchema,
+) -> Result<SuccessResponse<UserResponse>, ServerFnError> {
+    let (user, _) = authenticate(&body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let name = body.name.trim().to_string();
+    let email = body.email.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(ServerFnError::new("Name can't be blank"));
+    }
+    if !email.contains('@') || email.contains(char::is_whitespace) {
+        return Err(ServerFnError::new("Enter a valid email address"));
+    }
+
+    if email == user.email {
+        return save_profile(user, name, None).await;
+    }
+    let account = user.email.clone();
+    guard(
+        Action::PasswordChange,
+        &account,
+        change_email(user, name, email, body.current_password),
+    )
+    .await
+}
+
+#[cfg(feature = "server")]
+async fn change_email(
+    user: User,
+    name: String,
+    email: String,
+    current_password: String,
+) -> Result<SuccessResponse<UserResponse>, ServerFnError> {
+    check_current_password(&user, &current_password)?;
+
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
-    let user_collection = db.collection::<User>("users");
-    let trip_collection = db.collection::<Trip>("trips");
+    if db
+        .collection::<User>("users")
+        .find_one(doc! { "email": &email })
+        .await?
+        .is_some()
+    {
+        return Err(ServerFnError::new("User with that email already exists"));
+    }
+
+    send_email_change_email(&user, &email).await?;
+    save_profile(user, name, Some(email)).await
+}
 
-    let users = user_collection.estimated_document_count().await?;
-    let trips = trip_collection.estimated_document_count().await?;
-    let paid_users = user_collection
-        .count_documents(doc! { "role": { "$ne": "free" } })
+/// Stores the name and the address waiting for verification, if any.
+#[cfg(feature = "server")]
+async fn save_profile(
+    mut user: User,
+    name: String,
+    pending_email: Option<String>,
+) -> Result<SuccessResponse<UserResponse>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": {
+                "name": &name,
+                "pendingEmail": bson::to_bson(&pending_email)?,
+                "updatedAt": Utc::now(),
+            } },
+        )
         .await?;
 
+    user.name = name;
+    user.pending_email = pending_email;
+
     Ok(SuccessResponse {
         status: "success".into(),
-        data: DashboardResponse {
-            users,
-            trips,
-            paid_users,
+        data: UserResponse {
+            user: user.redacted(),
         },
     })
 }
+
+/// Fails unless `password` is the user's current password.
+#[cfg(feature = "server")]
+fn check_current_password(user: &User, password: &str) -> Result<(), ServerFnError> {
+    // Accounts created through a sign-in provider set their first password from a reset link.
+    if user.password.is_empty() {
+        return Err(ServerFnError::new(
+            "This account has no password yet. Use \"Forgot password?\" to set one.",
+        ));
+    }
+    let parsed_hash = PasswordHash::new(&user.password)
+        .map_err(|_| ServerFnError::new("Password verification error"))?;
+    if Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_err()
+    {
+        return Err(ServerFnError::new("Current password is incorrect"));
+    }
+    Ok(())
+}
+
+/// Changes the caller's password after checking the current one, and signs out every other
+/// session.
+#[server]
+pub async fn change_password(
+    body: ChangePasswordSchema,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let (user, claims) = authenticate(&body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let account = user.email.clone();
+    guard(
+        Action::PasswordChange,
+        &account,
+        update_password(user, claims, body),
+    )
+    .await
+}
+
+#[cfg(feature = "server")]
+async fn update_password(
+    user: User,
+    claims: TokenClaims,
+    body: ChangePasswordSchema,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    check_current_password(&user, &body.current_password)?;
+    if body.new_password.is_empty() {
+        return Err(ServerFnError::new("Password can't be blank"));
+    }
+
+    let salt = SaltString::generate(&mut OsRng);
+    let hashed_password = Argon2::default()
+        .hash_password(body.new_password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| ServerFnError::new("Error while hashing password"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": { "password": hashed_password, "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    let mut others = doc! { "user": user.id };
+    if let Ok(sid) = ObjectId::parse_str(&claims.sid) {
+        others.insert("_id", doc! { "$ne": sid });
+    }
+    revoke_sessions(&db, others).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Password updated".into(),
+    })
+}
+
+/// Stores a new avatar for the caller and returns its URL.
+#[server]
+pub async fn upload_avatar(
+    body: UploadAvatarSchema,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let (user, _) = authenticate(&body.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let bytes = BASE64
+        .decode(body.image.trim())
+        .map_err(|_| ServerFnError::new("Invalid image data"))?;
+    if bytes.len() > MAX_AVATAR_BYTES {
+        return Err(ServerFnError::new("Image is too large"));
+    }
+    let image = inspect_image(&bytes).ok_or(ServerFnError::new("Upload a PNG or JPEG image"))?;
+    if image.width == 0
+        || image.height == 0
+        || image.width > MAX_AVATAR_SIZE
+        || image.height > MAX_AVATAR_SIZE
+    {
+        return Err(ServerFnError::new(format!(
+            "Avatars can be at most {0}x{0} pixels",
+            MAX_AVATAR_SIZE
+        )));
+    }
+
+    // Only what we encode ourselves is stored, never the uploaded bytes.
+    let bytes = tokio::task::spawn_blocking(move || square_jpeg(&bytes, AVATAR_SIZE))
+        .await
+        .map_err(|_| ServerFnError::new("Failed to process the image"))?
+        .map_err(|_| ServerFnError::new("That file could not be read as an image"))?;
+
+    let key = format!("avatars/{}-{}.jpg", user.id.to_hex(), random_hex(8));
+    let storage = get_storage().await;
+    storage
+        .put(
+            &key,
+            StoredObject {
+                content_type: "image/jpeg".to_string(),
+                bytes,
+            },
+        )
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to store avatar: {}", e)))?;
+
+    let photo = format!("{}{}", MEDIA_PREFIX, key);
+    set_photo(&user, &photo).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: photo,
+    })
+}
+
+/// Clears the caller's avatar.
+#[server]
+pub async fn remove_avatar(token: String) -> Result<SuccessResponse<String>, ServerFnError> {
+    let (user, _) = authenticate(&token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    set_photo(&user, "").await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Avatar removed".into(),
+    })
+}
+
+/// Points `user.photo` at `photo` and deletes the previous avatar if it was an upload.
+#[cfg(feature = "server")]
+async fn set_photo(user: &User, photo: &str) -> Result<(), ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user.id },
+            doc! { "$set": { "photo": photo, "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    if let Some(key) = key_from_url(&user.photo) {
+        if let Err(e) = get_storage().await.delete(key).await {
+            tracing::error!("Failed to delete old avatar {}: {}", key, e);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the caller's own record, or any user's for an admin.
+#[server]
+pub async fn get_user_info(
+    token: String,
+    user_id: ObjectId,
+) -> Result<SuccessResponse<User>, ServerFnError> {
+    let (caller, _) = authenticate(&token).await?;
+    if caller.id == user_id {
+        return Ok(SuccessResponse {
+            status: "success".into(),
+            data: caller.redacted(),
+        });
+    }
+    require_admin(token).await?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set"));
+    let user_collection = db.collection::<User>("users");
+
+    let filter = doc! { "_id": user_id };
+    let user = user_collection
+        .find_one(filter)
+        .await
+        .map_err(|_| ServerFnError::new("Error fetching user data"))?
+        .ok_or(ServerFnError::new("User not found"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: user.redacted(),
+    })
+}
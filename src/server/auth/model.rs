@@ -1,3 +1,4 @@
+use crate::server::plan::model::Plan;
 use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -10,18 +11,171 @@ pub struct User {
     pub name: String,
     pub email: String,
     pub password: String,
+    /// What the user may do: [`ROLE_USER`] or [`ROLE_ADMIN`]. Billing never touches it.
     pub role: String,
+    /// What the user has paid for. Kept in step with their subscriptions by billing, and the only
+    /// field paid features and quotas are checked against.
+    #[serde(default)]
+    pub plan: Plan,
     pub photo: String,
     pub verified: bool,
+    #[serde(default)]
+    pub suspended: bool,
+    #[serde(default)]
+    pub preferences: UserPreferences,
+    /// External sign-in accounts linked to this user.
+    #[serde(default)]
+    pub identities: Vec<Identity>,
+    #[serde(default, rename = "twoFactor")]
+    pub two_factor: Option<TwoFactor>,
+    /// A new address waiting for its verification link to be opened.
+    #[serde(default, rename = "pendingEmail")]
+    pub pending_email: Option<String>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
 
+pub const ROLE_USER: &str = "user";
+pub const ROLE_ADMIN: &str = "admin";
+/// Roles an admin can assign.
+pub const ROLES: [&str; 2] = [ROLE_USER, ROLE_ADMIN];
+
+impl User {
+    pub fn is_admin(&self) -> bool {
+        self.role == ROLE_ADMIN
+    }
+
+    pub fn two_factor_enabled(&self) -> bool {
+        self.two_factor.as_ref().is_some_and(|tf| tf.enabled)
+    }
+
+    /// The user without credentials, for sending to the client. Backup codes are blanked
+    /// rather than removed so the number left can still be shown.
+    pub fn redacted(mut self) -> Self {
+        self.password.clear();
+        if let Some(two_factor) = &mut self.two_factor {
+            two_factor.secret.clear();
+            two_factor.backup_codes.iter_mut().for_each(String::clear);
+        }
+        self
+    }
+}
+
+/// TOTP settings. Stays disabled until enrollment is confirmed with a valid code.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct TwoFactor {
+    /// Base32-encoded shared secret.
+    pub secret: String,
+    pub enabled: bool,
+    /// SHA-256 hashes of the unused backup codes.
+    #[serde(default, rename = "backupCodes")]
+    pub backup_codes: Vec<String>,
+    /// The last time step a code was accepted for, so a code cannot be replayed.
+    #[serde(default, rename = "lastStep")]
+    pub last_step: u64,
+}
+
+/// An account at a sign-in provider, identified by the provider's subject ID.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Identity {
+    pub provider: String,
+    pub subject: String,
+}
+
+/// Travel preferences, used as form defaults and passed to every generation prompt.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct UserPreferences {
+    pub home_currency: String,
+    pub home_city: String,
+    /// Language trips are written in unless the trip form says otherwise.
+    pub language: String,
+    pub pace: TravelPace,
+    pub interests: Vec<String>,
+    pub dietary_needs: String,
+    pub mobility: String,
+    pub budget_style: BudgetStyle,
+    /// IANA timezone the dashboard's charts count days in.
+    pub timezone: String,
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        UserPreferences {
+            home_currency: "USD".to_string(),
+            home_city: String::new(),
+            language: "English".to_string(),
+            pace: TravelPace::default(),
+            interests: Vec::new(),
+            dietary_needs: String::new(),
+            mobility: String::new(),
+            budget_style: BudgetStyle::default(),
+            timezone: "UTC".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TravelPace {
+    Relaxed,
+    #[default]
+    Moderate,
+    Packed,
+}
+
+impl TravelPace {
+    pub const ALL: [TravelPace; 3] = [
+        TravelPace::Relaxed,
+        TravelPace::Moderate,
+        TravelPace::Packed,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TravelPace::Relaxed => "Relaxed",
+            TravelPace::Moderate => "Moderate",
+            TravelPace::Packed => "Packed",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetStyle {
+    Budget,
+    #[default]
+    MidRange,
+    Luxury,
+}
+
+impl BudgetStyle {
+    pub const ALL: [BudgetStyle; 3] = [
+        BudgetStyle::Budget,
+        BudgetStyle::MidRange,
+        BudgetStyle::Luxury,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BudgetStyle::Budget => "Budget",
+            BudgetStyle::MidRange => "Mid-range",
+            BudgetStyle::Luxury => "Luxury",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TokenClaims {
     pub sub: String,
     pub iat: usize,
     pub exp: usize,
+    /// Unique ID of this access token, checked against the revocation denylist.
+    #[serde(default)]
+    pub jti: String,
+    /// The session the token was issued for.
+    #[serde(default)]
+    pub sid: String,
 }
@@ -1,4 +1,6 @@
 use crate::server::auth::model::User;
+use crate::server::auth::model::UserPreferences;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -14,19 +16,104 @@ pub struct LoginUserSchema {
     pub password: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OidcLoginSchema {
+    pub provider: String,
+    /// The query string the provider redirected back with.
+    pub query: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResetPasswordSchema {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TwoFactorLoginSchema {
+    pub challenge: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TwoFactorCodeSchema {
+    pub token: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevokeSessionSchema {
+    pub token: String,
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdatePreferencesSchema {
+    pub token: String,
+    pub preferences: UserPreferences,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateProfileSchema {
+    pub token: String,
+    pub name: String,
+    pub email: String,
+    /// Only checked when the email changes.
+    pub current_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangePasswordSchema {
+    pub token: String,
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// A new avatar as a base64-encoded PNG or JPEG, already cropped and resized by the browser.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UploadAvatarSchema {
+    pub token: String,
+    pub image: String,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct AuthResponse {
     pub token: String,
+    pub refresh_token: String,
+    /// Set instead of the tokens when the account needs a second factor; pass it to
+    /// `verify_two_factor_login` with the code.
+    #[serde(default)]
+    pub two_factor_challenge: Option<String>,
+}
+
+/// A configured sign-in provider, for the "Continue with ..." buttons.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct OidcProviderInfo {
+    pub id: String,
+    pub name: String,
+}
+
+/// A pending 2FA enrollment, shown once so it can be added to an authenticator app.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TwoFactorSetup {
+    pub secret: String,
+    pub otpauth_uri: String,
+    /// `otpauth_uri` as an SVG QR code.
+    pub qr_svg: String,
+}
+
+/// A session as shown to its owner, without any token material.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SessionInfo {
+    pub id: String,
+    pub device: String,
+    pub user_agent: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub current: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct UserResponse {
     pub user: User,
 }
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DashboardResponse {
-    pub users: u64,
-    pub trips: u64,
-    pub paid_users: u64,
-}
@@ -0,0 +1,83 @@
+use bson::doc;
+use dioxus::prelude::*;
+
+use crate::server::billing::model::{CheckoutSession, Subscription};
+use crate::server::billing::request::CreateCheckoutRequest;
+use crate::server::common::response::SuccessResponse;
+
+#[cfg(feature = "server")]
+use {
+    crate::billing::{payment_provider, CheckoutRequest, PaymentProvider},
+    crate::db::get_client,
+    crate::server::auth::controller::auth,
+    crate::server::plan::model::Plan,
+    dioxus_logger::tracing,
+    mongodb::options::FindOneOptions,
+};
+
+#[server]
+pub async fn create_checkout_session(
+    req: CreateCheckoutRequest,
+) -> Result<SuccessResponse<CheckoutSession>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    if req.plan == Plan::Free {
+        return Err(ServerFnError::new("Choose a paid plan"));
+    }
+    if user.plan >= req.plan {
+        return Err(ServerFnError::new(format!(
+            "You are already on the {} plan",
+            user.plan
+        )));
+    }
+
+    let app_url = std::env::var("APP_URL").unwrap_or_else(|_| "http://localhost:3000".into());
+    let session = payment_provider()
+        .await
+        .create_checkout_session(CheckoutRequest {
+            user: &user,
+            plan: req.plan,
+            success_url: format!("{}/dashboard", app_url),
+            cancel_url: format!("{}/dashboard", app_url),
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create checkout session: {}", e);
+            ServerFnError::new("Failed to start checkout")
+        })?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: session,
+    })
+}
+
+#[server]
+pub async fn get_subscription(
+    token: String,
+) -> Result<SuccessResponse<Option<Subscription>>, ServerFnError> {
+    let user = auth(token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let subscription = db
+        .collection::<Subscription>("subscriptions")
+        .find_one(doc! { "user": user.id })
+        .with_options(
+            FindOneOptions::builder()
+                .sort(doc! { "updatedAt": -1 })
+                .build(),
+        )
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: subscription,
+    })
+}
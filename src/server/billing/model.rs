@@ -0,0 +1,85 @@
+use crate::server::plan::model::Plan;
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle of a paid subscription as reported by the payment provider.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    Trialing,
+    Active,
+    PastDue,
+    Canceled,
+}
+
+impl SubscriptionStatus {
+    /// Maps a Stripe subscription status. `unpaid` is treated as past due and
+    /// `incomplete_expired` as canceled; statuses with no counterpart yield `None`.
+    pub fn from_provider(status: &str) -> Option<Self> {
+        match status {
+            "trialing" => Some(SubscriptionStatus::Trialing),
+            "active" => Some(SubscriptionStatus::Active),
+            "past_due" | "unpaid" => Some(SubscriptionStatus::PastDue),
+            "canceled" | "incomplete_expired" => Some(SubscriptionStatus::Canceled),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubscriptionStatus::Trialing => "trialing",
+            SubscriptionStatus::Active => "active",
+            SubscriptionStatus::PastDue => "past due",
+            SubscriptionStatus::Canceled => "canceled",
+        }
+    }
+
+    /// Whether a subscription in this state entitles the user to its plan. Past-due
+    /// subscriptions lose access until payment succeeds.
+    pub fn grants_plan(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionStatus::Trialing | SubscriptionStatus::Active
+        )
+    }
+
+    /// Allowed state changes. Canceled is terminal: resubscribing creates a new subscription.
+    pub fn can_transition_to(&self, next: SubscriptionStatus) -> bool {
+        use SubscriptionStatus::*;
+        match (self, next) {
+            (current, next) if *current == next => true,
+            (Trialing, Active | PastDue | Canceled) => true,
+            (Active, PastDue | Canceled) => true,
+            (PastDue, Active | Canceled) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Subscription {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub plan: Plan,
+    pub status: SubscriptionStatus,
+    pub provider: String,
+    pub provider_customer_id: String,
+    pub provider_subscription_id: String,
+    #[serde(default)]
+    pub current_period_end: Option<DateTime<Utc>>,
+    /// Creation time of the newest provider event applied, used to drop stale deliveries.
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub last_event_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct CheckoutSession {
+    pub id: String,
+    pub url: String,
+}
@@ -0,0 +1,8 @@
+use crate::server::plan::model::Plan;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateCheckoutRequest {
+    pub token: String,
+    pub plan: Plan,
+}
@@ -1,2 +1,3 @@
+pub(crate) mod model;
 pub(crate) mod request;
 pub(crate) mod response;
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// Identifies the prompt template, and its version, that produced a piece of content.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
+pub struct PromptRef {
+    pub name: String,
+    pub version: u32,
+}
@@ -12,11 +12,13 @@ use crate::server::conversation::model::Message;
 use crate::server::conversation::request::CreateConversationRequest;
 use crate::server::conversation::request::GetConversationsRequest;
 use crate::server::conversation::request::GetMessagesRequest;
+use crate::server::conversation::request::SaveMessageRequest;
 use crate::server::conversation::request::SendQueryRequest;
 use crate::server::conversation::response::ConversationResponse;
 use crate::server::conversation::response::ConversationsListResponse;
 use crate::server::conversation::response::MessageResponse;
 use crate::server::conversation::response::MessagesListResponse;
+use crate::server::plan::model::QuotaMetric;
 use crate::server::trip::model::Detail;
 use crate::server::trip::model::Trip;
 #[cfg(feature = "server")]
@@ -24,7 +26,6 @@ use aws_config::BehaviorVersion;
 #[cfg(feature = "server")]
 use aws_sdk_bedrockruntime::{
     operation::converse::{ConverseError, ConverseOutput},
-    types::{ContentBlock, ConversationRole, Message as BedrockMessage},
     Client,
 };
 use bson::oid::ObjectId;
@@ -32,7 +33,18 @@ use chrono::prelude::*;
 use futures_util::TryStreamExt;
 use std::env;
 #[cfg(feature = "server")]
-use {crate::ai::get_ai, crate::db::get_client};
+use {
+    crate::ai::get_ai,
+    crate::authz::{parse_id, Access, Caller},
+    crate::db::get_client,
+    crate::llm::{converse, resolve_model, user_message},
+    crate::prompts::{render, traveller_profile, ChatAnswerPrompt},
+    crate::quota::reserve_quota,
+    crate::sanitize::sanitize_html,
+    crate::server::auth::model::User,
+    crate::server::llm::model::ModelInfo,
+    crate::verification::require_verified,
+};
 
 #[derive(Debug)]
 #[cfg(feature = "server")]
@@ -40,11 +52,7 @@ pub struct BedrockConverseError(pub String);
 #[cfg(feature = "server")]
 impl std::fmt::Display for BedrockConverseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Can't invoke '{}'. Reason: {}",
-            "anthropic.claude-3-haiku-20240307-v1:0", self.0
-        )
+        write!(f, "Can't invoke the model. Reason: {}", self.0)
     }
 }
 #[cfg(feature = "server")]
@@ -70,21 +78,20 @@ impl From<&ConverseError> for BedrockConverseError {
 pub async fn create_conversation(
     req: CreateConversationRequest,
 ) -> Result<ConversationResponse, ServerFnError> {
-    let user = auth(req.token)
-        .await
-        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Modify)
+        .await?;
+
     let db_client = get_client().await;
     let db = db_client
         .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let conversation_collection = db.collection::<Conversation>("conversations");
 
-    let trip_id =
-        ObjectId::parse_str(&req.trip_id).map_err(|_| ServerFnError::new("Invalid trip ID"))?;
-
     let conversation = Conversation {
         id: ObjectId::new(),
-        user: user.id,
-        trip: trip_id,
+        user: caller.user.id,
+        trip: trip.id,
         title: req.title,
         created_at: Utc::now(),
         updated_at: Utc::now(),
@@ -104,16 +111,17 @@ pub async fn create_conversation(
 pub async fn get_conversations(
     req: GetConversationsRequest,
 ) -> Result<ConversationsListResponse, ServerFnError> {
-    let user = auth(req.token)
-        .await
-        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Read)
+        .await?;
 
     let db_client = get_client().await;
     let db = db_client
         .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let conversation_collection = db.collection::<Conversation>("conversations");
 
-    let filter = doc! {"user": user.id, "trip": req.trip_id};
+    let filter = doc! {"user": trip.user, "trip": trip.id};
     let cursor = conversation_collection
         .find(filter)
         .await
@@ -130,31 +138,49 @@ pub async fn get_conversations(
 }
 
 #[server]
-pub async fn save_message_to_db(message: Message) -> Result<(), ServerFnError> {
+pub async fn save_message_to_db(req: SaveMessageRequest) -> Result<MessageResponse, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let conversation = caller
+        .conversation(req.conversation_id, Access::Modify)
+        .await?;
+
     let db_client = get_client().await;
     let db = db_client
         .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let messages_collection = db.collection::<Message>("messages");
 
+    let message = Message {
+        id: ObjectId::new(),
+        conversation: conversation.id,
+        sender: "user".to_string(),
+        content: sanitize_html(&req.content),
+        prompt: None,
+        timestamp: Utc::now(),
+    };
+
     messages_collection
-        .insert_one(message)
+        .insert_one(message.clone())
         .await
         .map_err(|e| ServerFnError::new(&e.to_string()))?;
-    Ok(())
+    Ok(MessageResponse {
+        status: "success".to_string(),
+        data: message,
+    })
 }
 
 #[server]
 pub async fn get_messages(req: GetMessagesRequest) -> Result<MessagesListResponse, ServerFnError> {
-    let user = auth(req.token)
-        .await
-        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let caller = Caller::from_token(req.token).await?;
+    let conversation = caller
+        .conversation(req.conversation_id, Access::Read)
+        .await?;
 
     let db_client = get_client().await;
     let db = db_client
         .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let messages_collection = db.collection::<Message>("messages");
 
-    let filter = doc! {"conversation": req.conversation_id};
+    let filter = doc! {"conversation": conversation.id};
     let cursor = messages_collection
         .find(filter)
         .await
@@ -174,115 +200,80 @@ pub async fn get_messages(req: GetMessagesRequest) -> Result<MessagesListRespons
 pub async fn send_query_to_bedrock(
     req: SendQueryRequest,
 ) -> Result<MessageResponse, ServerFnError> {
-    let user = auth(req.token)
-        .await
-        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    let caller = Caller::from_token(req.token).await?;
+    let conversation = caller
+        .conversation(req.conversation_id, Access::Modify)
+        .await?;
+    let (trip, detail) = caller
+        .detail(parse_id(&req.detail, "detail")?, Access::Modify)
+        .await?;
+    if trip.id != parse_id(&req.trip, "trip")? || conversation.trip != trip.id {
+        return Err(ServerFnError::new("Detail not found"));
+    }
+    let user = caller.user;
+
+    require_verified(&user)?;
+    let model = resolve_model(&req.model).await?;
+    let reservation =
+        reserve_quota(&user, &[QuotaMetric::ChatMessages, QuotaMetric::Tokens]).await?;
+    let result = answer_query(&user, &model, &trip, &detail, &conversation, req.query).await;
+    if result.is_err() {
+        reservation.release().await;
+    }
+    result
+}
 
+#[cfg(feature = "server")]
+async fn answer_query(
+    user: &User,
+    model: &ModelInfo,
+    trip: &Trip,
+    detail: &Detail,
+    conversation: &Conversation,
+    query: String,
+) -> Result<MessageResponse, ServerFnError> {
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let messages_collection = db.collection::<Message>("messages");
-    let trip_collection = db.collection::<Trip>("trips");
-    let details_collection = db.collection::<Detail>("details");
 
-    let mut client = get_ai().await.lock().await;
-
-    let trip_id =
-        ObjectId::parse_str(&req.trip).map_err(|_| ServerFnError::new("Invalid trip ID"))?;
-
-    let trip = trip_collection
-        .find_one(doc! { "_id": trip_id, "user": user.id })
-        .await?
-        .ok_or(ServerFnError::new("Trip not found"))?;
-
-    let detail_id =
-        ObjectId::parse_str(&req.detail).map_err(|_| ServerFnError::new("Invalid detail ID"))?;
-
-    let detail = details_collection
-        .find_one(doc! { "_id": detail_id })
-        .await?
-        .ok_or(ServerFnError::new("Detail not found"))?;
-
-    let system_prompt = format!(
-        "
-        **System Prompt (SP):** You are a knowledgeable assistant specializing in providing in-depth responses based on specific trip details. You understand the structure, themes, and content of trips, and you answer questions with context and precision.
-        Generate your response as HTML-formatted response with examples, links and images, based on the query: '{user_query}'. \
-        Each section should be structured with appropriate HTML tags, including <h1> for the main title, \
-        <h2> for detail titles, <h3> for subheadings, and <p> for paragraphs. \
-        Include well-organized, readable content that aligns with the trip's title {trip_title}, ensuring each section is \
-        clear and logically flows from one to the next. Avoid markdown format entirely, and provide inline HTML styling \
-        if necessary to enhance readability. The HTML content should be well-formatted, semantically correct, and \
-        cover all relevant subtopics in depth to create an engaging reading experience.
-
-        **Context Information:**
-        - Trip Title: '{trip_title}'
-        - Detail: '{detail}'
-    
-        **User Query:** The user has a question related to the trip detail provided. Use the context of the trip and detail to respond accurately and engage thoughtfully.
-    
-        **Prompt (P):** Answer the user's question in detail, focusing on information specific to the detail content '{detail}' from the trip '{trip_title}'. 
-        - Explain complex concepts in an accessible way if the user’s query requires it.
-        - Where applicable, relate your answer back to key themes and ideas presented in this detail.
-        - If the detail has distinct characters, events, or themes, draw on these to enhance your response.
-        - Provide direct and actionable information if the question is specific, or a comprehensive overview if the question is broad.
-    
-        **Expected Format (EF):**
-        - Begin with a brief introduction if the question pertains to a major theme or character in the detail.
-        - Answer in a clear, step-by-step, or structured format when applicable.
-        - For complex queries, summarize the response in the last sentence to ensure clarity for the user.
-    
-        Make sure to always return back with html formmatted text and not empty response. If the user asks to translate a detail, always respond with the corresponding translation and never reject the request.
-
-        **Roleplay (RP):** Act as a well-read, insightful assistant dedicated to enhancing the reader’s understanding of the material in this trip detail. Aim to be both informative and engaging in your response.
-    
-        **User Query:** '{user_query}'
-        ",
-        trip_title = trip.title,
-        detail = detail.html,
-        user_query = req.query
-    );
-
-    let response = client
-        .converse()
-        .model_id("anthropic.claude-3-haiku-20240307-v1:0")
-        .messages(
-            BedrockMessage::builder()
-                .role(ConversationRole::User)
-                .content(ContentBlock::Text(system_prompt.to_string()))
-                .build()
-                .map_err(|_| "failed to build message")
-                .unwrap(),
-        )
-        .send()
-        .await;
-
-    match response {
-        Ok(output) => {
-            let text = get_converse_output_text(output)?;
-            let response_message = Message {
-                id: ObjectId::new(),
-                conversation: req.conversation_id,
-                sender: "bedrock".to_string(),
-                content: text.clone(),
-                timestamp: Utc::now(),
-            };
+    let prompt = render(&ChatAnswerPrompt {
+        trip_title: trip.title.clone(),
+        detail: detail.html.clone(),
+        user_query: query,
+        language: user.preferences.language.clone(),
+        traveller: traveller_profile(&user.preferences),
+    })
+    .await?;
+
+    let message = user_message(prompt.text)?;
+    let text = converse(
+        user,
+        "chat_answer",
+        model,
+        vec![message],
+        model.max_output_tokens as i32,
+    )
+    .await?;
+
+    let response_message = Message {
+        id: ObjectId::new(),
+        conversation: conversation.id,
+        sender: "bedrock".to_string(),
+        content: sanitize_html(&text),
+        prompt: Some(prompt.reference),
+        timestamp: Utc::now(),
+    };
 
-            messages_collection
-                .insert_one(response_message.clone())
-                .await
-                .map_err(|e| ServerFnError::new(&e.to_string()))?;
+    messages_collection
+        .insert_one(response_message.clone())
+        .await
+        .map_err(|e| ServerFnError::new(&e.to_string()))?;
 
-            Ok(MessageResponse {
-                status: "success".to_string(),
-                data: response_message,
-            })
-        }
-        Err(e) => Err(e
-            .as_service_error()
-            .map(BedrockConverseError::from)
-            .unwrap_or_else(|| BedrockConverseError("Unknown service error".into()))
-            .into()),
-    }
+    Ok(MessageResponse {
+        status: "success".to_string(),
+        data: response_message,
+    })
 }
 
 #[cfg(feature = "server")]
@@ -1,5 +1,6 @@
 #![allow(non_snake_case)]
 
+use crate::server::common::model::PromptRef;
 use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,8 @@ pub struct Message {
     pub conversation: ObjectId,
     pub sender: String,
     pub content: String,
+    #[serde(default)]
+    pub prompt: Option<PromptRef>,
     #[serde(with = "chrono_datetime_as_bson_datetime")]
     pub timestamp: DateTime<Utc>,
 }
@@ -20,6 +20,13 @@ pub struct GetMessagesRequest {
     pub conversation_id: ObjectId,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SaveMessageRequest {
+    pub token: String,
+    pub conversation_id: ObjectId,
+    pub content: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SendQueryRequest {
     pub conversation_id: ObjectId,
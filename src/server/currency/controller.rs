@@ -0,0 +1,14 @@
+use dioxus::prelude::*;
+
+use crate::currency::RateTable;
+#[cfg(feature = "server")]
+use crate::rates::get_rates;
+use crate::server::common::response::SuccessResponse;
+
+#[server]
+pub async fn get_rate_table() -> Result<SuccessResponse<RateTable>, ServerFnError> {
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: get_rates().await,
+    })
+}
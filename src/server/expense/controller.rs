@@ -0,0 +1,143 @@
+#![allow(unused_imports)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use futures_util::TryStreamExt;
+
+#[cfg(feature = "server")]
+use crate::authz::{parse_id, Access, Caller};
+use crate::currency::find_currency;
+#[cfg(feature = "server")]
+use crate::db::get_client;
+use crate::server::common::response::SuccessResponse;
+use crate::server::expense::model::Expense;
+use crate::server::expense::model::EXPENSE_CATEGORIES;
+use crate::server::expense::request::AddExpenseRequest;
+use crate::server::expense::request::DeleteExpenseRequest;
+use crate::server::expense::request::GetExpensesRequest;
+use crate::server::trip::model::Detail;
+use crate::server::trip::model::Trip;
+
+#[server]
+pub async fn add_expense(
+    req: AddExpenseRequest,
+) -> Result<SuccessResponse<Expense>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+
+    if !req.amount.is_finite() || req.amount <= 0.0 {
+        return Err(ServerFnError::new("Amount must be greater than zero"));
+    }
+    if !EXPENSE_CATEGORIES.contains(&req.category.as_str()) {
+        return Err(ServerFnError::new("Unknown expense category"));
+    }
+    let currency = find_currency(&req.currency)
+        .ok_or(ServerFnError::new("Unsupported currency"))?
+        .code
+        .to_string();
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let details_collection = db.collection::<Detail>("details");
+    let expense_collection = db.collection::<Expense>("expenses");
+
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Modify)
+        .await?;
+
+    let mut day = req.day;
+    let detail = match req.detail_id.filter(|id| !id.is_empty()) {
+        Some(detail_id) => {
+            let detail_id = ObjectId::parse_str(&detail_id)
+                .map_err(|_| ServerFnError::new("Invalid detail ID"))?;
+            let detail = details_collection
+                .find_one(doc! { "_id": detail_id, "trip_id": trip.id })
+                .await?
+                .ok_or(ServerFnError::new("Detail not found"))?;
+            day = day.or(Some(detail.day));
+            Some(detail.id)
+        }
+        None => None,
+    };
+
+    let expense = Expense {
+        id: ObjectId::new(),
+        user: caller.user.id,
+        trip: trip.id,
+        detail,
+        day,
+        amount: req.amount,
+        currency,
+        category: req.category,
+        payer: req.payer.trim().to_string(),
+        note: req.note.trim().to_string(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    expense_collection.insert_one(expense.clone()).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: expense,
+    })
+}
+
+#[server]
+pub async fn get_expenses_for_trip(
+    req: GetExpensesRequest,
+) -> Result<SuccessResponse<Vec<Expense>>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Read)
+        .await?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let expense_collection = db.collection::<Expense>("expenses");
+
+    let expenses = expense_collection
+        .find(doc! { "trip": trip.id, "user": trip.user })
+        .sort(doc! { "createdAt": 1 })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: expenses,
+    })
+}
+
+#[server]
+pub async fn delete_expense(
+    req: DeleteExpenseRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let expense_collection = db.collection::<Expense>("expenses");
+
+    let expense = expense_collection
+        .find_one(doc! { "_id": parse_id(&req.expense_id, "expense")? })
+        .await?
+        .ok_or(ServerFnError::new("Expense not found"))?;
+    // Expenses belong to their trip, so whoever may modify the trip may delete them.
+    let trip = caller
+        .trip(expense.trip, Access::Modify)
+        .await
+        .map_err(|_| ServerFnError::new("Expense not found"))?;
+
+    expense_collection
+        .delete_one(doc! { "_id": expense.id, "trip": trip.id })
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Expense deleted successfully".into(),
+    })
+}
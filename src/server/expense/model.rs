@@ -0,0 +1,33 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const EXPENSE_CATEGORIES: [&str; 6] = [
+    "Food",
+    "Lodging",
+    "Transport",
+    "Activities",
+    "Shopping",
+    "Other",
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Expense {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub trip: ObjectId,
+    pub detail: Option<ObjectId>,
+    pub day: Option<u64>,
+    pub amount: f64,
+    pub currency: String,
+    pub category: String,
+    pub payer: String,
+    pub note: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
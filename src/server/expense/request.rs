@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AddExpenseRequest {
+    pub token: String,
+    pub trip_id: String,
+    pub detail_id: Option<String>,
+    pub day: Option<u64>,
+    pub amount: f64,
+    pub currency: String,
+    pub category: String,
+    pub payer: String,
+    pub note: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetExpensesRequest {
+    pub token: String,
+    pub trip_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteExpenseRequest {
+    pub token: String,
+    pub expense_id: String,
+}
@@ -0,0 +1,14 @@
+use dioxus::prelude::*;
+
+#[cfg(feature = "server")]
+use crate::llm::get_models;
+use crate::server::common::response::SuccessResponse;
+use crate::server::llm::model::ModelInfo;
+
+#[server]
+pub async fn get_model_registry() -> Result<SuccessResponse<Vec<ModelInfo>>, ServerFnError> {
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: get_models().await.to_vec(),
+    })
+}
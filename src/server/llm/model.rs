@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Bedrock,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct Capabilities {
+    #[serde(default)]
+    pub streaming: bool,
+    #[serde(default)]
+    pub tools: bool,
+    #[serde(default)]
+    pub vision: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ModelInfo {
+    pub name: String,
+    /// Older names that still resolve to this model, e.g. ones stored by earlier clients.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub provider: Provider,
+    pub model_id: String,
+    pub context_window: u32,
+    pub max_output_tokens: u32,
+    pub input_price_per_token: f64,
+    pub output_price_per_token: f64,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+#[cfg(feature = "server")]
+impl ModelInfo {
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.name.eq_ignore_ascii_case(name)
+            || self.model_id == name
+            || self
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        input_tokens as f64 * self.input_price_per_token
+            + output_tokens as f64 * self.output_price_per_token
+    }
+
+    /// Tokens as counted against a plan's allowance: priced relative to `base`, the default
+    /// model, so a call to a model ten times as expensive uses ten times the allowance.
+    pub fn quota_tokens(&self, base: &ModelInfo, input_tokens: u64, output_tokens: u64) -> u64 {
+        let ratio = |price: f64, base_price: f64| {
+            if base_price > 0.0 {
+                price / base_price
+            } else {
+                1.0
+            }
+        };
+        (input_tokens as f64 * ratio(self.input_price_per_token, base.input_price_per_token)
+            + output_tokens as f64
+                * ratio(self.output_price_per_token, base.output_price_per_token))
+        .ceil() as u64
+    }
+}
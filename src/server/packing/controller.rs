@@ -0,0 +1,400 @@
+#![allow(unused_imports)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use futures_util::TryStreamExt;
+use regex::Regex;
+
+use crate::server::auth::controller::auth;
+use crate::server::common::response::SuccessResponse;
+#[cfg(feature = "server")]
+use crate::server::packing::model::merge_items;
+use crate::server::packing::model::PackingItem;
+use crate::server::packing::model::PackingList;
+use crate::server::packing::model::PackingTemplate;
+use crate::server::packing::model::PACKING_CATEGORIES;
+use crate::server::packing::request::ApplyPackingTemplateRequest;
+use crate::server::packing::request::DeletePackingTemplateRequest;
+use crate::server::packing::request::GeneratePackingListRequest;
+use crate::server::packing::request::GetPackingListRequest;
+use crate::server::packing::request::GetPackingTemplatesRequest;
+use crate::server::packing::request::SavePackingTemplateRequest;
+use crate::server::packing::request::UpdatePackingListRequest;
+use crate::server::trip::model::Detail;
+use crate::server::trip::model::Trip;
+#[cfg(feature = "server")]
+use {
+    crate::authz::{parse_id, Access, Caller},
+    crate::db::get_client,
+    crate::llm::{converse, resolve_model, user_message},
+    crate::prompts::{render, PackingListPrompt},
+    crate::quota::reserve_quota,
+    crate::server::auth::model::User,
+    crate::server::plan::model::QuotaMetric,
+    crate::verification::require_verified,
+    crate::weather::weather_for_trip,
+    mongodb::Database,
+    std::sync::OnceLock,
+};
+
+/// Parses lines shaped like `- [Clothing] T-shirt x 4` into packing items.
+#[cfg(feature = "server")]
+fn parse_packing_list(text: &str) -> Vec<PackingItem> {
+    static LINE: OnceLock<Regex> = OnceLock::new();
+    let re = LINE
+        .get_or_init(|| Regex::new(r"^\s*[-*]\s*\[([^\]]+)\]\s*(.+?)\s*[x×]\s*(\d+)\s*$").unwrap());
+    let mut items = Vec::new();
+
+    for line in text.lines() {
+        if let Some(caps) = re.captures(line) {
+            let category = PACKING_CATEGORIES
+                .iter()
+                .find(|category| category.eq_ignore_ascii_case(caps[1].trim()))
+                .unwrap_or(&"Other");
+            items.push(PackingItem {
+                name: caps[2].trim().to_string(),
+                category: category.to_string(),
+                quantity: caps[3].parse::<u32>().unwrap_or(1).max(1),
+                packed: false,
+            });
+        }
+    }
+
+    items
+}
+
+#[cfg(feature = "server")]
+fn clean_items(items: Vec<PackingItem>) -> Vec<PackingItem> {
+    items
+        .into_iter()
+        .filter(|item| !item.name.trim().is_empty())
+        .map(|item| PackingItem {
+            name: item.name.trim().to_string(),
+            category: if PACKING_CATEGORIES.contains(&item.category.as_str()) {
+                item.category
+            } else {
+                "Other".to_string()
+            },
+            quantity: item.quantity.max(1),
+            packed: item.packed,
+        })
+        .collect()
+}
+
+#[cfg(feature = "server")]
+async fn get_db() -> Database {
+    let client = get_client().await;
+    client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."))
+}
+
+/// Stores `items` as the packing list of `trip`, creating the list if needed.
+#[cfg(feature = "server")]
+async fn save_packing_list(
+    db: &Database,
+    user: ObjectId,
+    trip: ObjectId,
+    items: Vec<PackingItem>,
+) -> Result<PackingList, ServerFnError> {
+    let collection = db.collection::<PackingList>("packing_lists");
+
+    let list = match collection
+        .find_one(doc! { "trip": trip, "user": user })
+        .await?
+    {
+        Some(mut list) => {
+            list.items = items;
+            list.updated_at = Utc::now();
+            collection
+                .replace_one(doc! { "_id": list.id }, list.clone())
+                .await?;
+            list
+        }
+        None => {
+            let list = PackingList {
+                id: ObjectId::new(),
+                user,
+                trip,
+                items,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            };
+            collection.insert_one(list.clone()).await?;
+            list
+        }
+    };
+
+    Ok(list)
+}
+
+#[server]
+pub async fn generate_packing_list(
+    req: GeneratePackingListRequest,
+) -> Result<SuccessResponse<PackingList>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Modify)
+        .await?;
+    let user = caller.user;
+    require_verified(&user)?;
+    let reservation = reserve_quota(&user, &[QuotaMetric::Tokens]).await?;
+
+    let result = create_packing_list(&user, &trip, req.language).await;
+    if result.is_err() {
+        reservation.release().await;
+    }
+    result
+}
+
+/// Generates and stores the packing list for `trip`, once tokens have been reserved for `user`.
+#[cfg(feature = "server")]
+async fn create_packing_list(
+    user: &User,
+    trip: &Trip,
+    language: String,
+) -> Result<SuccessResponse<PackingList>, ServerFnError> {
+    let db = get_db().await;
+
+    let details: Vec<Detail> = db
+        .collection::<Detail>("details")
+        .find(doc! { "trip_id": trip.id })
+        .await?
+        .try_collect()
+        .await?;
+    let activities = details
+        .iter()
+        .map(|detail| format!("- Day {}: {}", detail.day, detail.title))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let dates = match (trip.start_date, trip.end_date) {
+        (Some(start), Some(end)) => format!("from {} to {}", start, end),
+        _ => "on unspecified dates".to_string(),
+    };
+    let weather = weather_for_trip(trip)
+        .await
+        .iter()
+        .map(|day| format!("- {}: {}", day.date, day.describe()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let prompt = render(&PackingListPrompt {
+        language,
+        title: trip.title.clone(),
+        destination: trip.subtitle.clone().unwrap_or_else(|| trip.title.clone()),
+        dates,
+        activities,
+        weather: if weather.is_empty() {
+            "- Unknown".to_string()
+        } else {
+            weather
+        },
+        categories: PACKING_CATEGORIES.join(", "),
+    })
+    .await?;
+
+    let model = resolve_model("").await?;
+    let message = user_message(prompt.text)?;
+    let text = converse(
+        user,
+        "packing_list",
+        &model,
+        vec![message],
+        model.max_output_tokens as i32,
+    )
+    .await?;
+
+    let mut items = parse_packing_list(&text);
+    if items.is_empty() {
+        return Err(ServerFnError::new("Failed to generate a packing list"));
+    }
+
+    if let Some(existing) = db
+        .collection::<PackingList>("packing_lists")
+        .find_one(doc! { "trip": trip.id, "user": user.id })
+        .await?
+    {
+        for item in items.iter_mut() {
+            item.packed = existing
+                .items
+                .iter()
+                .any(|old| old.packed && old.name.eq_ignore_ascii_case(&item.name));
+        }
+    }
+
+    let list = save_packing_list(&db, user.id, trip.id, items).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: list,
+    })
+}
+
+#[server]
+pub async fn get_packing_list(
+    req: GetPackingListRequest,
+) -> Result<SuccessResponse<Option<PackingList>>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Read)
+        .await?;
+
+    let db = get_db().await;
+    let list = db
+        .collection::<PackingList>("packing_lists")
+        .find_one(doc! { "trip": trip.id, "user": trip.user })
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: list,
+    })
+}
+
+#[server]
+pub async fn update_packing_list(
+    req: UpdatePackingListRequest,
+) -> Result<SuccessResponse<PackingList>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Modify)
+        .await?;
+    let user = caller.user;
+
+    let db = get_db().await;
+    let list = save_packing_list(&db, user.id, trip.id, clean_items(req.items)).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: list,
+    })
+}
+
+#[server]
+pub async fn save_packing_template(
+    req: SavePackingTemplateRequest,
+) -> Result<SuccessResponse<PackingTemplate>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let name = req.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ServerFnError::new("Template name is required"));
+    }
+    let items = clean_items(req.items)
+        .into_iter()
+        .map(|item| PackingItem {
+            packed: false,
+            ..item
+        })
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        return Err(ServerFnError::new(
+            "Template must contain at least one item",
+        ));
+    }
+
+    let db = get_db().await;
+    let template = PackingTemplate {
+        id: ObjectId::new(),
+        user: user.id,
+        name,
+        items,
+        created_at: Utc::now(),
+    };
+    db.collection::<PackingTemplate>("packing_templates")
+        .insert_one(template.clone())
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: template,
+    })
+}
+
+#[server]
+pub async fn get_packing_templates(
+    req: GetPackingTemplatesRequest,
+) -> Result<SuccessResponse<Vec<PackingTemplate>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let db = get_db().await;
+    let templates = db
+        .collection::<PackingTemplate>("packing_templates")
+        .find(doc! { "user": user.id })
+        .sort(doc! { "name": 1 })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: templates,
+    })
+}
+
+#[server]
+pub async fn apply_packing_template(
+    req: ApplyPackingTemplateRequest,
+) -> Result<SuccessResponse<PackingList>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Modify)
+        .await?;
+    let user = caller.user;
+
+    let db = get_db().await;
+
+    let template_id = ObjectId::parse_str(&req.template_id)
+        .map_err(|_| ServerFnError::new("Invalid template ID"))?;
+    let template = db
+        .collection::<PackingTemplate>("packing_templates")
+        .find_one(doc! { "_id": template_id, "user": user.id })
+        .await?
+        .ok_or(ServerFnError::new("Template not found"))?;
+
+    let mut items = db
+        .collection::<PackingList>("packing_lists")
+        .find_one(doc! { "trip": trip.id, "user": user.id })
+        .await?
+        .map(|list| list.items)
+        .unwrap_or_default();
+    merge_items(&mut items, template.items);
+
+    let list = save_packing_list(&db, user.id, trip.id, items).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: list,
+    })
+}
+
+#[server]
+pub async fn delete_packing_template(
+    req: DeletePackingTemplateRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let db = get_db().await;
+    let template_id = ObjectId::parse_str(&req.template_id)
+        .map_err(|_| ServerFnError::new("Invalid template ID"))?;
+
+    let result = db
+        .collection::<PackingTemplate>("packing_templates")
+        .delete_one(doc! { "_id": template_id, "user": user.id })
+        .await?;
+    if result.deleted_count == 0 {
+        return Err(ServerFnError::new("Template not found"));
+    }
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Template deleted successfully".into(),
+    })
+}
@@ -0,0 +1,65 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const PACKING_CATEGORIES: [&str; 7] = [
+    "Clothing",
+    "Toiletries",
+    "Electronics",
+    "Documents",
+    "Health",
+    "Gear",
+    "Other",
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PackingItem {
+    pub name: String,
+    pub category: String,
+    pub quantity: u32,
+    #[serde(default)]
+    pub packed: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PackingList {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub trip: ObjectId,
+    pub items: Vec<PackingItem>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PackingTemplate {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub name: String,
+    pub items: Vec<PackingItem>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Adds `extra` to `items`, bumping the quantity of items that are already on the list.
+#[cfg(feature = "server")]
+pub fn merge_items(items: &mut Vec<PackingItem>, extra: Vec<PackingItem>) {
+    for item in extra {
+        match items
+            .iter_mut()
+            .find(|existing| existing.name.eq_ignore_ascii_case(&item.name))
+        {
+            Some(existing) => existing.quantity = existing.quantity.max(item.quantity),
+            None => items.push(PackingItem {
+                packed: false,
+                ..item
+            }),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::server::packing::model::PackingItem;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GeneratePackingListRequest {
+    pub token: String,
+    pub trip_id: String,
+    pub language: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetPackingListRequest {
+    pub token: String,
+    pub trip_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdatePackingListRequest {
+    pub token: String,
+    pub trip_id: String,
+    pub items: Vec<PackingItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavePackingTemplateRequest {
+    pub token: String,
+    pub name: String,
+    pub items: Vec<PackingItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetPackingTemplatesRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApplyPackingTemplateRequest {
+    pub token: String,
+    pub trip_id: String,
+    pub template_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeletePackingTemplateRequest {
+    pub token: String,
+    pub template_id: String,
+}
@@ -0,0 +1,19 @@
+use dioxus::prelude::*;
+
+use crate::server::common::response::SuccessResponse;
+use crate::server::plan::model::Usage;
+
+#[cfg(feature = "server")]
+use {crate::quota::current_usage, crate::server::auth::controller::auth};
+
+#[server]
+pub async fn get_usage(token: String) -> Result<SuccessResponse<Usage>, ServerFnError> {
+    let user = auth(token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: current_usage(&user).await?,
+    })
+}
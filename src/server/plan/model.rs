@@ -0,0 +1,188 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Subscription tier of a user, ordered from least to most generous. Users created before
+/// plans existed are on the free plan.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Plan {
+    #[default]
+    Free,
+    Pro,
+    Team,
+}
+
+impl Plan {
+    pub const ALL: [Plan; 3] = [Plan::Free, Plan::Pro, Plan::Team];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Plan::Free => "free",
+            Plan::Pro => "pro",
+            Plan::Team => "team",
+        }
+    }
+
+    /// Monthly allowances of the plan.
+    pub fn limits(&self) -> PlanLimits {
+        match self {
+            Plan::Free => PlanLimits {
+                trips: 3,
+                chat_messages: 50,
+                tokens: 200_000,
+            },
+            Plan::Pro => PlanLimits {
+                trips: 30,
+                chat_messages: 1_000,
+                tokens: 3_000_000,
+            },
+            Plan::Team => PlanLimits {
+                trips: 150,
+                chat_messages: 5_000,
+                tokens: 15_000_000,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Plan {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Plan::ALL
+            .into_iter()
+            .find(|plan| plan.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or(())
+    }
+}
+
+/// Per-month allowances, also used to report the amounts consumed so far.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub struct PlanLimits {
+    pub trips: u64,
+    pub chat_messages: u64,
+    pub tokens: u64,
+}
+
+impl PlanLimits {
+    pub fn get(&self, metric: QuotaMetric) -> u64 {
+        match metric {
+            QuotaMetric::Trips => self.trips,
+            QuotaMetric::ChatMessages => self.chat_messages,
+            QuotaMetric::Tokens => self.tokens,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaMetric {
+    Trips,
+    ChatMessages,
+    Tokens,
+}
+
+impl QuotaMetric {
+    pub const ALL: [QuotaMetric; 3] = [
+        QuotaMetric::Trips,
+        QuotaMetric::ChatMessages,
+        QuotaMetric::Tokens,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            QuotaMetric::Trips => "trips",
+            QuotaMetric::ChatMessages => "chat messages",
+            QuotaMetric::Tokens => "tokens",
+        }
+    }
+}
+
+/// Returned by generation endpoints once a monthly allowance is used up.
+///
+/// Server functions carry it as the error message, so clients can recover it from a
+/// `ServerFnError` with [`QuotaError::from_server_error`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct QuotaError {
+    pub plan: Plan,
+    pub metric: QuotaMetric,
+    pub used: u64,
+    pub limit: u64,
+}
+
+const QUOTA_ERROR_PREFIX: &str = "Monthly quota exceeded: ";
+
+impl QuotaError {
+    pub fn from_server_error<E>(error: &dioxus::prelude::ServerFnError<E>) -> Option<Self>
+    where
+        E: fmt::Display,
+    {
+        error.to_string().parse().ok()
+    }
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}/{} {} on the {} plan",
+            QUOTA_ERROR_PREFIX,
+            self.used,
+            self.limit,
+            self.metric.label(),
+            self.plan
+        )
+    }
+}
+
+impl std::error::Error for QuotaError {}
+
+impl FromStr for QuotaError {
+    type Err = ();
+
+    /// Parses the [`Display`](fmt::Display) form, ignoring anything before it such as the
+    /// `error running server function:` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (_, rest) = s.split_once(QUOTA_ERROR_PREFIX).ok_or(())?;
+        let (counts, rest) = rest.split_once(' ').ok_or(())?;
+        let (used, limit) = counts.split_once('/').ok_or(())?;
+        let (label, rest) = rest.split_once(" on the ").ok_or(())?;
+        let plan = rest.trim().strip_suffix(" plan").ok_or(())?;
+
+        Ok(QuotaError {
+            plan: plan.parse()?,
+            metric: QuotaMetric::ALL
+                .into_iter()
+                .find(|metric| metric.label() == label)
+                .ok_or(())?,
+            used: used.parse().map_err(|_| ())?,
+            limit: limit.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+/// A user's plan together with what they have consumed in the current calendar month.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct Usage {
+    pub plan: Plan,
+    pub limits: PlanLimits,
+    pub used: PlanLimits,
+    pub period_start: DateTime<Utc>,
+}
+
+impl Usage {
+    /// Share of the allowance consumed, from 0 to 100.
+    pub fn percent(&self, metric: QuotaMetric) -> u64 {
+        match self.limits.get(metric) {
+            0 => 100,
+            limit => (self.used.get(metric) * 100 / limit).min(100),
+        }
+    }
+}
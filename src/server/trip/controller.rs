@@ -5,8 +5,12 @@ use bson::doc;
 use dioxus::prelude::*;
 use dioxus_logger::tracing;
 
+use crate::currency::find_currency;
 use crate::server::auth::controller::auth;
+use crate::server::common::model::PromptRef;
 use crate::server::common::response::SuccessResponse;
+use crate::server::plan::model::QuotaMetric;
+use crate::server::trip::model::default_currency;
 use crate::server::trip::model::Detail;
 use crate::server::trip::model::Trip;
 use crate::server::trip::request::AIRequest;
@@ -17,26 +21,37 @@ use crate::server::trip::request::GetDetailContentRequest;
 use crate::server::trip::request::GetTripForUserRequest;
 use crate::server::trip::request::GetTripsForUserRequest;
 use crate::server::trip::request::StoreTripRequest;
+use crate::server::trip::request::UpdateDetailMarkdownRequest;
 use crate::server::trip::request::UpdateTripContentRequest;
 use crate::server::trip::response::GenerateTripOutlineResponse;
 use crate::server::trip::response::TripResponse;
-use crate::server::trip::response::{
-    AIUsageStats, AnalyticsData, EngagementStats, PredictiveStats,
-};
 use std::env;
 
 use bson::oid::ObjectId;
 use chrono::prelude::*;
+use chrono::Days;
 use futures_util::StreamExt;
 use futures_util::TryStreamExt;
 use regex::Regex;
 #[cfg(feature = "server")]
 use {
     crate::ai::get_ai,
+    crate::authz::{can_access_trip, parse_id, Access, Caller},
     crate::db::get_client,
+    crate::llm::{assistant_message, converse, resolve_model, user_message},
+    crate::markdown::render_markdown,
+    crate::prompts::{
+        render, traveller_profile, DetailMarkdownPrompt, TripOutlineFixPrompt, TripOutlinePrompt,
+    },
+    crate::quota::reserve_quota,
+    crate::sanitize::sanitize_html,
+    crate::server::auth::model::{User, UserPreferences},
     crate::server::conversation::controller::get_converse_output_text,
     crate::server::conversation::controller::BedrockConverseError,
+    crate::server::llm::model::ModelInfo,
     crate::unsplash::get_unsplash_client,
+    crate::verification::require_verified,
+    crate::weather::weather_for_trip,
     http_api_isahc_client::{Client as _, IsahcClient},
     rand::thread_rng,
     rand::Rng,
@@ -52,7 +67,6 @@ use aws_config::BehaviorVersion;
 #[cfg(feature = "server")]
 use aws_sdk_bedrockruntime::{
     operation::converse::{ConverseError, ConverseOutput},
-    types::{ContentBlock, ConversationRole, Message as BedrockMessage},
     Client,
 };
 #[cfg(feature = "server")]
@@ -93,6 +107,12 @@ pub async fn store_trip(
         trip_type: req.trip_type,
         cover: photo_url,
         completed: false,
+        budget: 0.0,
+        currency: default_currency(),
+        start_date: None,
+        end_date: None,
+        timezone: None,
+        prompt: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -141,18 +161,20 @@ pub async fn fetch_cover(topic: String) -> Result<Option<String>, ServerFnError>
 pub async fn update_detail_content(
     req: UpdateTripContentRequest,
 ) -> Result<SuccessResponse<String>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Modify)
+        .await?;
+
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let trip_collection = db.collection::<Trip>("trips");
 
-    let trip_id =
-        ObjectId::parse_str(&req.trip_id).map_err(|_| ServerFnError::new("Invalid trip ID"))?;
-
     trip_collection
         .update_one(
-            doc! { "_id": trip_id },
-            doc! { "$set": { "content": req.new_content, "updatedAt": Utc::now() } },
+            doc! { "_id": trip.id },
+            doc! { "$set": { "content": sanitize_html(&req.new_content), "updatedAt": Utc::now() } },
         )
         .await?;
 
@@ -166,6 +188,9 @@ pub async fn update_detail_content(
 pub async fn complete_trip(
     req: CompleteTripRequest,
 ) -> Result<SuccessResponse<String>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller.trip(req.trip_id, Access::Modify).await?;
+
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
@@ -173,7 +198,7 @@ pub async fn complete_trip(
 
     trip_collection
         .update_one(
-            doc! { "_id": req.trip_id },
+            doc! { "_id": trip.id },
             doc! { "$set": { "completed": true, "updatedAt": Utc::now() } },
         )
         .await?;
@@ -213,22 +238,10 @@ pub async fn get_trips_for_user(
 pub async fn get_trip_for_user(
     req: GetTripForUserRequest,
 ) -> Result<SuccessResponse<Trip>, ServerFnError> {
-    let user = auth(req.token)
-        .await
-        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
-
-    let client = get_client().await;
-    let db =
-        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
-    let trip_collection = db.collection::<Trip>("trips");
-
-    let trip_id =
-        ObjectId::parse_str(&req.trip_id).map_err(|_| ServerFnError::new("Invalid trip ID"))?;
-
-    let trip = trip_collection
-        .find_one(doc! { "_id": trip_id, "user": user.id })
-        .await?
-        .ok_or(ServerFnError::new("Trip not found"))?;
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Read)
+        .await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
@@ -236,67 +249,146 @@ pub async fn get_trip_for_user(
     })
 }
 
+const MAX_TRIP_DAYS: u64 = 30;
+const MAX_PLACES_PER_DAY: u64 = 10;
+const OUTLINE_FIX_ATTEMPTS: usize = 2;
+
+/// Describes how a parsed outline deviates from the requested shape, if it does.
+fn outline_problem(details: &[Detail], days: u64, places: u64) -> Option<String> {
+    let mut per_day = std::collections::BTreeMap::new();
+    for detail in details {
+        *per_day.entry(detail.day).or_insert(0u64) += 1;
+    }
+
+    let expected: Vec<u64> = (1..=days).collect();
+    let found: Vec<u64> = per_day.keys().copied().collect();
+    if found != expected {
+        return Some(format!(
+            "Your outline has {} days ({:?}) instead of {}.",
+            found.len(),
+            found,
+            days
+        ));
+    }
+    per_day
+        .iter()
+        .find(|(_, count)| **count != places)
+        .map(|(day, count)| format!("Day {} has {} places instead of {}.", day, count, places))
+}
+
 #[server]
 pub async fn generate_trip_outline(
     req: GenerateTripRequest,
 ) -> Result<SuccessResponse<GenerateTripOutlineResponse>, ServerFnError> {
-    let user = auth(req.token)
+    let user = auth(req.token.clone())
         .await
         .map_err(|_| ServerFnError::new("Not Authenticated"))?;
 
-    let mut client = get_ai().await.lock().await;
-
-    let system_prompt = format!(
-        "
-        **System Prompt (SP):** You are an expert travel planner creating a structured, day-by-day trip itinerary.
-    
-        **Prompt (P):** Create a travel outline titled '{title}' to the destination '{subtitle}'. The trip should be planned with a main theme of '{title}', and presented in {language}. The itinerary should fit within a budget of {budget}. 
-    
-        Generate a day-by-day schedule for the trip, including specific places to visit, activities, and an estimated time duration for each. Use a structured format for each day and activity.
-    
-        **Expected Format (EF):**
-        ### Day [number]: [Day Title]
-        #### Place [number]: [Place Name]
-        **Estimated Duration:** [Duration] minutes
-    
-        * [Activity description]
-        * [Additional information as needed]
-    
-        **Roleplay (RP):** As a travel planner, make the plan engaging and realistic.
-        ",
-        title = req.title,
-        subtitle = req.subtitle,
-        budget = req.subtopics,
-        language = req.language,
-    );
+    let currency = find_currency(&req.currency)
+        .ok_or(ServerFnError::new("Unsupported currency"))?
+        .code
+        .to_string();
 
-    let mut outline = "".to_string();
+    if !(1..=MAX_TRIP_DAYS).contains(&req.max_length) {
+        return Err(ServerFnError::new(format!(
+            "Days must be between 1 and {}",
+            MAX_TRIP_DAYS
+        )));
+    }
+    if !(1..=MAX_PLACES_PER_DAY).contains(&req.details) {
+        return Err(ServerFnError::new(format!(
+            "Places per day must be between 1 and {}",
+            MAX_PLACES_PER_DAY
+        )));
+    }
+    require_verified(&user)?;
+    let model = resolve_model(&req.model).await?;
+    let reservation = reserve_quota(&user, &[QuotaMetric::Trips, QuotaMetric::Tokens]).await?;
 
-    let response = client
-        .converse()
-        .model_id("anthropic.claude-3-haiku-20240307-v1:0")
-        .messages(
-            BedrockMessage::builder()
-                .role(ConversationRole::User)
-                .content(ContentBlock::Text(system_prompt.to_string()))
-                .build()
-                .map_err(|_| "failed to build message")
-                .unwrap(),
-        )
-        .send()
-        .await;
+    let result = create_trip_outline(&user, &model, currency, req).await;
+    if result.is_err() {
+        reservation.release().await;
+    }
+    result
+}
 
-    match response {
-        Ok(output) => {
-            outline = get_converse_output_text(output)?;
-        }
-        Err(e) => {
-            return Err(e
-                .as_service_error()
-                .map(BedrockConverseError::from)
-                .unwrap_or_else(|| BedrockConverseError("Unknown service error".into()))
-                .into());
+/// Generates the outline and stores the trip, once a trip has been reserved for `user`.
+#[cfg(feature = "server")]
+async fn create_trip_outline(
+    user: &User,
+    model: &ModelInfo,
+    currency: String,
+    req: GenerateTripRequest,
+) -> Result<SuccessResponse<GenerateTripOutlineResponse>, ServerFnError> {
+    let days = req.max_length;
+    let places = req.details;
+    let timezone = match req.timezone.as_deref().map(str::trim) {
+        Some(tz) if !tz.is_empty() => Some(
+            tz.parse::<chrono_tz::Tz>()
+                .map_err(|_| ServerFnError::new("Invalid IANA timezone"))?
+                .name()
+                .to_string(),
+        ),
+        _ => None,
+    };
+    let start_date = req.start_date;
+    let end_date = start_date.and_then(|start| start.checked_add_days(Days::new(days - 1)));
+    let schedule = match (start_date, end_date) {
+        (Some(start), Some(end)) => format!(
+            "The trip lasts exactly {days} days, from {start} to {end}, so Day 1 is {start}. Take the day of the week and the season at the destination into account.",
+            start = start.format("%A, %B %-d, %Y"),
+            end = end.format("%A, %B %-d, %Y"),
+        ),
+        _ => format!("The trip lasts exactly {days} days."),
+    };
+
+    let prompt = render(&TripOutlinePrompt {
+        title: req.title.clone(),
+        destination: req.subtitle.clone(),
+        language: req.language.clone(),
+        budget: req.budget,
+        currency: currency.clone(),
+        schedule,
+        places,
+        traveller: traveller_profile(&user.preferences),
+    })
+    .await?;
+
+    // Roughly 200 tokens per place plus headings; `converse` caps it at the model's limit.
+    let max_tokens = (days * places * 200 + days * 50 + 200).min(i32::MAX as u64) as i32;
+    let trip_id = ObjectId::new();
+
+    let mut messages = vec![user_message(prompt.text)?];
+    let mut details = Vec::new();
+
+    for attempt in 0..=OUTLINE_FIX_ATTEMPTS {
+        let outline = converse(user, "trip_outline", model, messages.clone(), max_tokens).await?;
+        details = parse_outline(outline.clone(), trip_id, req.language.clone())?;
+
+        let Some(problem) = outline_problem(&details, days, places) else {
+            break;
+        };
+        if attempt == OUTLINE_FIX_ATTEMPTS {
+            tracing::error!("Trip outline still invalid after retries: {}", problem);
+            break;
         }
+
+        messages.push(assistant_message(outline)?);
+        let fix = render(&TripOutlineFixPrompt {
+            problem,
+            days,
+            places,
+        })
+        .await?;
+        messages.push(user_message(fix.text)?);
+    }
+
+    details.retain(|detail| (1..=days).contains(&detail.day));
+    for detail in details.iter_mut() {
+        detail.prompt = Some(prompt.reference.clone());
+    }
+    if details.is_empty() {
+        return Err(ServerFnError::new("Failed to generate a trip outline"));
     }
 
     let db_client = get_client().await;
@@ -307,21 +399,25 @@ pub async fn generate_trip_outline(
     let photo_url = fetch_cover(req.title.clone()).await?;
 
     let trip = Trip {
-        id: ObjectId::new(),
+        id: trip_id,
         user: user.id,
         title: req.title.clone(),
         subtitle: Some(req.subtitle.clone()),
         trip_type: Some(req.title.clone()),
         completed: false,
         cover: photo_url,
+        budget: req.budget as f64,
+        currency,
+        start_date,
+        end_date,
+        timezone,
+        prompt: Some(prompt.reference.clone()),
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
 
     trip_collection.insert_one(trip.clone()).await?;
 
-    let details = parse_outline(outline.clone(), trip.id, req.language)?;
-
     let details_collection = db.collection::<Detail>("details");
     details_collection.insert_many(details.clone()).await?;
 
@@ -334,6 +430,7 @@ pub async fn generate_trip_outline(
     })
 }
 
+#[cfg(feature = "server")]
 fn parse_outline(
     outline: String,
     trip_id: ObjectId,
@@ -342,8 +439,10 @@ fn parse_outline(
     let mut details = Vec::new();
 
     let day_re = Regex::new(r"### Day (\d+): (.*?)\n").unwrap();
-    let place_re =
-        Regex::new(r"#### Place (\d+): (.*?)\n\*\*Estimated Duration:\*\* (\d+) minutes").unwrap();
+    let place_re = Regex::new(
+        r"#### Place (\d+): (.*?)\n\*\*Estimated Duration:\*\* (\d+) minutes(?:[ \t]*\n\*\*Estimated Cost:\*\* [^\d\n]*([\d,]+(?:\.\d+)?))?",
+    )
+    .unwrap();
     let activity_re = Regex::new(r"\* (.+)").unwrap();
 
     let mut current_position = 0;
@@ -367,6 +466,10 @@ fn parse_outline(
             let place_number: i32 = place_caps[1].parse().unwrap_or(1);
             let place_name = &place_caps[2];
             let estimated_duration = place_caps[3].parse().unwrap_or(0);
+            let estimated_cost = place_caps
+                .get(4)
+                .and_then(|cost| cost.as_str().replace(',', "").parse().ok())
+                .unwrap_or(0.0);
 
             let place_start = place_pos + place_caps.get(0).unwrap().end();
             let next_place_pos = place_re
@@ -383,14 +486,25 @@ fn parse_outline(
                 .collect::<Vec<String>>()
                 .join("\n");
 
+            // Model output goes through the markdown renderer, which sanitizes it, like any
+            // other stored content.
+            let markdown = format!(
+                "Place {}: {}\n\n{}",
+                place_number, place_name, bullet_points
+            );
             details.push(Detail {
                 id: ObjectId::new(),
                 trip_id,
                 title: format!("Day {} - {}", day_number, day_title),
-                html: format!("Place {}: {}\n{}", place_number, place_name, bullet_points),
+                day: day_number as u64,
+                html: render_markdown(&markdown),
                 estimated_duration,
+                estimated_cost,
+                markdown,
                 language: language.clone(),
                 completed: false,
+                prompt: None,
+                generated_at: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             });
@@ -404,107 +518,40 @@ fn parse_outline(
 pub async fn generate_detail_content(
     req: GenerateDetailContentRequest,
 ) -> Result<SuccessResponse<String>, ServerFnError> {
-    let mut client = get_ai().await.lock().await;
-
-    let system_prompt = format!(
-        "
-        **System Prompt (SP):** You are writing detailed content for a trip detail.
-
-        **Prompt (P):** Write content for detail '{detail_title}' of the trip '{trip_title}' in {language}. Ensure clarity, detailed explanations, and structured markdown.
-
-        **Expected Format (EF):**
-        - detailed markdown format for this detail.
-
-        **Roleplay (RP):** Provide as much educational content as possible.
-        ",
-        detail_title = req.detail_title,
-        trip_title = req.trip_title,
-        language = req.language,
-    );
-
-    let mut markdown = "".to_string();
+    let caller = Caller::from_token(req.token).await?;
+    let (_, detail) = caller.detail(req.detail_id, Access::Modify).await?;
+    let user = caller.user;
+    require_verified(&user)?;
+    let model = resolve_model(&req.model).await?;
+    let _ = reserve_quota(&user, &[QuotaMetric::Tokens]).await?;
+
+    let (markdown, prompt) = generate_detail_markdown(
+        &user,
+        &model,
+        detail.id,
+        &req.detail_title,
+        &req.trip_title,
+        &req.language,
+        &user.preferences,
+    )
+    .await?;
+    let html = render_markdown(&markdown);
 
-    let mut html = "".to_string();
-
-    let response = client
-        .converse()
-        .model_id("anthropic.claude-3-haiku-20240307-v1:0")
-        .messages(
-            BedrockMessage::builder()
-                .role(ConversationRole::User)
-                .content(ContentBlock::Text(system_prompt.to_string()))
-                .build()
-                .map_err(|_| "failed to build message")
-                .unwrap(),
-        )
-        .send()
-        .await;
-
-    match response {
-        Ok(output) => {
-            markdown = get_converse_output_text(output)?;
-        }
-        Err(e) => {
-            return Err(e
-                .as_service_error()
-                .map(BedrockConverseError::from)
-                .unwrap_or_else(|| BedrockConverseError("Unknown service error".into()))
-                .into());
-        }
-    }
-
-    let content_prompt = format!(
-        "Generate a comprehensive HTML-formatted trip detail with examples, links and images, based on the outline: '{}' in {language}. \
-        Each section should be structured with appropriate HTML tags, including <h1> for the main title, \
-        <h2> for detail titles, <h3> for subheadings, and <p> for paragraphs. \
-        Include well-organized, readable content that aligns with the trip's outline, ensuring each section is \
-        clear and logically flows from one to the next. Avoid markdown format entirely, and provide inline HTML styling \
-        if necessary to enhance readability. The HTML content should be well-formatted, semantically correct, and \
-        cover all relevant subtopics in depth to create an engaging reading experience. \
-        Make sure to always return back with html formmatted text and not empty response.
-        ",
-        markdown.clone(),
-        language = req.language,
-    );
-
-    let response = client
-        .converse()
-        .model_id("anthropic.claude-3-haiku-20240307-v1:0")
-        .messages(
-            BedrockMessage::builder()
-                .role(ConversationRole::User)
-                .content(ContentBlock::Text(content_prompt.to_string()))
-                .build()
-                .map_err(|_| "failed to build message")
-                .unwrap(),
+    let db_client = get_client().await;
+    let db = db_client
+        .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    db.collection::<Detail>("details")
+        .update_one(
+            doc! { "_id": detail.id },
+            doc! { "$set": {
+                "markdown": markdown,
+                "html": html.clone(),
+                "prompt": bson::to_bson(&prompt)?,
+                "generatedAt": Utc::now(),
+                "updatedAt": Utc::now(),
+            } },
         )
-        .send()
-        .await;
-
-    match response {
-        Ok(output) => {
-            html = get_converse_output_text(output)
-                .map_err(ServerFnError::new)?
-                .trim_start_matches("```html")
-                .trim_end_matches("```")
-                .trim()
-                .to_string();
-        }
-        Err(e) => {
-            return Err(e
-                .as_service_error()
-                .map(BedrockConverseError::from)
-                .unwrap_or_else(|| BedrockConverseError("Unknown service error".into()))
-                .into());
-        }
-    }
-
-    html = update_detail_content(UpdateTripContentRequest {
-        trip_id: req.detail_id.to_string(),
-        new_content: html.clone(),
-    })
-    .await?
-    .data;
+        .await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
@@ -512,211 +559,197 @@ pub async fn generate_detail_content(
     })
 }
 
+/// Asks the model for a detail's markdown content in a single call.
+#[cfg(feature = "server")]
+async fn generate_detail_markdown(
+    user: &User,
+    model: &ModelInfo,
+    detail_id: ObjectId,
+    detail_title: &str,
+    trip_title: &str,
+    language: &str,
+    preferences: &UserPreferences,
+) -> Result<(String, PromptRef), ServerFnError> {
+    let weather = detail_weather_context(detail_id)
+        .await?
+        .map(|weather| {
+            format!(
+                "**Weather (W):** {weather} Schedule outdoor activities around it and suggest indoor alternatives if rain, storms or extreme heat are expected.\n"
+            )
+        })
+        .unwrap_or_default();
+
+    let prompt = render(&DetailMarkdownPrompt {
+        detail_title: detail_title.to_string(),
+        trip_title: trip_title.to_string(),
+        language: language.to_string(),
+        weather,
+        traveller: traveller_profile(preferences),
+    })
+    .await?;
+
+    let markdown = converse(
+        user,
+        "detail_markdown",
+        model,
+        vec![user_message(prompt.text)?],
+        model.max_output_tokens as i32,
+    )
+    .await?
+    .trim()
+    .trim_start_matches("```markdown")
+    .trim_end_matches("```")
+    .trim()
+    .to_string();
+
+    Ok((markdown, prompt.reference))
+}
+
 #[server]
-pub async fn fetch_analytics_data(
-    token: String,
-) -> Result<SuccessResponse<AnalyticsData>, ServerFnError> {
-    let user = auth(token)
-        .await
-        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+pub async fn update_detail_markdown(
+    req: UpdateDetailMarkdownRequest,
+) -> Result<SuccessResponse<Detail>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let (_, mut detail) = caller
+        .detail(parse_id(&req.detail_id, "detail")?, Access::Modify)
+        .await?;
 
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
-
-    let trips_collection = db.collection::<Trip>("trips");
     let details_collection = db.collection::<Detail>("details");
 
-    // Engagement Metrics
-    let total_trips = trips_collection
-        .count_documents(doc! { "user": user.id })
-        .await?;
-    let mut total_details = 0;
-    if total_trips > 0 {
-        total_details = details_collection.count_documents(doc! {}).await?;
-    }
-    let avg_details_per_trip = if total_trips > 0 {
-        total_details as f64 / total_trips as f64
-    } else {
-        0.0
-    };
+    detail.markdown = req.markdown.trim().to_string();
+    detail.html = render_markdown(&detail.markdown);
+    detail.updated_at = Utc::now();
 
-    let mut total_estimated_duration = 0.0;
-    let total_ai_details = total_details as u64;
-
-    // AI Usage Metrics
-    if total_trips > 0 {
-        let total_estimated_duration: u64 = details_collection
-            .aggregate(vec![
-                doc! { "$group": { "_id": null, "total_duration": { "$sum": "$estimated_duration" } } },
-            ])
-            .await?
-            .next()
-            .await
-            .and_then(|doc| doc.ok()?.get_i64("total_duration").ok())
-            .unwrap_or(0) as u64;
-    }
+    details_collection
+        .update_one(
+            doc! { "_id": detail.id },
+            doc! { "$set": {
+                "markdown": detail.markdown.clone(),
+                "html": detail.html.clone(),
+                "updatedAt": detail.updated_at,
+            } },
+        )
+        .await?;
 
-    let avg_gen_time = if total_ai_details > 0 {
-        total_estimated_duration as f64 / total_ai_details as f64
-    } else {
-        0.0
-    };
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: detail,
+    })
+}
 
-    let success_rate = 100.0;
+/// Describes the expected weather on the calendar day of a detail, if its trip is dated.
+#[cfg(feature = "server")]
+async fn detail_weather_context(detail_id: ObjectId) -> Result<Option<String>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
 
-    // Trending Topic
-    let trending_topic = trips_collection
-        .aggregate(vec![
-            doc! { "$match": { "user_id": user.id } },
-            doc! { "$group": { "_id": "$title", "count": { "$sum": 1 } } },
-            doc! { "$sort": { "count": -1 } },
-            doc! { "$limit": 1 },
-        ])
+    let Some(detail) = db
+        .collection::<Detail>("details")
+        .find_one(doc! { "_id": detail_id })
         .await?
-        .next()
-        .await
-        .and_then(|doc| doc.ok()?.get_str("_id").ok().map(|s| s.to_string()))
-        .unwrap_or_else(|| "Unknown".to_string());
-
-    // Projected Growth
-    let monthly_trip_growth = trips_collection
-        .aggregate(vec![
-            doc! { "$match": { "user_id": user.id } },
-            doc! { "$group": {
-                "_id": { "month": { "$month": "$created_at" }, "year": { "$year": "$created_at" } },
-                "count": { "$sum": 1 }
-            }},
-            doc! { "$sort": { "_id.year": 1, "_id.month": 1 } },
-        ])
-        .await?;
+    else {
+        return Ok(None);
+    };
+    let Some(trip) = db
+        .collection::<Trip>("trips")
+        .find_one(doc! { "_id": detail.trip_id })
+        .await?
+    else {
+        return Ok(None);
+    };
+    let Some(date) = trip.date_for_day(detail.day) else {
+        return Ok(None);
+    };
 
-    let growth_rates: Vec<f64> = monthly_trip_growth
-        .collect::<Vec<_>>()
+    Ok(weather_for_trip(&trip)
         .await
-        .windows(2)
-        .filter_map(|window| {
-            if let (Ok(prev), Ok(curr)) = (window[0].as_ref(), window[1].as_ref()) {
-                let prev_count = prev
-                    .get_document("count")
-                    .unwrap_or(&doc! {})
-                    .get_i32("count")
-                    .unwrap_or(1) as f64;
-                let curr_count = curr
-                    .get_document("count")
-                    .unwrap_or(&doc! {})
-                    .get_i32("count")
-                    .unwrap_or(1) as f64;
-                Some(((curr_count - prev_count) / prev_count) * 100.0)
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    let projected_growth = growth_rates.last().cloned().unwrap_or(0.0);
-
-    Ok(SuccessResponse {
-        status: "success".into(),
-        data: AnalyticsData {
-            engagement: EngagementStats {
-                total_trips,
-                total_details,
-                avg_details_per_trip,
-            },
-            ai_usage: AIUsageStats {
-                total_ai_details,
-                avg_gen_time,
-                success_rate,
-            },
-            predictions: PredictiveStats {
-                trending_genre: trending_topic,
-                projected_growth,
-            },
-        },
-    })
+        .into_iter()
+        .find(|day| day.date == date)
+        .map(|day| {
+            format!(
+                "Expected weather on {}: {}.",
+                date.format("%A, %B %-d, %Y"),
+                day.describe()
+            )
+        }))
 }
 
 #[server]
 pub async fn get_details_for_trip(
     req: GetDetailContentRequest,
 ) -> Result<SuccessResponse<Vec<Detail>>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Read)
+        .await?;
+
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let trip_collection = db.collection::<Detail>("details");
 
-    let trip_object_id =
-        ObjectId::parse_str(&req.trip_id).map_err(|_| ServerFnError::new("Invalid trip ID"))?;
-
     let mut details = trip_collection
-        .find(doc! { "trip_id": trip_object_id })
+        .find(doc! { "trip_id": trip.id })
         .await?
         .try_collect::<Vec<Detail>>()
         .await?;
 
+    // Missing content is generated on the owner's behalf and counts against their quota;
+    // anyone else, such as an admin, sees the trip as it is.
+    let pending = details
+        .iter()
+        .any(|detail| detail.html.is_empty() && detail.markdown.is_empty());
+    let generate = pending && can_access_trip(&caller.user, &trip, Access::Modify);
+    let model = if generate {
+        require_verified(&caller.user)?;
+        let model = resolve_model("").await?;
+        let _ = reserve_quota(&caller.user, &[QuotaMetric::Tokens]).await?;
+        Some(model)
+    } else {
+        None
+    };
+
     for detail in details.iter_mut() {
-        if detail.html.is_empty() {
-            let markdown_content = detail.html.clone();
-
-            let content_prompt = format!(
-                "Generate a comprehensive HTML-formatted trip trip with examples, links and images, based on the outline: '{}' in {language}. \
-                Each section should be structured with appropriate HTML tags, including <h1> for the main title, \
-                <h2> for trip titles, <h3> for subheadings, and <p> for paragraphs. \
-                Include well-organized, readable content that aligns with the trip's outline, ensuring each section is \
-                clear and logically flows from one to the next. Avoid markdown format entirely, and provide inline HTML styling \
-                if necessary to enhance readability. The HTML content should be well-formatted, semantically correct, and \
-                cover all relevant subtopics in depth to create an engaging reading experience. \
-                Make sure to always return back with html formmatted text and not empty response.",
-                markdown_content,
-                language = detail.language,
-            );
+        if !detail.html.is_empty() {
+            continue;
+        }
 
-            let mut ai_client = get_ai().await.lock().await;
-
-            let mut html_content = "".to_string();
-
-            let response = ai_client
-                .converse()
-                .model_id("anthropic.claude-3-haiku-20240307-v1:0")
-                .messages(
-                    BedrockMessage::builder()
-                        .role(ConversationRole::User)
-                        .content(ContentBlock::Text(content_prompt.to_string()))
-                        .build()
-                        .map_err(|_| "failed to build message")
-                        .unwrap(),
-                )
-                .send()
-                .await;
-
-            match response {
-                Ok(output) => {
-                    html_content = get_converse_output_text(output)
-                        .map_err(ServerFnError::new)?
-                        .trim_start_matches("```html")
-                        .trim_end_matches("```")
-                        .trim()
-                        .to_string();
-                }
-                Err(e) => {
-                    return Err(e
-                        .as_service_error()
-                        .map(BedrockConverseError::from)
-                        .unwrap_or_else(|| BedrockConverseError("Unknown service error".into()))
-                        .into());
-                }
-            }
-
-            trip_collection
-                .update_one(
-                    doc! { "_id": detail.id },
-                    doc! { "$set": { "html": html_content.clone(), "updatedAt": Utc::now() } },
-                )
-                .await?;
-
-            detail.html = html_content;
+        if detail.markdown.is_empty() {
+            let Some(model) = &model else {
+                continue;
+            };
+            let (markdown, prompt) = generate_detail_markdown(
+                &caller.user,
+                model,
+                detail.id,
+                &detail.title,
+                &trip.title,
+                &detail.language,
+                &caller.user.preferences,
+            )
+            .await?;
+            detail.markdown = markdown;
+            detail.prompt = Some(prompt);
+            detail.generated_at = Some(Utc::now());
         }
+        detail.html = render_markdown(&detail.markdown);
+
+        trip_collection
+            .update_one(
+                doc! { "_id": detail.id },
+                doc! { "$set": {
+                    "markdown": detail.markdown.clone(),
+                    "html": detail.html.clone(),
+                    "prompt": bson::to_bson(&detail.prompt)?,
+                    "generatedAt": detail.generated_at,
+                    "updatedAt": Utc::now(),
+                } },
+            )
+            .await?;
     }
 
     Ok(SuccessResponse {
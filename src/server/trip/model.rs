@@ -1,10 +1,15 @@
 #![allow(non_snake_case)]
 
-use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use crate::server::common::model::PromptRef;
+use bson::{
+    oid::ObjectId,
+    serde_helpers::{chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional},
+};
 use chrono::prelude::*;
+use chrono::Days;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
 pub struct Trip {
     #[serde(rename = "_id")]
     pub id: ObjectId,
@@ -16,24 +21,73 @@ pub struct Trip {
     #[serde(rename = "mainTopic")]
     pub completed: bool,
     pub cover: Option<String>,
+    #[serde(default)]
+    pub budget: f64,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub prompt: Option<PromptRef>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+impl Trip {
+    /// Calendar date of the given 1-based trip day, if the trip is dated.
+    pub fn date_for_day(&self, day: u64) -> Option<NaiveDate> {
+        let start = self.start_date?;
+        let date = start.checked_add_days(Days::new(day.checked_sub(1)?))?;
+        match self.end_date {
+            Some(end) if date > end => None,
+            _ => Some(date),
+        }
+    }
+
+    pub fn days(&self) -> Option<u64> {
+        let (start, end) = (self.start_date?, self.end_date?);
+        u64::try_from((end - start).num_days() + 1).ok()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Detail {
     #[serde(rename = "_id")]
     pub id: ObjectId,
     pub trip_id: ObjectId,
     pub title: String,
+    #[serde(default)]
+    pub day: u64,
+    /// Source of truth for the content; `html` is rendered from it on the server.
+    #[serde(default)]
+    pub markdown: String,
     pub html: String,
     pub estimated_duration: u64,
+    #[serde(default)]
+    pub estimated_cost: f64,
     pub language: String,
     pub completed: bool,
+    #[serde(default)]
+    pub prompt: Option<PromptRef>,
+    /// When the model last wrote the content; `None` until it has.
+    #[serde(
+        default,
+        with = "chrono_datetime_as_bson_datetime_optional",
+        rename = "generatedAt"
+    )]
+    pub generated_at: Option<DateTime<Utc>>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
+
+pub fn default_currency() -> String {
+    "USD".to_string()
+}
@@ -1,4 +1,5 @@
 use bson::oid::ObjectId;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,12 +13,14 @@ pub struct StoreTripRequest {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateTripContentRequest {
+    pub token: String,
     pub trip_id: String,
     pub new_content: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CompleteTripRequest {
+    pub token: String,
     pub trip_id: ObjectId,
 }
 
@@ -32,14 +35,18 @@ pub struct GenerateTripRequest {
     pub subtitle: String,
     pub token: String,
     pub model: String,
-    pub subtopics: u64,
+    pub budget: u64,
+    pub currency: String,
     pub details: u64,
     pub language: String,
     pub max_length: u64,
+    pub start_date: Option<NaiveDate>,
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GenerateDetailContentRequest {
+    pub token: String,
     pub detail_title: String,
     pub detail_id: ObjectId,
     pub trip_title: String,
@@ -61,5 +68,13 @@ pub struct AIRequest {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GetDetailContentRequest {
+    pub token: String,
     pub trip_id: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateDetailMarkdownRequest {
+    pub token: String,
+    pub detail_id: String,
+    pub markdown: String,
+}
@@ -0,0 +1,31 @@
+#![allow(unused_imports)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use dioxus::prelude::*;
+
+use crate::server::auth::controller::auth;
+use crate::server::common::response::SuccessResponse;
+use crate::server::trip::model::Trip;
+use crate::server::weather::model::DayWeather;
+use crate::server::weather::request::GetTripWeatherRequest;
+#[cfg(feature = "server")]
+use {
+    crate::authz::{parse_id, Access, Caller},
+    crate::weather::weather_for_trip,
+};
+
+#[server]
+pub async fn get_trip_weather(
+    req: GetTripWeatherRequest,
+) -> Result<SuccessResponse<Vec<DayWeather>>, ServerFnError> {
+    let caller = Caller::from_token(req.token).await?;
+    let trip = caller
+        .trip(parse_id(&req.trip_id, "trip")?, Access::Read)
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: weather_for_trip(&trip).await,
+    })
+}
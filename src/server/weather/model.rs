@@ -0,0 +1,36 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum WeatherSource {
+    Forecast,
+    Climate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DayWeather {
+    pub date: NaiveDate,
+    pub source: WeatherSource,
+    pub summary: String,
+    pub temp_max: f64,
+    pub temp_min: f64,
+    pub precipitation_mm: f64,
+    pub precipitation_probability: Option<u8>,
+}
+
+impl DayWeather {
+    /// One-line description used in prompts, e.g. `Rain showers, 18–24°C, 6.5 mm (80%)`.
+    pub fn describe(&self) -> String {
+        let mut description = format!(
+            "{}, {:.0}–{:.0}°C, {:.1} mm precipitation",
+            self.summary, self.temp_min, self.temp_max, self.precipitation_mm
+        );
+        if let Some(probability) = self.precipitation_probability {
+            description.push_str(&format!(" ({}% chance)", probability));
+        }
+        if self.source == WeatherSource::Climate {
+            description.push_str(" [typical for the season]");
+        }
+        description
+    }
+}
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetTripWeatherRequest {
+    pub token: String,
+    pub trip_id: String,
+}
@@ -0,0 +1,94 @@
+use crate::server::auth::controller::{about_me, refresh_session};
+use crate::server::auth::response::AuthResponse;
+use dioxus::prelude::*;
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
+use regex::Regex;
+
+const ACCESS_TOKEN_KEY: &str = "jwt";
+const REFRESH_TOKEN_KEY: &str = "refresh_token";
+/// Access tokens live for 15 minutes; renew them well before that.
+#[allow(dead_code)]
+const REFRESH_INTERVAL_MS: u32 = 10 * 60 * 1000;
+
+fn extract_token(cookie_str: &str) -> Option<String> {
+    let re = Regex::new(r"token=([^;]+)").unwrap();
+    re.captures(cookie_str).map(|caps| caps[1].to_string())
+}
+
+/// Keeps the tokens from a login or refresh. The refresh token survives browser restarts only
+/// when `remember` is set. Returns the access token.
+pub fn store_tokens(auth: &AuthResponse, remember: bool) -> Option<String> {
+    let token = extract_token(&auth.token)?;
+    SessionStorage::set(ACCESS_TOKEN_KEY, token.clone()).ok()?;
+    if remember {
+        LocalStorage::set(REFRESH_TOKEN_KEY, auth.refresh_token.clone()).ok()?;
+    } else {
+        SessionStorage::set(REFRESH_TOKEN_KEY, auth.refresh_token.clone()).ok()?;
+    }
+    Some(token)
+}
+
+pub fn clear_tokens() {
+    SessionStorage::delete(ACCESS_TOKEN_KEY);
+    SessionStorage::delete(REFRESH_TOKEN_KEY);
+    LocalStorage::delete(REFRESH_TOKEN_KEY);
+}
+
+/// Exchanges the stored refresh token for new tokens.
+pub async fn refresh_tokens() -> Option<String> {
+    let (refresh_token, remember) = match LocalStorage::get::<String>(REFRESH_TOKEN_KEY) {
+        Ok(token) => (token, true),
+        Err(_) => (
+            SessionStorage::get::<String>(REFRESH_TOKEN_KEY).ok()?,
+            false,
+        ),
+    };
+
+    let response = refresh_session(refresh_token).await.ok()?;
+    store_tokens(&response.data, remember)
+}
+
+/// The stored access token if it is still accepted, otherwise a freshly refreshed one. Clears
+/// the stored tokens when neither works.
+pub async fn restore_session() -> Option<String> {
+    let token: String = SessionStorage::get(ACCESS_TOKEN_KEY).unwrap_or_default();
+    if !token.is_empty() && about_me(token.clone()).await.is_ok() {
+        return Some(token);
+    }
+    let token = refresh_tokens().await;
+    if token.is_none() {
+        clear_tokens();
+    }
+    token
+}
+
+/// Renews the access token in the background so long sessions are not sent back to `/login`.
+#[allow(unused_mut, unused_variables)]
+pub fn use_token_refresh(mut user_token: Signal<String>) {
+    client! {
+        let mut eval = use_hook(|| {
+            eval(&format!(
+                r#"
+                setInterval(() => {{
+                    dioxus.send("");
+                }}, {})
+                "#,
+                REFRESH_INTERVAL_MS
+            ))
+        });
+
+        use_hook(|| {
+            spawn(async move {
+                loop {
+                    let _ = eval.recv().await;
+                    if user_token().is_empty() {
+                        continue;
+                    }
+                    if let Some(token) = refresh_tokens().await {
+                        user_token.set(token);
+                    }
+                }
+            })
+        });
+    }
+}
@@ -0,0 +1,303 @@
+//! Storage for user uploads such as avatars, served back under `/media/<key>`.
+
+use crate::db::get_client;
+use axum::extract::Path;
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use bson::{doc, oid::ObjectId, spec::BinarySubtype, Binary};
+use chrono::prelude::*;
+use dioxus_logger::tracing;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::PathBuf;
+use tokio::sync::OnceCell;
+
+const DEFAULT_MEDIA_DIR: &str = "media";
+pub const MEDIA_PREFIX: &str = "/media/";
+
+/// A stored file and its MIME type.
+pub struct StoredObject {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+pub(crate) trait Storage {
+    async fn put(&self, key: &str, object: StoredObject) -> anyhow::Result<()>;
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredObject>>;
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// Keys are generated by the server, so anything outside this shape is refused rather than
+/// risking a path outside the storage root.
+fn valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('/')
+        && !key
+            .split('/')
+            .any(|segment| segment.is_empty() || segment.starts_with('.'))
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.'))
+}
+
+fn content_type_for(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The format and size of an uploaded image.
+pub struct ImageInfo {
+    pub content_type: &'static str,
+    pub extension: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Identifies a PNG or JPEG from its contents and reads its dimensions from the header.
+pub fn inspect_image(bytes: &[u8]) -> Option<ImageInfo> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") && bytes.get(12..16)? == b"IHDR" {
+        return Some(ImageInfo {
+            content_type: "image/png",
+            extension: "png",
+            width: u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?),
+            height: u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?),
+        });
+    }
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    // Walk the JPEG segments up to the start-of-frame marker, which holds the dimensions.
+    let mut offset = 2;
+    loop {
+        if *bytes.get(offset)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(offset + 1)?;
+        let length = u16::from_be_bytes(bytes.get(offset + 2..offset + 4)?.try_into().ok()?);
+        if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let frame = bytes.get(offset + 5..offset + 9)?;
+            return Some(ImageInfo {
+                content_type: "image/jpeg",
+                extension: "jpg",
+                width: u16::from_be_bytes([frame[2], frame[3]]).into(),
+                height: u16::from_be_bytes([frame[0], frame[1]]).into(),
+            });
+        }
+        offset += 2 + usize::from(length);
+    }
+}
+
+/// Decodes an image, crops it to a centered square of `size` pixels and re-encodes it as JPEG,
+/// which also drops any metadata the original carried.
+pub fn square_jpeg(bytes: &[u8], size: u32) -> anyhow::Result<Vec<u8>> {
+    let image = image::load_from_memory(bytes)?
+        .resize_to_fill(size, size, FilterType::Lanczos3)
+        .to_rgb8();
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, 90).encode_image(&image)?;
+    Ok(jpeg)
+}
+
+/// Files in `MEDIA_DIR` on local disk. Fine for a single server.
+pub struct DiskStorage {
+    dir: PathBuf,
+}
+
+impl Storage for DiskStorage {
+    async fn put(&self, key: &str, object: StoredObject) -> anyhow::Result<()> {
+        anyhow::ensure!(valid_key(key), "invalid storage key");
+        let path = self.dir.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, object.bytes).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredObject>> {
+        if !valid_key(key) {
+            return Ok(None);
+        }
+        match tokio::fs::read(self.dir.join(key)).await {
+            Ok(bytes) => Ok(Some(StoredObject {
+                content_type: content_type_for(key).to_string(),
+                bytes,
+            })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(valid_key(key), "invalid storage key");
+        match tokio::fs::remove_file(self.dir.join(key)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct MediaDocument {
+    #[serde(rename = "_id")]
+    key: String,
+    #[serde(rename = "contentType")]
+    content_type: String,
+    data: Binary,
+    #[serde(
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime",
+        rename = "createdAt"
+    )]
+    created_at: DateTime<Utc>,
+}
+
+/// Files in the `media` collection, shared by every server using the same database.
+pub struct MongoStorage;
+
+impl MongoStorage {
+    async fn collection(&self) -> mongodb::Collection<MediaDocument> {
+        let client = get_client().await;
+        client
+            .database(&env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."))
+            .collection::<MediaDocument>("media")
+    }
+}
+
+impl Storage for MongoStorage {
+    async fn put(&self, key: &str, object: StoredObject) -> anyhow::Result<()> {
+        anyhow::ensure!(valid_key(key), "invalid storage key");
+        let document = MediaDocument {
+            key: key.to_string(),
+            content_type: object.content_type,
+            data: Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: object.bytes,
+            },
+            created_at: Utc::now(),
+        };
+        self.collection()
+            .await
+            .replace_one(doc! { "_id": key }, document)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredObject>> {
+        Ok(self
+            .collection()
+            .await
+            .find_one(doc! { "_id": key })
+            .await?
+            .map(|document| StoredObject {
+                content_type: document.content_type,
+                bytes: document.data.bytes,
+            }))
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.collection()
+            .await
+            .delete_one(doc! { "_id": key })
+            .await?;
+        Ok(())
+    }
+}
+
+/// The storage selected by `MEDIA_STORAGE`: `disk` (the default) or `mongo`.
+pub enum ConfiguredStorage {
+    Disk(DiskStorage),
+    Mongo(MongoStorage),
+}
+
+impl ConfiguredStorage {
+    pub fn from_env() -> Self {
+        match env::var("MEDIA_STORAGE").as_deref() {
+            Ok("mongo") => Self::Mongo(MongoStorage),
+            _ => Self::Disk(DiskStorage {
+                dir: env::var("MEDIA_DIR")
+                    .unwrap_or_else(|_| DEFAULT_MEDIA_DIR.to_string())
+                    .into(),
+            }),
+        }
+    }
+}
+
+impl Storage for ConfiguredStorage {
+    async fn put(&self, key: &str, object: StoredObject) -> anyhow::Result<()> {
+        match self {
+            Self::Disk(storage) => storage.put(key, object).await,
+            Self::Mongo(storage) => storage.put(key, object).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<StoredObject>> {
+        match self {
+            Self::Disk(storage) => storage.get(key).await,
+            Self::Mongo(storage) => storage.get(key).await,
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Disk(storage) => storage.delete(key).await,
+            Self::Mongo(storage) => storage.delete(key).await,
+        }
+    }
+}
+
+static STORAGE: OnceCell<ConfiguredStorage> = OnceCell::const_new();
+
+pub async fn get_storage() -> &'static ConfiguredStorage {
+    STORAGE
+        .get_or_init(|| async { ConfiguredStorage::from_env() })
+        .await
+}
+
+/// The storage key behind a `/media/...` URL, if `url` points into storage.
+pub fn key_from_url(url: &str) -> Option<&str> {
+    url.strip_prefix(MEDIA_PREFIX).filter(|key| valid_key(key))
+}
+
+/// Deletes `user`'s avatar: the object `photo` points to, and any avatar of theirs left in
+/// the `media` collection.
+pub async fn delete_avatars(user: ObjectId, photo: &str) -> anyhow::Result<()> {
+    if let Some(key) = key_from_url(photo) {
+        get_storage().await.delete(key).await?;
+    }
+    MongoStorage
+        .collection()
+        .await
+        .delete_many(doc! { "_id": { "$regex": format!("^avatars/{}-", user.to_hex()) } })
+        .await?;
+    Ok(())
+}
+
+/// Serves stored files. Keys are never reused, so responses can be cached for good.
+pub async fn serve_media(Path(key): Path<String>) -> Response {
+    match get_storage().await.get(&key).await {
+        Ok(Some(object)) => (
+            [
+                (CONTENT_TYPE, object.content_type),
+                (
+                    CACHE_CONTROL,
+                    "public, max-age=31536000, immutable".to_string(),
+                ),
+                (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            ],
+            object.bytes,
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!("Failed to read media {}: {}", key, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
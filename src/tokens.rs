@@ -0,0 +1,357 @@
+use crate::server::auth::model::{TokenClaims, User};
+use crate::server::auth::response::AuthResponse;
+use axum::http::header::USER_AGENT;
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use bson::{doc, oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use chrono::Duration;
+use dioxus::prelude::{server_context, ServerFnError};
+use jsonwebtoken::{encode, DecodingKey, EncodingKey, Header, Validation};
+use mongodb::Database;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+
+const ACCESS_TOKEN_MINUTES: i64 = 15;
+/// Password reset links stop working after this long.
+const PASSWORD_RESET_MINUTES: i64 = 60;
+/// Sessions expire after this long without a refresh.
+const REFRESH_TOKEN_DAYS: i64 = 30;
+/// A rotated-out refresh token presented this soon after rotation is taken to be a concurrent
+/// refresh from another tab rather than a stolen token.
+const REUSE_GRACE_SECS: i64 = 30;
+
+/// A signed-in device. Holds the hash of its current refresh token, which is replaced on
+/// every refresh.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Session {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    #[serde(rename = "refreshHash")]
+    pub refresh_hash: String,
+    /// Hash of the refresh token this one replaced; presenting it again means it was stolen.
+    #[serde(default, rename = "previousHash")]
+    pub previous_hash: Option<String>,
+    /// The latest access token issued for this session, denylisted when it is revoked.
+    #[serde(rename = "accessJti")]
+    pub access_jti: String,
+    pub device: String,
+    #[serde(rename = "userAgent")]
+    pub user_agent: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "lastUsedAt")]
+    pub last_used_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(default, rename = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// An access token that must be refused until it expires on its own.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RevokedToken {
+    pub jti: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A single-use password reset link. Only the hash of its secret is stored.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PasswordReset {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    #[serde(rename = "tokenHash")]
+    pub token_hash: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(default, rename = "usedAt")]
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+pub fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buf);
+    hex::encode(buf)
+}
+
+pub fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// A short label such as "Firefox on Linux" for a `User-Agent` header.
+fn describe_device(user_agent: &str) -> String {
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .into_iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map_or("Unknown browser", |(_, name)| name);
+    let os = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("CrOS", "ChromeOS"),
+        ("Linux", "Linux"),
+    ]
+    .into_iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map_or("unknown OS", |(_, name)| name);
+
+    format!("{} on {}", browser, os)
+}
+
+async fn request_user_agent() -> String {
+    server_context()
+        .request_parts()
+        .await
+        .headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub fn decode_claims(token: &str) -> Result<TokenClaims, ServerFnError> {
+    jsonwebtoken::decode::<TokenClaims>(
+        token,
+        &DecodingKey::from_secret(
+            env::var("JWT_SECRET")
+                .expect("JWT_SECRET must be set")
+                .as_ref(),
+        ),
+        &Validation::default(),
+    )
+    .map(|data| data.claims)
+    .map_err(|_| ServerFnError::new("Invalid token"))
+}
+
+pub async fn is_revoked(db: &Database, jti: &str) -> Result<bool, ServerFnError> {
+    Ok(db
+        .collection::<RevokedToken>("revoked_tokens")
+        .find_one(doc! { "jti": jti })
+        .await?
+        .is_some())
+}
+
+/// Signs a new access token for `session` and returns it with its `jti`.
+fn issue_access_token(
+    user: ObjectId,
+    session: ObjectId,
+) -> Result<(String, String), ServerFnError> {
+    let now = Utc::now();
+    let jti = random_hex(16);
+    let claims = TokenClaims {
+        sub: user.to_hex(),
+        iat: now.timestamp() as usize,
+        exp: (now + Duration::minutes(ACCESS_TOKEN_MINUTES)).timestamp() as usize,
+        jti: jti.clone(),
+        sid: session.to_hex(),
+    };
+
+    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret.as_ref()),
+    )?;
+    Ok((token, jti))
+}
+
+fn auth_response(access_token: String, session: ObjectId, secret: &str) -> AuthResponse {
+    let cookie = Cookie::build(("token", access_token))
+        .path("/")
+        .max_age(time::Duration::minutes(ACCESS_TOKEN_MINUTES))
+        .same_site(SameSite::Lax)
+        .http_only(true);
+
+    AuthResponse {
+        token: cookie.to_string(),
+        refresh_token: format!("{}.{}", session.to_hex(), secret),
+        two_factor_challenge: None,
+    }
+}
+
+/// Opens a session for `user` on the requesting device.
+pub async fn start_session(db: &Database, user: &User) -> Result<AuthResponse, ServerFnError> {
+    let now = Utc::now();
+    let id = ObjectId::new();
+    let secret = random_hex(32);
+    let (access_token, jti) = issue_access_token(user.id, id)?;
+    let user_agent = request_user_agent().await;
+
+    let session = Session {
+        id,
+        user: user.id,
+        refresh_hash: hash_secret(&secret),
+        previous_hash: None,
+        access_jti: jti,
+        device: describe_device(&user_agent),
+        user_agent,
+        created_at: now,
+        last_used_at: now,
+        expires_at: now + Duration::days(REFRESH_TOKEN_DAYS),
+        revoked_at: None,
+    };
+    db.collection::<Session>("sessions")
+        .insert_one(session)
+        .await?;
+
+    Ok(auth_response(access_token, id, &secret))
+}
+
+/// Exchanges a refresh token for a new access token and a new refresh token.
+///
+/// Presenting a refresh token that was already rotated out revokes the whole session, since
+/// either the client or an attacker is holding a stolen copy.
+pub async fn rotate_session(
+    db: &Database,
+    refresh_token: &str,
+) -> Result<AuthResponse, ServerFnError> {
+    let invalid = || ServerFnError::new("Invalid refresh token");
+    let (session_id, secret) = refresh_token.split_once('.').ok_or_else(invalid)?;
+    let session_id = ObjectId::parse_str(session_id).map_err(|_| invalid())?;
+
+    let collection = db.collection::<Session>("sessions");
+    let session = collection
+        .find_one(doc! { "_id": session_id, "revokedAt": null })
+        .await?
+        .ok_or_else(invalid)?;
+
+    let hash = hash_secret(secret);
+    if session.previous_hash.as_deref() == Some(hash.as_str()) {
+        if Utc::now() - session.last_used_at < Duration::seconds(REUSE_GRACE_SECS) {
+            return Err(invalid());
+        }
+        revoke_sessions(db, doc! { "_id": session.id }).await?;
+        return Err(ServerFnError::new("Session revoked"));
+    }
+    if hash != session.refresh_hash || session.expires_at < Utc::now() {
+        return Err(invalid());
+    }
+
+    let user = db
+        .collection::<User>("users")
+        .find_one(doc! { "_id": session.user })
+        .await?
+        .ok_or_else(invalid)?;
+    if user.suspended {
+        return Err(ServerFnError::new("Account suspended"));
+    }
+
+    let now = Utc::now();
+    let secret = random_hex(32);
+    let (access_token, jti) = issue_access_token(user.id, session.id)?;
+    let updated = collection
+        .update_one(
+            doc! { "_id": session.id, "refreshHash": &session.refresh_hash },
+            doc! { "$set": {
+                "refreshHash": hash_secret(&secret),
+                "previousHash": &session.refresh_hash,
+                "accessJti": jti,
+                "lastUsedAt": now,
+                "expiresAt": now + Duration::days(REFRESH_TOKEN_DAYS),
+            } },
+        )
+        .await?;
+    // Another request rotated the same token first.
+    if updated.modified_count == 0 {
+        return Err(invalid());
+    }
+
+    Ok(auth_response(access_token, session.id, &secret))
+}
+
+/// Revokes every active session matching `filter`, which stops every access token issued for
+/// them, and denylists their latest access tokens. Returns how many sessions were revoked.
+pub async fn revoke_sessions(db: &Database, filter: bson::Document) -> Result<u64, ServerFnError> {
+    let collection = db.collection::<Session>("sessions");
+    let mut filter = filter;
+    filter.insert("revokedAt", bson::Bson::Null);
+
+    let mut cursor = collection.find(filter.clone()).await?;
+    let mut revoked = Vec::new();
+    while cursor.advance().await? {
+        let session = cursor.deserialize_current()?;
+        revoked.push(RevokedToken {
+            jti: session.access_jti,
+            expires_at: Utc::now() + Duration::minutes(ACCESS_TOKEN_MINUTES),
+        });
+    }
+    if revoked.is_empty() {
+        return Ok(0);
+    }
+
+    db.collection::<RevokedToken>("revoked_tokens")
+        .insert_many(revoked)
+        .await?;
+    let result = collection
+        .update_many(
+            filter,
+            doc! { "$set": { "revokedAt": bson::to_bson(&Utc::now())? } },
+        )
+        .await?;
+    Ok(result.modified_count)
+}
+
+/// Stores a new reset link for `user` and returns its token, shaped `resetid.secret`.
+pub async fn create_password_reset(db: &Database, user: &User) -> Result<String, ServerFnError> {
+    let now = Utc::now();
+    let secret = random_hex(32);
+    let reset = PasswordReset {
+        id: ObjectId::new(),
+        user: user.id,
+        token_hash: hash_secret(&secret),
+        created_at: now,
+        expires_at: now + Duration::minutes(PASSWORD_RESET_MINUTES),
+        used_at: None,
+    };
+    db.collection::<PasswordReset>("password_resets")
+        .insert_one(&reset)
+        .await?;
+
+    Ok(format!("{}.{}", reset.id.to_hex(), secret))
+}
+
+/// Marks a reset token as used and returns the user it was issued to. Fails for unknown,
+/// expired or already used tokens; every other pending link for the user is voided as well.
+pub async fn consume_password_reset(db: &Database, token: &str) -> Result<ObjectId, ServerFnError> {
+    let invalid = || ServerFnError::new("This link is invalid or has expired");
+    let (reset_id, secret) = token.split_once('.').ok_or_else(invalid)?;
+    let reset_id = ObjectId::parse_str(reset_id).map_err(|_| invalid())?;
+
+    let collection = db.collection::<PasswordReset>("password_resets");
+    let now = bson::to_bson(&Utc::now())?;
+    // Matching on the hash and `usedAt` in one update keeps the token single-use under races.
+    let reset = collection
+        .find_one_and_update(
+            doc! {
+                "_id": reset_id,
+                "tokenHash": hash_secret(secret),
+                "usedAt": null,
+                "expiresAt": { "$gt": Utc::now() },
+            },
+            doc! { "$set": { "usedAt": now.clone() } },
+        )
+        .await?
+        .ok_or_else(invalid)?;
+    collection
+        .update_many(
+            doc! { "user": reset.user, "usedAt": null },
+            doc! { "$set": { "usedAt": now } },
+        )
+        .await?;
+
+    Ok(reset.user)
+}
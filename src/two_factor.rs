@@ -0,0 +1,257 @@
+//! TOTP two-factor authentication (RFC 6238) with single-use backup codes.
+
+use crate::server::auth::model::{TwoFactor, User};
+use crate::tokens::{hash_secret, random_hex};
+use bson::{doc, oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use chrono::Duration;
+use data_encoding::BASE32_NOPAD;
+use dioxus::prelude::ServerFnError;
+use hmac::{Hmac, Mac};
+use mongodb::Database;
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+use rand_core::{OsRng, RngCore};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+
+const ISSUER: &str = "Tripper";
+const STEP_SECS: i64 = 30;
+const DIGITS: u32 = 6;
+/// Codes from this many steps either side of now are accepted, to allow for clock drift.
+const SKEW_STEPS: i64 = 1;
+const BACKUP_CODES: usize = 10;
+/// How long the second sign-in step may take after the password was accepted.
+const CHALLENGE_MINUTES: i64 = 5;
+/// Wrong codes allowed per sign-in before the password has to be entered again.
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+/// A sign-in whose password was accepted and that now waits for a second factor.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LoginChallenge {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    #[serde(rename = "tokenHash")]
+    pub token_hash: String,
+    pub attempts: i32,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+/// How a code checked out against the user's settings.
+enum SecondFactor {
+    Totp { step: u64 },
+    Backup { hash: String },
+}
+
+/// A new random secret, base32-encoded as authenticator apps expect.
+pub fn generate_secret() -> String {
+    let mut buf = [0u8; 20];
+    OsRng.fill_bytes(&mut buf);
+    BASE32_NOPAD.encode(&buf)
+}
+
+/// The `otpauth://` URI that authenticator apps scan to add the account.
+pub fn otpauth_uri(secret: &str, email: &str) -> String {
+    let mut url = Url::parse("otpauth://totp/").expect("valid otpauth URL");
+    url.set_path(&format!("{}:{}", ISSUER, email));
+    url.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", ISSUER)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &DIGITS.to_string())
+        .append_pair("period", &STEP_SECS.to_string());
+    url.into()
+}
+
+/// `uri` as an SVG QR code, rendered here so the page needs no script to draw it.
+pub fn qr_code_svg(uri: &str) -> Result<String, ServerFnError> {
+    let code = QrCode::with_error_correction_level(uri, EcLevel::M)
+        .map_err(|_| ServerFnError::new("Failed to draw the QR code"))?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .quiet_zone(true)
+        .build())
+}
+
+fn hotp(key: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+/// The time step a TOTP code is valid for, if it matches one near `now` that is newer than
+/// `last_step`.
+fn totp_step(secret: &str, code: &str, now: DateTime<Utc>, last_step: u64) -> Option<u64> {
+    let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code: u32 = code.parse().ok()?;
+    let current = now.timestamp() / STEP_SECS;
+    (current - SKEW_STEPS..=current + SKEW_STEPS)
+        .filter(|step| *step >= 0)
+        .map(|step| step as u64)
+        .find(|step| *step > last_step && hotp(&key, *step) == code)
+}
+
+/// Codes are compared without spaces or dashes and case-insensitively.
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// New backup codes, returned in plain text together with the hashes to store.
+pub fn generate_backup_codes() -> (Vec<String>, Vec<String>) {
+    (0..BACKUP_CODES)
+        .map(|_| {
+            let code = random_hex(5);
+            let display = format!("{}-{}", &code[..5], &code[5..]);
+            (display, hash_secret(&code))
+        })
+        .unzip()
+}
+
+fn check_code(two_factor: &TwoFactor, code: &str) -> Option<SecondFactor> {
+    let code = normalize_code(code);
+    if code.len() == DIGITS as usize && code.chars().all(|c| c.is_ascii_digit()) {
+        return totp_step(&two_factor.secret, &code, Utc::now(), two_factor.last_step)
+            .map(|step| SecondFactor::Totp { step });
+    }
+    let hash = hash_secret(&code);
+    two_factor
+        .backup_codes
+        .contains(&hash)
+        .then_some(SecondFactor::Backup { hash })
+}
+
+/// Checks a code against a pending enrollment, which only accepts TOTP codes. Returns the
+/// step it was valid for.
+pub fn check_enrollment_code(two_factor: &TwoFactor, code: &str) -> Result<u64, ServerFnError> {
+    totp_step(&two_factor.secret, &normalize_code(code), Utc::now(), 0)
+        .ok_or(ServerFnError::new("Invalid authentication code"))
+}
+
+/// Verifies a TOTP or backup code for a user with 2FA enabled and uses it up, so neither can be
+/// accepted twice.
+pub async fn consume_code(db: &Database, user: &User, code: &str) -> Result<(), ServerFnError> {
+    let invalid = || ServerFnError::new("Invalid authentication code");
+    let two_factor = user
+        .two_factor
+        .as_ref()
+        .filter(|tf| tf.enabled)
+        .ok_or_else(invalid)?;
+
+    let users = db.collection::<User>("users");
+    let result = match check_code(two_factor, code).ok_or_else(invalid)? {
+        SecondFactor::Totp { step } => {
+            users
+                .update_one(
+                    doc! { "_id": user.id, "twoFactor.lastStep": { "$lt": step as i64 } },
+                    doc! { "$set": { "twoFactor.lastStep": step as i64 } },
+                )
+                .await?
+        }
+        SecondFactor::Backup { hash } => {
+            users
+                .update_one(
+                    doc! { "_id": user.id, "twoFactor.backupCodes": &hash },
+                    doc! { "$pull": { "twoFactor.backupCodes": &hash } },
+                )
+                .await?
+        }
+    };
+    // A concurrent request used the same code first.
+    if result.modified_count == 0 {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// Records that `user` passed the password check and returns the token for the second step,
+/// shaped `challengeid.secret`.
+pub async fn create_login_challenge(db: &Database, user: &User) -> Result<String, ServerFnError> {
+    let secret = random_hex(32);
+    let challenge = LoginChallenge {
+        id: ObjectId::new(),
+        user: user.id,
+        token_hash: hash_secret(&secret),
+        attempts: 0,
+        expires_at: Utc::now() + Duration::minutes(CHALLENGE_MINUTES),
+    };
+    db.collection::<LoginChallenge>("login_challenges")
+        .insert_one(&challenge)
+        .await?;
+
+    Ok(format!("{}.{}", challenge.id.to_hex(), secret))
+}
+
+/// The email of the account a pending second step belongs to, if `token` names one.
+pub async fn challenge_account(
+    db: &Database,
+    token: &str,
+) -> Result<Option<String>, ServerFnError> {
+    let Some(challenge_id) = token
+        .split_once('.')
+        .and_then(|(id, _)| ObjectId::parse_str(id).ok())
+    else {
+        return Ok(None);
+    };
+    let Some(challenge) = db
+        .collection::<LoginChallenge>("login_challenges")
+        .find_one(doc! { "_id": challenge_id })
+        .await?
+    else {
+        return Ok(None);
+    };
+    Ok(db
+        .collection::<User>("users")
+        .find_one(doc! { "_id": challenge.user })
+        .await?
+        .map(|user| user.email))
+}
+
+/// Completes the second sign-in step and returns the user it was for.
+pub async fn complete_login_challenge(
+    db: &Database,
+    token: &str,
+    code: &str,
+) -> Result<User, ServerFnError> {
+    let expired = || ServerFnError::new("Sign-in expired, please sign in again");
+    let (challenge_id, secret) = token.split_once('.').ok_or_else(expired)?;
+    let challenge_id = ObjectId::parse_str(challenge_id).map_err(|_| expired())?;
+
+    let challenges = db.collection::<LoginChallenge>("login_challenges");
+    let challenge = challenges
+        .find_one_and_update(
+            doc! {
+                "_id": challenge_id,
+                "tokenHash": hash_secret(secret),
+                "attempts": { "$lt": MAX_CHALLENGE_ATTEMPTS },
+                "expiresAt": { "$gt": Utc::now() },
+            },
+            doc! { "$inc": { "attempts": 1 } },
+        )
+        .await?
+        .ok_or_else(expired)?;
+
+    let user = db
+        .collection::<User>("users")
+        .find_one(doc! { "_id": challenge.user })
+        .await?
+        .ok_or_else(expired)?;
+    consume_code(db, &user, code).await?;
+
+    challenges.delete_one(doc! { "_id": challenge.id }).await?;
+    Ok(user)
+}
@@ -0,0 +1,160 @@
+use crate::mailer::{get_mailer, Email, Mailer};
+use crate::server::auth::model::User;
+use chrono::prelude::*;
+use chrono::Duration;
+use dioxus::prelude::ServerFnError;
+use jsonwebtoken::{encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Verification links stop working after this long.
+const VERIFY_EMAIL_HOURS: i64 = 24;
+
+/// What an emailed token authorizes. Checked on use so a token signed for one purpose cannot
+/// be replayed for another, or as an access token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTokenPurpose {
+    VerifyEmail,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailTokenClaims {
+    pub sub: String,
+    /// The address the token was sent to; the token is void once the account's address changes.
+    pub email: String,
+    pub purpose: EmailTokenPurpose,
+    pub exp: usize,
+}
+
+fn jwt_secret() -> String {
+    env::var("JWT_SECRET").expect("JWT_SECRET must be set")
+}
+
+/// Signs a token for `email`, which is the user's address or the one they are changing to.
+pub fn sign_email_token(
+    user: &User,
+    email: &str,
+    purpose: EmailTokenPurpose,
+    ttl: Duration,
+) -> Result<String, ServerFnError> {
+    let claims = EmailTokenClaims {
+        sub: user.id.to_hex(),
+        email: email.to_string(),
+        purpose,
+        exp: (Utc::now() + ttl).timestamp() as usize,
+    };
+    Ok(encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret().as_ref()),
+    )?)
+}
+
+pub fn decode_email_token(
+    token: &str,
+    purpose: EmailTokenPurpose,
+) -> Result<EmailTokenClaims, ServerFnError> {
+    let invalid = || ServerFnError::new("This link is invalid or has expired");
+    let claims = jsonwebtoken::decode::<EmailTokenClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret().as_ref()),
+        &Validation::default(),
+    )
+    .map_err(|_| invalid())?
+    .claims;
+    if claims.purpose != purpose {
+        return Err(invalid());
+    }
+    Ok(claims)
+}
+
+fn app_url() -> String {
+    env::var("APP_URL").unwrap_or_else(|_| "http://localhost:3000".into())
+}
+
+async fn send(email: Email) -> Result<(), ServerFnError> {
+    get_mailer()
+        .await
+        .map_err(|e| ServerFnError::new(format!("Mailer misconfigured: {}", e)))?
+        .send(&email)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to send email: {}", e)))
+}
+
+/// Emails `user` a link to `/verify/:token`.
+pub async fn send_verification_email(user: &User) -> Result<(), ServerFnError> {
+    let token = sign_email_token(
+        user,
+        &user.email,
+        EmailTokenPurpose::VerifyEmail,
+        Duration::hours(VERIFY_EMAIL_HOURS),
+    )?;
+    send(Email {
+        to: user.email.clone(),
+        subject: "Verify your Tripper email address".into(),
+        text: format!(
+            "Hi {},\n\nConfirm your email address by opening this link:\n\n{}/verify/{}\n\nThe link expires in {} hours. If you did not create a Tripper account, you can ignore this email.\n",
+            user.name,
+            app_url(),
+            token,
+            VERIFY_EMAIL_HOURS
+        ),
+    })
+    .await
+}
+
+/// Emails `new_email` a link to `/verify/:token` that moves the account to that address.
+pub async fn send_email_change_email(user: &User, new_email: &str) -> Result<(), ServerFnError> {
+    let token = sign_email_token(
+        user,
+        new_email,
+        EmailTokenPurpose::VerifyEmail,
+        Duration::hours(VERIFY_EMAIL_HOURS),
+    )?;
+    send(Email {
+        to: new_email.to_string(),
+        subject: "Confirm your new Tripper email address".into(),
+        text: format!(
+            "Hi {},\n\nTo use this address for your Tripper account, open this link:\n\n{}/verify/{}\n\nThe link expires in {} hours. Until then your account keeps its current address. If you did not ask for this, you can ignore this email.\n",
+            user.name,
+            app_url(),
+            token,
+            VERIFY_EMAIL_HOURS
+        ),
+    })
+    .await
+}
+
+/// Emails `user` a link to `/reset-password/:token`.
+pub async fn send_password_reset_email(user: &User, token: &str) -> Result<(), ServerFnError> {
+    send(Email {
+        to: user.email.clone(),
+        subject: "Reset your Tripper password".into(),
+        text: format!(
+            "Hi {},\n\nSomeone asked to reset the password for your Tripper account. To choose a new one, open this link:\n\n{}/reset-password/{}\n\nThe link works once and expires in an hour. Resetting your password signs you out on every device. If you did not ask for this, you can ignore this email.\n",
+            user.name,
+            app_url(),
+            token
+        ),
+    })
+    .await
+}
+
+/// Whether `REQUIRE_VERIFIED_EMAIL` restricts generation to verified accounts.
+fn verification_required() -> bool {
+    matches!(
+        env::var("REQUIRE_VERIFIED_EMAIL").as_deref(),
+        Ok("1" | "true" | "yes")
+    )
+}
+
+/// Refuses generation for unverified accounts when `REQUIRE_VERIFIED_EMAIL` is set.
+pub fn require_verified(user: &User) -> Result<(), ServerFnError> {
+    if verification_required() && !user.verified {
+        return Err(ServerFnError::new(
+            "Please verify your email address before generating content",
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,420 @@
+use crate::server::trip::model::Trip;
+use crate::server::weather::model::{DayWeather, WeatherSource};
+use chrono::prelude::*;
+use chrono::Days;
+use dioxus_logger::tracing;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OnceCell};
+
+const DEFAULT_FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+const DEFAULT_GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const FORECAST_HORIZON_DAYS: u64 = 15;
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+/// Most trips cached at once; expired entries go first, then the oldest.
+const CACHE_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+pub trait WeatherProvider {
+    async fn daily(
+        &self,
+        location: &Location,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> anyhow::Result<Vec<DayWeather>>;
+}
+
+/// Describes a WMO weather interpretation code as returned by Open-Meteo.
+fn describe_weather_code(code: u8) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 | 2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51..=57 => "Drizzle",
+        61..=67 => "Rain",
+        71..=77 => "Snow",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95..=99 => "Thunderstorms",
+        _ => "Mixed conditions",
+    }
+}
+
+pub struct OpenMeteoProvider {
+    forecast_url: String,
+    geocoding_url: String,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct GeocodingResponse {
+    #[serde(default)]
+    results: Vec<GeocodingResult>,
+}
+
+#[derive(Deserialize)]
+struct GeocodingResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct ForecastResponse {
+    daily: ForecastDaily,
+}
+
+#[derive(Deserialize)]
+struct ForecastDaily {
+    time: Vec<NaiveDate>,
+    weather_code: Vec<Option<u8>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    precipitation_sum: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_probability_max: Vec<Option<u8>>,
+}
+
+impl OpenMeteoProvider {
+    pub fn from_env() -> Self {
+        OpenMeteoProvider {
+            forecast_url: env::var("WEATHER_FORECAST_URL")
+                .unwrap_or_else(|_| DEFAULT_FORECAST_URL.to_string()),
+            geocoding_url: env::var("WEATHER_GEOCODING_URL")
+                .unwrap_or_else(|_| DEFAULT_GEOCODING_URL.to_string()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn geocode(&self, query: &str) -> anyhow::Result<Option<Location>> {
+        let response = self
+            .client
+            .get(&self.geocoding_url)
+            .query(&[("name", query), ("count", "1")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<GeocodingResponse>()
+            .await?;
+
+        Ok(response.results.into_iter().next().map(|result| Location {
+            name: result.name,
+            latitude: result.latitude,
+            longitude: result.longitude,
+        }))
+    }
+}
+
+impl WeatherProvider for OpenMeteoProvider {
+    async fn daily(
+        &self,
+        location: &Location,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> anyhow::Result<Vec<DayWeather>> {
+        let response = self
+            .client
+            .get(&self.forecast_url)
+            .query(&[
+                ("latitude", location.latitude.to_string()),
+                ("longitude", location.longitude.to_string()),
+                (
+                    "daily",
+                    "weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum,precipitation_probability_max"
+                        .to_string(),
+                ),
+                ("timezone", "auto".to_string()),
+                ("start_date", start.to_string()),
+                ("end_date", end.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ForecastResponse>()
+            .await?;
+
+        let daily = response.daily;
+        Ok(daily
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, date)| DayWeather {
+                date: *date,
+                source: WeatherSource::Forecast,
+                summary: describe_weather_code(
+                    daily
+                        .weather_code
+                        .get(i)
+                        .copied()
+                        .flatten()
+                        .unwrap_or(u8::MAX),
+                )
+                .to_string(),
+                temp_max: daily
+                    .temperature_2m_max
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(0.0),
+                temp_min: daily
+                    .temperature_2m_min
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(0.0),
+                precipitation_mm: daily
+                    .precipitation_sum
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(0.0),
+                precipitation_probability: daily
+                    .precipitation_probability_max
+                    .get(i)
+                    .copied()
+                    .flatten(),
+            })
+            .collect())
+    }
+}
+
+struct ClimateNormal {
+    city: String,
+    latitude: f64,
+    longitude: f64,
+    high: [f64; 12],
+    low: [f64; 12],
+    precip: [f64; 12],
+}
+
+/// Typical weather from the monthly climate normals bundled in `data/climate_normals.csv`.
+pub struct ClimateNormalsProvider {
+    normals: Vec<ClimateNormal>,
+}
+
+impl ClimateNormalsProvider {
+    pub fn bundled() -> Self {
+        Self::parse(include_str!("../data/climate_normals.csv"))
+    }
+
+    fn parse(csv: &str) -> Self {
+        let mut normals: Vec<ClimateNormal> = Vec::new();
+
+        for line in csv.lines().skip_while(|line| line.starts_with('#')).skip(1) {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != 17 {
+                continue;
+            }
+            let values: Vec<f64> = fields[5..].iter().filter_map(|v| v.parse().ok()).collect();
+            let Ok(values) = <[f64; 12]>::try_from(values) else {
+                continue;
+            };
+
+            let index = match normals.iter().position(|normal| normal.city == fields[0]) {
+                Some(index) => index,
+                None => {
+                    normals.push(ClimateNormal {
+                        city: fields[0].to_string(),
+                        latitude: fields[2].parse().unwrap_or(0.0),
+                        longitude: fields[3].parse().unwrap_or(0.0),
+                        high: [0.0; 12],
+                        low: [0.0; 12],
+                        precip: [0.0; 12],
+                    });
+                    normals.len() - 1
+                }
+            };
+
+            match fields[4] {
+                "high" => normals[index].high = values,
+                "low" => normals[index].low = values,
+                "precip" => normals[index].precip = values,
+                _ => {}
+            }
+        }
+
+        ClimateNormalsProvider { normals }
+    }
+
+    /// Finds the closest city within 500 km, or one whose name appears in `name`. A country
+    /// alone is not enough, since its cities can have very different climates.
+    fn nearest(&self, location: &Location) -> Option<&ClimateNormal> {
+        let by_distance = self
+            .normals
+            .iter()
+            .map(|normal| (normal, haversine_km(location, normal)))
+            .filter(|(_, distance)| *distance <= 500.0)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(normal, _)| normal);
+
+        by_distance.or_else(|| {
+            let name = location.name.to_lowercase();
+            self.normals
+                .iter()
+                .find(|normal| name.contains(&normal.city.to_lowercase()))
+        })
+    }
+}
+
+fn haversine_km(location: &Location, normal: &ClimateNormal) -> f64 {
+    if location.latitude.is_nan() || location.longitude.is_nan() {
+        return f64::INFINITY;
+    }
+    let (lat1, lat2) = (location.latitude.to_radians(), normal.latitude.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (normal.longitude - location.longitude).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    6371.0 * 2.0 * a.sqrt().asin()
+}
+
+fn days_in_month(date: NaiveDate) -> f64 {
+    let first = date.with_day(1).unwrap_or(date);
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(first);
+    (next - first).num_days().max(1) as f64
+}
+
+impl WeatherProvider for ClimateNormalsProvider {
+    async fn daily(
+        &self,
+        location: &Location,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> anyhow::Result<Vec<DayWeather>> {
+        let normal = self
+            .nearest(location)
+            .ok_or_else(|| anyhow::anyhow!("no climate normals near {}", location.name))?;
+
+        Ok(start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .map(|date| {
+                let month = date.month0() as usize;
+                let monthly_precip = normal.precip[month];
+                let summary = if monthly_precip >= 200.0 {
+                    "Rainy season, frequent heavy rain"
+                } else if monthly_precip >= 80.0 {
+                    "Showers likely"
+                } else if normal.high[month] >= 35.0 {
+                    "Very hot and mostly dry"
+                } else {
+                    "Mostly dry"
+                };
+                DayWeather {
+                    date,
+                    source: WeatherSource::Climate,
+                    summary: summary.to_string(),
+                    temp_max: normal.high[month],
+                    temp_min: normal.low[month],
+                    precipitation_mm: monthly_precip / days_in_month(date),
+                    precipitation_probability: None,
+                }
+            })
+            .collect())
+    }
+}
+
+type WeatherCache = Mutex<HashMap<String, (Instant, Vec<DayWeather>)>>;
+
+static CACHE: OnceCell<WeatherCache> = OnceCell::const_new();
+
+async fn get_cache() -> &'static WeatherCache {
+    CACHE
+        .get_or_init(|| async { Mutex::new(HashMap::new()) })
+        .await
+}
+
+static PROVIDERS: OnceCell<(OpenMeteoProvider, ClimateNormalsProvider)> = OnceCell::const_new();
+
+async fn get_providers() -> &'static (OpenMeteoProvider, ClimateNormalsProvider) {
+    PROVIDERS
+        .get_or_init(|| async {
+            (
+                OpenMeteoProvider::from_env(),
+                ClimateNormalsProvider::bundled(),
+            )
+        })
+        .await
+}
+
+/// Per-day weather for a dated trip: a live forecast for days within the forecast horizon,
+/// climate normals for the rest or when the forecast service is unavailable.
+pub async fn weather_for_trip(trip: &Trip) -> Vec<DayWeather> {
+    let (Some(start), Some(end)) = (trip.start_date, trip.end_date) else {
+        return Vec::new();
+    };
+    let destination = trip.subtitle.clone().unwrap_or_else(|| trip.title.clone());
+
+    let key = format!("{}|{}|{}", destination, start, end);
+    if let Some((fetched_at, days)) = get_cache().await.lock().await.get(&key) {
+        if fetched_at.elapsed() < CACHE_TTL {
+            return days.clone();
+        }
+    }
+
+    let (open_meteo, climate) = get_providers().await;
+
+    let query = destination.split(',').next().unwrap_or(&destination).trim();
+    let location = match open_meteo.geocode(query).await {
+        Ok(Some(location)) => location,
+        Ok(None) | Err(_) => Location {
+            name: destination.clone(),
+            latitude: f64::NAN,
+            longitude: f64::NAN,
+        },
+    };
+
+    let today = Utc::now().date_naive();
+    let horizon = today + Days::new(FORECAST_HORIZON_DAYS);
+    let mut forecast = Vec::new();
+    if !location.latitude.is_nan() && end >= today && start <= horizon {
+        match open_meteo
+            .daily(&location, start.max(today), end.min(horizon))
+            .await
+        {
+            Ok(days) => forecast = days,
+            Err(e) => tracing::error!("Weather forecast failed for {}: {}", location.name, e),
+        }
+    }
+
+    let typical = climate
+        .daily(&location, start, end)
+        .await
+        .unwrap_or_default();
+
+    let days: Vec<DayWeather> = start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .filter_map(|date| {
+            forecast
+                .iter()
+                .chain(typical.iter())
+                .find(|day| day.date == date)
+                .cloned()
+        })
+        .collect();
+
+    let mut cache = get_cache().await.lock().await;
+    cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < CACHE_TTL);
+    if cache.len() >= CACHE_CAPACITY {
+        if let Some(oldest) = cache
+            .iter()
+            .min_by_key(|(_, (fetched_at, _))| *fetched_at)
+            .map(|(key, _)| key.clone())
+        {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(key, (Instant::now(), days.clone()));
+    days
+}